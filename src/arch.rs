@@ -0,0 +1,208 @@
+//! The CPU architecture of an interpreter
+
+use crate::{parse_err, query, Commander, PyResult, PythonConfig};
+
+use std::fmt;
+
+/// The CPU architecture that an interpreter runs on
+///
+/// See [`PythonConfig::architecture`](struct.PythonConfig.html#method.architecture).
+/// This is the interpreter's architecture, not the machine's: a 32-bit
+/// interpreter on a 64-bit machine is `X86`, not `X86_64`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Arch {
+    /// 32-bit x86, like `i686`
+    X86,
+    /// 64-bit x86, also called `AMD64`
+    X86_64,
+    /// 64-bit ARM, also called `arm64`
+    Aarch64,
+    /// 32-bit ARM, like `armv7l`
+    Arm,
+    /// 64-bit PowerPC, big endian
+    PowerPc64,
+    /// 64-bit PowerPC, little endian
+    PowerPc64Le,
+    /// IBM Z
+    S390x,
+    /// 64-bit RISC-V
+    Riscv64,
+    /// A macOS universal2 build, which runs natively on both `x86_64` and
+    /// `arm64`
+    Universal2,
+    /// Another architecture, with the name from `platform.machine()`
+    Other(String),
+}
+
+impl Arch {
+    /// Names the interpreter's architecture from `platform.machine()`, which
+    /// describes the machine, and its pointer width
+    pub(crate) fn from_machine(machine: &str, pointer_width: u32) -> Self {
+        match (machine.to_ascii_lowercase().as_str(), pointer_width) {
+            ("x86_64", 32)
+            | ("amd64", 32)
+            | ("i386", _)
+            | ("i486", _)
+            | ("i586", _)
+            | ("i686", _)
+            | ("x86", _) => Arch::X86,
+            ("x86_64", _) | ("amd64", _) => Arch::X86_64,
+            ("aarch64", 32) | ("arm64", 32) => Arch::Arm,
+            ("aarch64", _) | ("arm64", _) => Arch::Aarch64,
+            ("ppc64", _) => Arch::PowerPc64,
+            ("ppc64le", _) => Arch::PowerPc64Le,
+            ("s390x", _) => Arch::S390x,
+            ("riscv64", _) => Arch::Riscv64,
+            (arm, _) if arm.starts_with("arm") => Arch::Arm,
+            _ => Arch::Other(machine.to_owned()),
+        }
+    }
+
+    /// Returns the values of Cargo's `CARGO_CFG_TARGET_ARCH` that this
+    /// interpreter can load, like `["x86_64"]`, or `["x86_64", "aarch64"]`
+    /// for a universal2 build
+    ///
+    /// `Other` architectures return nothing.
+    pub fn rust_target_arches(&self) -> &'static [&'static str] {
+        match self {
+            Arch::X86 => &["x86"],
+            Arch::X86_64 => &["x86_64"],
+            Arch::Aarch64 => &["aarch64"],
+            Arch::Arm => &["arm"],
+            Arch::PowerPc64 | Arch::PowerPc64Le => &["powerpc64"],
+            Arch::S390x => &["s390x"],
+            Arch::Riscv64 => &["riscv64"],
+            Arch::Universal2 => &["x86_64", "aarch64"],
+            Arch::Other(_) => &[],
+        }
+    }
+
+    /// Returns `true` if the interpreter can load code built for the Rust
+    /// `target_arch`, like the `CARGO_CFG_TARGET_ARCH` of a build script
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// // build.rs
+    /// let arch = PythonConfig::new().architecture().unwrap();
+    /// let target = std::env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    /// if !arch.supports_target_arch(&target) {
+    ///     panic!("Python is {}, but we're building for {}", arch, target);
+    /// }
+    /// ```
+    pub fn supports_target_arch(&self, target_arch: &str) -> bool {
+        self.rust_target_arches().contains(&target_arch)
+    }
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Arch::X86 => "x86",
+            Arch::X86_64 => "x86_64",
+            Arch::Aarch64 => "aarch64",
+            Arch::Arm => "arm",
+            Arch::PowerPc64 => "ppc64",
+            Arch::PowerPc64Le => "ppc64le",
+            Arch::S390x => "s390x",
+            Arch::Riscv64 => "riscv64",
+            Arch::Universal2 => "universal2",
+            Arch::Other(machine) => machine,
+        })
+    }
+}
+
+/// Parses a pointer width line, like `64`
+fn parse_pointer_width(line: &str, resp: &str) -> PyResult<u32> {
+    line.parse()
+        .map_err(|_| parse_err("expected the pointer width in bits", resp))
+}
+
+impl<C: Commander> PythonConfig<C> {
+    /// Returns the interpreter's CPU architecture, like `x86_64`, or
+    /// `universal2` for a macOS build that runs on both Intel and Apple
+    /// silicon
+    ///
+    /// A build script can compare the architecture to the Rust target, and
+    /// fail early, instead of failing to link. See
+    /// [`Arch::supports_target_arch`](enum.Arch.html#method.supports_target_arch).
+    pub fn architecture(&self) -> PyResult<Arch> {
+        let resp = self.script(query::ARCHITECTURE)?;
+        let lines: Vec<&str> = resp.lines().collect();
+        match lines[..] {
+            [_, _, platform] if platform.ends_with("-universal2") => Ok(Arch::Universal2),
+            [machine, pointer_width, _] => Ok(Arch::from_machine(
+                machine,
+                parse_pointer_width(pointer_width, &resp)?,
+            )),
+            _ => Err(parse_err(
+                "expected the machine, the pointer width, and the platform",
+                &resp,
+            )),
+        }
+    }
+
+    /// Returns the size of a pointer, in bits, for the interpreter: `32`
+    /// or `64`
+    ///
+    /// A 32-bit interpreter can't load a 64-bit extension module, even on
+    /// a 64-bit machine.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// // build.rs
+    /// let width = PythonConfig::new().pointer_width().unwrap();
+    /// let target = std::env::var("CARGO_CFG_TARGET_POINTER_WIDTH").unwrap();
+    /// assert_eq!(width.to_string(), target, "Python's bitness doesn't match the target");
+    /// ```
+    pub fn pointer_width(&self) -> PyResult<u32> {
+        if let Some(ref info) = self.info {
+            return Ok(info.pointer_width());
+        }
+        let resp = self.script(query::POINTER_WIDTH)?;
+        parse_pointer_width(&resp, &resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Arch;
+    use crate::PythonConfig;
+
+    #[test]
+    fn machines() {
+        assert_eq!(Arch::from_machine("AMD64", 64), Arch::X86_64);
+        assert_eq!(Arch::from_machine("AMD64", 32), Arch::X86);
+        assert_eq!(Arch::from_machine("arm64", 64), Arch::Aarch64);
+        assert_eq!(Arch::from_machine("aarch64", 32), Arch::Arm);
+        assert_eq!(Arch::from_machine("armv7l", 32), Arch::Arm);
+        assert_eq!(Arch::from_machine("ppc64le", 64), Arch::PowerPc64Le);
+        assert_eq!(
+            Arch::from_machine("loongarch64", 64),
+            Arch::Other("loongarch64".to_owned())
+        );
+        assert!(Arch::Universal2.supports_target_arch("aarch64"));
+        assert!(!Arch::X86.supports_target_arch("x86_64"));
+    }
+
+    #[test]
+    fn live() {
+        let cfg = PythonConfig::new();
+        let arch = cfg.architecture().unwrap();
+        assert!(
+            arch.supports_target_arch(std::env::consts::ARCH),
+            "{}",
+            arch
+        );
+        let width = cfg.pointer_width().unwrap();
+        assert_eq!(width as usize, std::mem::size_of::<usize>() * 8);
+        let probed = PythonConfig::interpreter(cfg.executable().unwrap()).unwrap();
+        assert_eq!(probed.pointer_width().unwrap(), width);
+    }
+}
@@ -2,12 +2,58 @@
 //! the python-config-rs crate.
 //!
 //! This is Python 3 only.
+//!
+//! Like upstream, `--embed` is only accepted for Python 3.8 and newer;
+//! older interpreters exit with code `4`. It changes `--libs` and
+//! `--ldflags` to include `-lpython`, for applications that embed Python.
+//!
+//! # Extensions (not in the usage message)
+//!
+//! - `--version` prints the version and path of the interpreter; see [`PythonConfig::version_info`](../python_config/struct.PythonConfig.html#method.version_info).
+//! - `--json` prints the requested values as one JSON object, keyed by the flag without its dashes, like `{"prefix": "/usr"}`.
+//! - `--all` requests every value.
+//! - `--rpath` makes `--ldflags` link libpython, and add its directory to the runtime search path; see [`PythonConfig::ldflags_with_rpath`](../python_config/struct.PythonConfig.html#method.ldflags_with_rpath).
+//! - `--filter=<policy>` filters `--cflags` and `--ldflags` with `includes`, `no-optimization`, or `raw`; see [`FilterPolicy`](../python_config/enum.FilterPolicy.html).
+//! - `--dump` prints every value as a `key='value'` line, quoted for `eval`; see [`PythonConfig::snapshot`](../python_config/struct.PythonConfig.html#method.snapshot).
+//! - `--write-pc <dir>` writes pkg-config files into `dir`, and prints their paths; see [`PythonConfig::pkg_config`](../python_config/struct.PythonConfig.html#method.pkg_config).
+//! - `--cmake` prints the variables of CMake's `FindPython3`; see [`PythonConfig::cmake_snippet`](../python_config/struct.PythonConfig.html#method.cmake_snippet).
+//! - `--meson` prints a Meson machine file; see [`PythonConfig::meson_machine_file`](../python_config/struct.PythonConfig.html#method.meson_machine_file).
+//! - `--list-interpreters` prints a table of the interpreters that we find; see [`discover::all`](../python_config/discover/fn.all.html).
+//! - `--doctor` checks that you can build against the interpreter; see [`PythonConfig::diagnose`](../python_config/struct.PythonConfig.html#method.diagnose).
+//! - `--interpreter <path>` selects the interpreter, before `PYTHON_CONFIG_INTERPRETER`; see [`PythonConfig::interpreter`](../python_config/struct.PythonConfig.html#method.interpreter).
+//! - `--generate-completions <shell>` prints a completion script for `bash`, `zsh`, or `fish`.
+//! - `--quiet` silences the error messages.
+//! - `diff <python-a> <python-b>` prints a table that compares two interpreters, highlighting the rows that differ; see [`PythonConfigData::diff`](../python_config/struct.PythonConfigData.html#method.diff).
+//!
+//! `--doctor` exits with `0` if every check passed, `2` if the
+//! interpreter doesn't run, or `3` if another check failed. `--filter`
+//! doesn't combine with `--embed` or `--rpath`.
+//!
+//! # Exit codes
+//!
+//! Wrapper scripts may branch on the exit code:
+//!
+//! - `0`: success
+//! - `1`: usage error (unknown flag, or no flags)
+//! - `2`: the Python interpreter was not found, or it's not Python
+//! - `3`: the interpreter was found, but a query failed
+//! - `4`: a flag is not supported for this Python version
+//!
+//! On failure, we print a single line to stderr, unless you pass
+//! `--quiet`. The line resembles
+//!
+//! ```text
+//! python3-config: error: --extension-suffix: this function is only available for Python 3
+//! ```
 
-use python_config::{PyResult, PythonConfig};
+use python_config::{
+    discover, Check, Error, FilterPolicy, LinkMode, PyResult, PythonConfig, PythonConfigData,
+};
 
 use std::collections::{HashMap, HashSet};
 use std::env;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
 use std::process;
 
 type Handler = fn(&PythonConfig) -> PyResult<String>;
@@ -18,18 +64,197 @@ static VALID_OPTS_TO_HANDLER: &[(&str, Handler)] = &[
     ("--includes", PythonConfig::includes),
     ("--libs", PythonConfig::libs),
     ("--cflags", PythonConfig::cflags),
-    ("--ldflags", PythonConfig::ldflags),
+    ("--ldflags", ldflags),
     ("--extension-suffix", PythonConfig::extension_suffix),
     ("--help", not_implemented), // unreachable; we check for help and handle it manually
     ("--abiflags", PythonConfig::abi_flags),
     ("--configdir", PythonConfig::config_dir),
 ];
 
-fn exit_with_usage(program: &str, code: i32) {
-    let flags: Vec<&'static str> = VALID_OPTS_TO_HANDLER
+/// Replaces the handlers in `VALID_OPTS_TO_HANDLER` when the user
+/// passes `--embed`
+static EMBED_OPTS_TO_HANDLER: &[(&str, Handler)] = &[
+    ("--libs", PythonConfig::libs_embed),
+    ("--ldflags", ldflags_embed),
+];
+
+/// Replaces the handlers in `VALID_OPTS_TO_HANDLER` when the user
+/// passes `--rpath`
+static RPATH_OPTS_TO_HANDLER: &[(&str, Handler)] =
+    &[("--ldflags", PythonConfig::ldflags_with_rpath)];
+
+type FilteredHandler = fn(&PythonConfig, FilterPolicy) -> PyResult<Vec<String>>;
+
+/// Replaces the handlers in `VALID_OPTS_TO_HANDLER` when the user
+/// passes `--filter`
+static FILTERED_OPTS_TO_HANDLER: &[(&str, FilteredHandler)] = &[
+    ("--cflags", PythonConfig::cflags_filtered),
+    ("--ldflags", PythonConfig::ldflags_filtered),
+];
+
+/// The `--embed` flag, which modifies other flags
+const EMBED: &str = "--embed";
+/// The `--rpath` flag, which modifies `--ldflags`
+const RPATH: &str = "--rpath";
+/// Filters `--cflags` and `--ldflags` with the policy that follows the flag
+const FILTER: &str = "--filter";
+/// The policies that `--filter` accepts
+const POLICIES: &str = "includes no-optimization raw";
+/// Prints the values as a JSON object
+const JSON: &str = "--json";
+/// Requests every value
+const ALL: &str = "--all";
+
+/// Writes pkg-config files into the directory that follows the flag
+const WRITE_PC: &str = "--write-pc";
+
+/// Lists the interpreters that we can find
+const LIST_INTERPRETERS: &str = "--list-interpreters";
+
+/// Prints every value as shell assignments
+const DUMP: &str = "--dump";
+
+/// Prints CMake code that describes the interpreter
+const CMAKE: &str = "--cmake";
+
+/// Prints a Meson machine file that describes the interpreter
+const MESON: &str = "--meson";
+
+/// Silences the error messages
+const QUIET: &str = "--quiet";
+
+/// Checks that we can build against the interpreter
+const DOCTOR: &str = "--doctor";
+
+/// Selects the interpreter with the path that follows the flag
+const INTERPRETER: &str = "--interpreter";
+
+/// Prints a completion script for the shell that follows the flag
+const GENERATE_COMPLETIONS: &str = "--generate-completions";
+
+/// Prints the version and path of the interpreter
+const VERSION: &str = "--version";
+
+/// The shells that `--generate-completions` accepts
+const SHELLS: &str = "bash zsh fish";
+
+/// The flags that we accept beyond those in `VALID_OPTS_TO_HANDLER`
+static EXTENSIONS: &[&str] = &[
+    EMBED,
+    RPATH,
+    FILTER,
+    JSON,
+    ALL,
+    QUIET,
+    VERSION,
+    DUMP,
+    CMAKE,
+    MESON,
+    DOCTOR,
+    LIST_INTERPRETERS,
+    WRITE_PC,
+    INTERPRETER,
+    GENERATE_COMPLETIONS,
+];
+
+/// Every flag that we accept, in the order that we complete them
+fn known_flags() -> impl Iterator<Item = &'static str> {
+    VALID_OPTS_TO_HANDLER
+        .iter()
+        .map(|&(flag, _)| flag)
+        .chain(EXTENSIONS.iter().copied())
+}
+
+/// Describes the flags in the completion scripts
+static DESCRIPTIONS: &[(&str, &str)] = &[
+    ("--prefix", "print the installation prefix"),
+    (
+        "--exec-prefix",
+        "print the prefix of platform-specific files",
+    ),
+    ("--includes", "print the include flags"),
+    ("--libs", "print the libraries to link"),
+    ("--cflags", "print the compiler flags"),
+    ("--ldflags", "print the linker flags"),
+    (
+        "--extension-suffix",
+        "print the suffix of extension modules",
+    ),
+    ("--help", "print the usage message"),
+    ("--abiflags", "print the ABI flags"),
+    ("--configdir", "print the directory of the build Makefile"),
+    (EMBED, "link libpython, to embed Python in an application"),
+    (RPATH, "link libpython, and add its directory to the rpath"),
+    (
+        FILTER,
+        "filter the flags: includes, no-optimization, or raw",
+    ),
+    (JSON, "print the values as a JSON object"),
+    (ALL, "print every value"),
+    (QUIET, "silence error messages"),
+    (VERSION, "print the version and path of the interpreter"),
+    (DUMP, "print every value as shell assignments"),
+    (CMAKE, "print the FindPython3 variables for CMake"),
+    (MESON, "print a Meson machine file"),
+    (DOCTOR, "check that you can build against the interpreter"),
+    (LIST_INTERPRETERS, "list the interpreters that we can find"),
+    (WRITE_PC, "write pkg-config files into a directory"),
+    (INTERPRETER, "the Python interpreter to describe"),
+    (
+        GENERATE_COMPLETIONS,
+        "print a completion script for a shell",
+    ),
+];
+
+/// Returns every flag that we accept, with its description
+fn completions() -> impl Iterator<Item = (&'static str, &'static str)> {
+    known_flags().map(|flag| {
+        let description = DESCRIPTIONS
+            .iter()
+            .find(|&&(known, _)| known == flag)
+            .map_or("", |&(_, description)| description);
+        (flag, description)
+    })
+}
+
+/// Renders `value` as a JSON string
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Quotes `value` for a POSIX shell
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Returns `true` if `py` is new enough to accept `--embed`. Upstream
+/// added the flag in Python 3.8.
+fn supports_embed(py: &PythonConfig) -> PyResult<bool> {
+    py.version_info().map(|version| version.at_least(3, 8))
+}
+
+fn exit_with_usage(program: &str, code: i32, embed: bool) {
+    let mut flags: Vec<&'static str> = VALID_OPTS_TO_HANDLER
         .iter()
         .map(|(flag, _)| *flag)
         .collect();
+    if embed {
+        flags.push(EMBED);
+    }
     let flags = flags.join("|");
 
     // Python3.7 python3-config on macos always prints
@@ -55,52 +280,602 @@ fn exit_with_usage(program: &str, code: i32) {
     process::exit(code);
 }
 
+/// The Python interpreter was not found, or it's not Python
+const EXIT_INTERPRETER_NOT_FOUND: i32 = 2;
+/// The interpreter was found, but a query failed
+const EXIT_QUERY_FAILED: i32 = 3;
+/// A flag is not supported for this Python version
+const EXIT_UNSUPPORTED_FLAG: i32 = 4;
+
+/// Describes the error that occured while handling `flag` on stderr, then
+/// exits with a code that describes the class of error.
+///
+/// With `--quiet`, we only exit.
+fn exit_with_error(flag: &str, err: Error) -> ! {
+    let (code, message) = match err {
+        // The hint only helps those who didn't pick the interpreter
+        Error::InterpreterNotFound { .. } if env::args().any(|arg| is_interpreter_flag(&arg)) => {
+            (EXIT_INTERPRETER_NOT_FOUND, err.to_string())
+        }
+        Error::InterpreterNotFound { .. } => (
+            EXIT_INTERPRETER_NOT_FOUND,
+            format!(
+                "{}; pass --interpreter, or set PYTHON_CONFIG_INTERPRETER, to use another interpreter",
+                err
+            ),
+        ),
+        Error::NotAPython { .. } => (EXIT_INTERPRETER_NOT_FOUND, err.to_string()),
+        Error::Python3Only | Error::UnsupportedVersion { .. } => {
+            (EXIT_UNSUPPORTED_FLAG, format!("{}: {}", flag, err))
+        }
+        // Only the last line of a traceback fits on one line, and it's
+        // usually the most useful.
+        Error::Interpreter { ref stderr, .. } => {
+            let last = stderr.lines().last().unwrap_or("the interpreter failed");
+            (EXIT_QUERY_FAILED, format!("{}: {}", flag, last))
+        }
+        _ => (EXIT_QUERY_FAILED, format!("{}: {}", flag, err)),
+    };
+    if !env::args().skip(1).any(|arg| arg == QUIET) {
+        eprintln!("python3-config: error: {}", message);
+    }
+    process::exit(code);
+}
+
+/// Prints the version and path of the interpreter described by `py`
+fn print_version(py: &PythonConfig) -> io::Result<()> {
+    let version = py
+        .version_info()
+        .unwrap_or_else(|err| exit_with_error(VERSION, err));
+    let executable = py
+        .executable()
+        .unwrap_or_else(|err| exit_with_error(VERSION, err));
+    writeln!(io::stdout(), "Python {} ({})", version, executable)
+}
+
+/// Prints every value that we know about `py` as `key='value'` lines
+fn dump(py: &PythonConfig) -> io::Result<()> {
+    let data = py
+        .snapshot()
+        .unwrap_or_else(|err| exit_with_error(DUMP, err));
+    let config_var = |name| data.config_var(name).unwrap_or("").to_owned();
+    let values = [
+        ("prefix", data.prefix.clone()),
+        ("exec_prefix", data.exec_prefix.clone()),
+        ("includes", data.includes.clone()),
+        ("libs", data.libs.clone()),
+        ("cflags", data.cflags.clone()),
+        ("ldflags", data.ldflags.clone()),
+        ("ext_suffix", data.extension_suffix.clone()),
+        ("abiflags", data.abi_flags.clone()),
+        ("configdir", data.config_dir.clone()),
+        ("libdir", config_var("LIBDIR")),
+        ("soabi", config_var("SOABI")),
+        ("version", data.version.to_string()),
+    ];
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for (key, value) in &values {
+        writeln!(stdout, "{}={}", key, shell_quote(value))?;
+    }
+    Ok(())
+}
+
+/// Prints the CMake snippet, or the Meson machine file, for `py`
+fn build_system(py: &PythonConfig, flag: &str) -> io::Result<()> {
+    let text = if flag == CMAKE {
+        py.cmake_snippet()
+    } else {
+        py.meson_machine_file()
+    }
+    .unwrap_or_else(|err| exit_with_error(flag, err));
+    write!(io::stdout(), "{}", text)
+}
+
+/// Prints the results of diagnosing `py`, then exits with a code that
+/// describes the first failed check
+fn doctor(py: &PythonConfig) -> io::Result<()> {
+    let diagnostics = py.diagnose();
+    write!(io::stdout(), "{}", diagnostics)?;
+    let failed = diagnostics.failures().next().map(|failure| failure.check);
+    match failed {
+        None => Ok(()),
+        Some(Check::Interpreter) => process::exit(EXIT_INTERPRETER_NOT_FOUND),
+        Some(_) => process::exit(EXIT_QUERY_FAILED),
+    }
+}
+
+/// Writes the pkg-config files for `py` into `dir`, printing each path
+fn write_pc(py: &PythonConfig, dir: &str) -> io::Result<()> {
+    let files = py
+        .pkg_config()
+        .unwrap_or_else(|err| exit_with_error(WRITE_PC, err));
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for file in files {
+        let path = file
+            .write_to(dir)
+            .unwrap_or_else(|err| exit_with_error(WRITE_PC, Error::IO(err)));
+        writeln!(stdout, "{}", path.display())?;
+    }
+    Ok(())
+}
+
+/// Compares the configurations of the two interpreters in `paths`
+fn diff(program: &str, paths: &[String]) -> io::Result<()> {
+    let (left, right) = match paths {
+        [left, right] => (left, right),
+        _ => {
+            eprintln!("Usage: {} diff <python-a> <python-b>", program);
+            process::exit(1);
+        }
+    };
+
+    let snapshots: Vec<PythonConfigData> = [left, right]
+        .iter()
+        .map(|path| {
+            PythonConfig::interpreter(path)
+                .and_then(|py| py.snapshot())
+                .unwrap_or_else(|err| exit_with_error(path, err))
+        })
+        .collect();
+    let differences = snapshots[0].diff(&snapshots[1]);
+
+    let mut rows = vec![vec![String::from("field"), left.clone(), right.clone()]];
+    for ((name, left), (_, right)) in snapshots[0].fields().into_iter().zip(snapshots[1].fields()) {
+        rows.push(vec![name.to_owned(), left, right]);
+    }
+    write_table(&rows, |row| differences.contains(&row[0]))
+}
+
+/// Prints `rows` as a table with aligned columns. The first row is the
+/// header. Rows for which `highlight` returns `true` are highlighted when
+/// printing to a terminal (unless `NO_COLOR` is set).
+fn write_table<F>(rows: &[Vec<String>], highlight: F) -> io::Result<()>
+where
+    F: Fn(&[String]) -> bool,
+{
+    let columns = rows.first().map_or(0, Vec::len);
+    let mut widths = vec![0; columns];
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    let stdout = io::stdout();
+    let color = stdout.is_terminal() && env::var_os("NO_COLOR").is_none();
+    let mut stdout = stdout.lock();
+    for (idx, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(value, &width)| format!("{:width$}", value, width = width))
+            .collect();
+        let line = cells.join(" | ");
+        let line = line.trim_end();
+        if color && idx > 0 && highlight(row) {
+            writeln!(stdout, "\x1b[31m{}\x1b[0m", line)?;
+        } else {
+            writeln!(stdout, "{}", line)?;
+        }
+        if idx == 0 {
+            let rule: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+            writeln!(stdout, "{}", rule.join("-+-"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Lists the interpreters that we discover, one row per interpreter.
+/// Interpreters that fail to run are highlighted.
+fn list_interpreters() -> io::Result<()> {
+    let paths: Vec<PathBuf> = discover::all()
+        .into_iter()
+        .map(|found| found.path)
+        .collect();
+    let mut rows = vec![[
+        "interpreter",
+        "version",
+        "prefix",
+        "abiflags",
+        "include dirs",
+    ]
+    .iter()
+    .map(|heading| heading.to_string())
+    .collect::<Vec<_>>()];
+    let mut failed = HashSet::new();
+    for report in python_config::compare(&paths) {
+        let interpreter = report.interpreter.display().to_string();
+        match report.result {
+            Ok(summary) => {
+                let include_dirs: Vec<String> = summary
+                    .include_dirs
+                    .iter()
+                    .map(|dir| dir.display().to_string())
+                    .collect();
+                rows.push(vec![
+                    interpreter,
+                    summary.version.to_string(),
+                    summary.prefix.display().to_string(),
+                    summary.abi_flags,
+                    include_dirs.join(" "),
+                ]);
+            }
+            Err(err) => {
+                failed.insert(interpreter.clone());
+                rows.push(vec![interpreter, format!("error: {}", err)]);
+            }
+        }
+    }
+    write_table(&rows, |row| failed.contains(&row[0]))
+}
+
+/// Like `PythonConfig::ldflags`, but links a macOS framework build's
+/// libpython with `-L` and `-l`, like upstream
+fn ldflags(py: &PythonConfig) -> PyResult<String> {
+    if py.version_info()?.at_least(3, 8) {
+        py.ldflags()
+    } else {
+        ldflags_embed(py)
+    }
+}
+
+/// Like `PythonConfig::ldflags_embed`, but links a macOS framework build's
+/// libpython from `LIBPL`, like upstream, rather than as a framework
+fn ldflags_embed(py: &PythonConfig) -> PyResult<String> {
+    // Framework builds aren't shared, and upstream searches LIBPL, where
+    // `libpythonX.Y.a` links to the framework's library.
+    if py.python_framework()?.is_some() {
+        py.ldflags_for(LinkMode::Static)
+    } else {
+        py.ldflags_embed()
+    }
+}
+
+/// Returns `true` if `arg` is `--interpreter`, or `--interpreter=<path>`
+fn is_interpreter_flag(arg: &str) -> bool {
+    arg == INTERPRETER || arg.starts_with("--interpreter=")
+}
+
+/// Removes `--interpreter <path>`, or `--interpreter=<path>`, from `argv`,
+/// returning the path
+fn take_interpreter(program: &str, argv: &mut Vec<String>) -> Option<String> {
+    let idx = argv.iter().position(|arg| is_interpreter_flag(arg))?;
+    let flag = argv.remove(idx);
+    match flag.split_once('=') {
+        Some((_, path)) if !path.is_empty() => Some(path.to_owned()),
+        None if idx < argv.len() => Some(argv.remove(idx)),
+        _ => {
+            eprintln!("Usage: {} {} <path> ...", program, INTERPRETER);
+            process::exit(1);
+        }
+    }
+}
+
+/// Removes `--filter=<policy>`, or `--filter <policy>`, from `argv`, and
+/// returns the policy, or exits with the usage if it's missing or unknown
+fn take_filter(program: &str, argv: &mut Vec<String>) -> Option<FilterPolicy> {
+    let idx = argv
+        .iter()
+        .position(|arg| arg == FILTER || arg.starts_with("--filter="))?;
+    let flag = argv.remove(idx);
+    let policy = match flag.split_once('=') {
+        Some((_, policy)) => Some(policy.to_owned()),
+        None if idx < argv.len() => Some(argv.remove(idx)),
+        None => None,
+    };
+    match policy.map(|policy| policy.parse()) {
+        Some(Ok(policy)) => Some(policy),
+        _ => {
+            eprintln!(
+                "Usage: {} {}=<{}> --cflags",
+                program,
+                FILTER,
+                POLICIES.replace(' ', "|")
+            );
+            process::exit(1);
+        }
+    }
+}
+
+/// Prints a completion script for the shell that follows
+/// `--generate-completions` in `argv`
+fn generate_completions(program: &str, argv: &[String]) -> io::Result<()> {
+    let name = std::path::Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("python3-config");
+    let shell = argv
+        .iter()
+        .skip_while(|arg| *arg != GENERATE_COMPLETIONS)
+        .nth(1);
+    let script = match shell.map(String::as_str) {
+        Some("bash") => bash_completions(name),
+        Some("zsh") => zsh_completions(name),
+        Some("fish") => fish_completions(name),
+        _ => {
+            eprintln!(
+                "Usage: {} {} <{}>",
+                program,
+                GENERATE_COMPLETIONS,
+                SHELLS.replace(' ', "|")
+            );
+            process::exit(1);
+        }
+    };
+    write!(io::stdout(), "{}", script)
+}
+
+fn bash_completions(name: &str) -> String {
+    let function: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let words: Vec<&str> = known_flags().collect();
+    format!(
+        "_{function}() {{
+    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\" prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"
+    case \"$prev\" in
+        {interpreter}) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;
+        {write_pc}) COMPREPLY=($(compgen -d -- \"$cur\")); return ;;
+        {filter}) COMPREPLY=($(compgen -W \"{policies}\" -- \"$cur\")); return ;;
+        {generate}) COMPREPLY=($(compgen -W \"{shells}\" -- \"$cur\")); return ;;
+    esac
+    if [ \"$COMP_CWORD\" -eq 1 ]; then
+        COMPREPLY=($(compgen -W \"diff {words}\" -- \"$cur\"))
+    else
+        COMPREPLY=($(compgen -W \"{words}\" -- \"$cur\"))
+    fi
+}}
+complete -F _{function} {name}
+",
+        function = function,
+        interpreter = INTERPRETER,
+        write_pc = WRITE_PC,
+        filter = FILTER,
+        policies = POLICIES,
+        generate = GENERATE_COMPLETIONS,
+        shells = SHELLS,
+        words = words.join(" "),
+        name = name,
+    )
+}
+
+fn zsh_completions(name: &str) -> String {
+    let mut script = format!("#compdef {}\n\n_arguments \\\n", name);
+    for (flag, description) in completions() {
+        let action = match flag {
+            INTERPRETER => String::from(":interpreter:_files"),
+            WRITE_PC => String::from(":directory:_files -/"),
+            FILTER => format!(":policy:({})", POLICIES),
+            GENERATE_COMPLETIONS => format!(":shell:({})", SHELLS),
+            _ => String::new(),
+        };
+        script.push_str(&format!("    '{}[{}]{}' \\\n", flag, description, action));
+    }
+    script.push_str("    '1:: :(diff)'\n");
+    script
+}
+
+fn fish_completions(name: &str) -> String {
+    let mut script = format!(
+        "complete -c {} -n __fish_use_subcommand -a diff -d 'compare two interpreters'\n",
+        name
+    );
+    for (flag, description) in completions() {
+        let action = match flag {
+            INTERPRETER => String::from(" -r -F"),
+            WRITE_PC => String::from(" -r -a '(__fish_complete_directories)'"),
+            FILTER => format!(" -r -f -a '{}'", POLICIES),
+            GENERATE_COMPLETIONS => format!(" -r -f -a '{}'", SHELLS),
+            _ => String::new(),
+        };
+        script.push_str(&format!(
+            "complete -c {} -l {}{} -d '{}'\n",
+            name,
+            flag.trim_start_matches('-'),
+            action,
+            description
+        ));
+    }
+    script
+}
+
 fn not_implemented(_: &PythonConfig) -> PyResult<String> {
     panic!("handler not implemented");
 }
 
-fn main() -> io::Result<()> {
+fn main() {
+    if let Err(err) = run() {
+        // Like other Unix tools, stop quietly when the reader goes away,
+        // as in `python3-config --dump | head -1`.
+        if err.kind() == io::ErrorKind::BrokenPipe {
+            process::exit(0);
+        }
+        exit_with_error("stdout", Error::IO(err));
+    }
+}
+
+fn run() -> io::Result<()> {
+    let mut argv: Vec<String> = env::args().collect();
+    let program = argv
+        .first()
+        .cloned()
+        .expect("no first argument representing the program path");
+    let interpreter = take_interpreter(&program, &mut argv);
+    let filter = take_filter(&program, &mut argv);
+    let config = || match interpreter {
+        Some(ref path) => {
+            PythonConfig::interpreter(path).unwrap_or_else(|err| exit_with_error(INTERPRETER, err))
+        }
+        None => PythonConfig::new(),
+    };
+    let has = |flag: &str| argv.iter().skip(1).any(|arg| arg == flag);
+
+    if argv.get(1).map(String::as_str) == Some("diff") {
+        return diff(&program, &argv[2..]);
+    }
+
+    // The usage only needs the interpreter to decide whether to mention
+    // `--embed`, so a missing interpreter doesn't hide the help.
+    let usage_embed = || {
+        let py = match interpreter {
+            Some(ref path) => PythonConfig::interpreter(path).ok(),
+            None => Some(PythonConfig::new()),
+        };
+        py.is_some_and(|py| supports_embed(&py).unwrap_or(false))
+    };
+
+    // Check every flag before choosing a mode, so that no mode ignores an
+    // unknown flag
+    let mut args = argv.iter().skip(1);
+    while let Some(arg) = args.next() {
+        if !known_flags().any(|flag| flag == arg) {
+            exit_with_usage(&program, 1, usage_embed());
+        } else if arg == WRITE_PC || arg == GENERATE_COMPLETIONS {
+            args.next();
+        }
+    }
+
+    if has(GENERATE_COMPLETIONS) {
+        return generate_completions(&program, &argv);
+    }
+
+    if has(LIST_INTERPRETERS) {
+        return list_interpreters();
+    }
+
+    if has(DUMP) {
+        return dump(&config());
+    }
+
+    for flag in [CMAKE, MESON] {
+        if has(flag) {
+            return build_system(&config(), flag);
+        }
+    }
+
+    if has(DOCTOR) {
+        return doctor(&config());
+    }
+
+    if has(VERSION) {
+        return print_version(&config());
+    }
+
+    let mut args = argv.iter().skip(1);
+    if args.any(|arg| arg == WRITE_PC) {
+        match (args.next(), args.next()) {
+            (Some(dir), None) => return write_pc(&config(), dir),
+            _ => {
+                eprintln!("Usage: {} {} <dir>", program, WRITE_PC);
+                process::exit(1);
+            }
+        }
+    }
+
     let flags: HashSet<String> = VALID_OPTS_TO_HANDLER
         .iter()
         .map(|&(flag, _)| flag.to_owned())
         .collect();
 
-    let all_valid = env::args().skip(1).all(|arg| flags.contains(&arg));
-    let args: Vec<String> = env::args()
+    let embed = has(EMBED);
+    let rpath = has(RPATH);
+    let json = has(JSON);
+    let all = has(ALL);
+    let mut args: Vec<String> = argv
+        .iter()
         .skip(1)
-        .filter(|arg| flags.contains(arg))
+        .filter(|arg| flags.contains(*arg))
+        .cloned()
         .collect();
+    if all && !args.contains(&String::from("--help")) {
+        args = VALID_OPTS_TO_HANDLER
+            .iter()
+            .map(|&(flag, _)| flag.to_owned())
+            .filter(|flag| flag != "--help")
+            .collect();
+    }
 
-    if !all_valid || args.is_empty() {
-        exit_with_usage(
-            &env::args()
-                .nth(0)
-                .expect("no first argument representing the program path"),
-            1,
-        );
+    // Without a value to print, `--json` has nothing to say, even with
+    // `--embed`
+    if args.is_empty() && (!embed || json) {
+        exit_with_usage(&program, 1, usage_embed());
     } else if args.contains(&String::from("--help")) {
-        exit_with_usage(
-            &env::args()
-                .nth(0)
-                .expect("no first argument representing the program path"),
-            0,
-        );
+        exit_with_usage(&program, 0, usage_embed());
     }
 
-    let py = PythonConfig::new();
+    let py = config();
+    if filter.is_some() && (embed || rpath) {
+        eprintln!(
+            "{}: {} doesn't combine with {} or {}",
+            program, FILTER, EMBED, RPATH
+        );
+        process::exit(1);
+    } else if embed && !supports_embed(&py).unwrap_or_else(|err| exit_with_error(EMBED, err)) {
+        let version = py
+            .version_info()
+            .unwrap_or_else(|err| exit_with_error(EMBED, err));
+        exit_with_error(
+            EMBED,
+            Error::UnsupportedVersion {
+                version: version.to_string(),
+                required: "Python 3.8 or newer",
+            },
+        );
+    }
 
-    let lookup: HashMap<String, Handler> = VALID_OPTS_TO_HANDLER
+    let mut lookup: HashMap<String, Handler> = VALID_OPTS_TO_HANDLER
         .iter()
         .map(|&(flag, handler)| (flag.to_owned(), handler))
         .collect();
+    if embed {
+        lookup.extend(
+            EMBED_OPTS_TO_HANDLER
+                .iter()
+                .map(|&(flag, handler)| (flag.to_owned(), handler)),
+        );
+    }
+    if rpath {
+        lookup.extend(
+            RPATH_OPTS_TO_HANDLER
+                .iter()
+                .map(|&(flag, handler)| (flag.to_owned(), handler)),
+        );
+    }
 
     let stdout = io::stdout();
     let mut stdout = stdout.lock();
+    let mut members = Vec::new();
     for arg in args {
         let handler = lookup
             .get(&arg)
             .expect("handler was not present in the filtered user arguments");
-        writeln!(stdout, "{}", (handler)(&py)?)?;
+        let filtered = FILTERED_OPTS_TO_HANDLER
+            .iter()
+            .find(|&&(flag, _)| flag == arg)
+            .and_then(|&(_, handler)| Some((handler, filter?)));
+        let resp = match filtered {
+            Some((handler, policy)) => (handler)(&py, policy).map(|flags| flags.join(" ")),
+            None => (handler)(&py),
+        }
+        .unwrap_or_else(|err| exit_with_error(&arg, err));
+        if !json {
+            writeln!(stdout, "{}", resp)?;
+        } else {
+            let member = format!(
+                "{}: {}",
+                json_string(arg.trim_start_matches('-')),
+                json_string(&resp)
+            );
+            if !members.contains(&member) {
+                members.push(member);
+            }
+        }
+    }
+    if json {
+        writeln!(stdout, "{{{}}}", members.join(", "))?;
     }
 
     Ok(())
@@ -0,0 +1,91 @@
+//! Helpers for `build.rs` scripts
+//!
+//! ```no_run
+//! // build.rs
+//! use python_config::{build, PythonConfig};
+//!
+//! fn main() {
+//!     build::emit_cargo_metadata(&PythonConfig::new()).unwrap();
+//! }
+//! ```
+
+use crate::{Commander, LinkArgs, PyResult, PythonConfig};
+
+use std::path::PathBuf;
+
+/// Renders the cargo directives for linking against the libraries in `link`,
+/// and for exposing the `includes` to dependent crates
+fn directives(link: &LinkArgs, includes: &[PathBuf]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for dir in &link.lib_dirs {
+        lines.push(format!("cargo:rustc-link-search=native={}", dir.display()));
+    }
+    for lib in &link.libs {
+        lines.push(format!("cargo:rustc-link-lib={}", lib));
+    }
+    for framework in &link.frameworks {
+        lines.push(format!("cargo:rustc-link-lib=framework={}", framework));
+    }
+    let mut seen = Vec::new();
+    for include in includes {
+        if !seen.contains(&include) {
+            lines.push(format!("cargo:include={}", include.display()));
+            seen.push(include);
+        }
+    }
+    lines
+}
+
+/// Returns the cargo directives for linking against libpython
+///
+/// The lines add the library search directories (`cargo:rustc-link-search`),
+/// link libpython and its dependencies (`cargo:rustc-link-lib`), and expose
+/// the header directories to dependent crates (`cargo:include`, available
+/// to them as `DEP_<name>_INCLUDE` if your crate sets `links`). Other
+/// linker flags, like those only needed by executables, are not included.
+///
+/// The libraries are the same as
+/// [`ldflags_embed`](../struct.PythonConfig.html#method.ldflags_embed), so
+/// libpython is linked for every Python version.
+pub fn cargo_metadata<C: Commander>(py: &PythonConfig<C>) -> PyResult<Vec<String>> {
+    let link = py.link_args_embed()?;
+    let includes = py.include_paths()?;
+    Ok(directives(&link, &includes))
+}
+
+/// Prints the [`cargo_metadata`](fn.cargo_metadata.html) lines to stdout,
+/// where cargo reads them when running a build script
+pub fn emit_cargo_metadata<C: Commander>(py: &PythonConfig<C>) -> PyResult<()> {
+    for line in cargo_metadata(py)? {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::directives;
+    use crate::LinkArgs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn cargo_directives() {
+        let link = LinkArgs {
+            lib_dirs: vec![PathBuf::from("/opt/python/lib")],
+            libs: vec!["python3.11".to_owned(), "dl".to_owned()],
+            frameworks: vec!["CoreFoundation".to_owned()],
+            other_flags: vec!["-Xlinker".to_owned()],
+        };
+        let include = PathBuf::from("/opt/python/include/python3.11");
+        assert_eq!(
+            directives(&link, &[include.clone(), include]),
+            [
+                "cargo:rustc-link-search=native=/opt/python/lib",
+                "cargo:rustc-link-lib=python3.11",
+                "cargo:rustc-link-lib=dl",
+                "cargo:rustc-link-lib=framework=CoreFoundation",
+                "cargo:include=/opt/python/include/python3.11",
+            ]
+        );
+    }
+}
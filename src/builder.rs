@@ -0,0 +1,355 @@
+//! Gathering the options for a `PythonConfig` before creating it
+
+use crate::{
+    cache::Cache, discover, other_err, Error, PyResult, PythonConfig, RetryPolicy, SysCommand,
+    Version,
+};
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Collects the options for a [`PythonConfig`](struct.PythonConfig.html),
+/// then creates it
+///
+/// Create one with [`PythonConfig::builder`](struct.PythonConfig.html#method.builder).
+/// Each option has the same meaning as the `PythonConfig` constructor,
+/// or `with_*` method, of the same name.
+///
+/// We choose the interpreter like this:
+///
+/// 1. the [`interpreter`](#method.interpreter), if you name one
+/// 2. otherwise, if you set a version requirement, the newest interpreter
+///    on this system that meets it, like
+///    [`discover_best`](struct.PythonConfig.html#method.discover_best)
+/// 3. otherwise, the interpreter for the [`version`](#method.version), if
+///    you set one
+/// 4. otherwise, the interpreter named by the environment, like
+///    [`from_env`](struct.PythonConfig.html#method.from_env)
+///
+/// In the first two cases, [`build`](#method.build) runs the interpreter,
+/// with the environment, timeout, and retry policy that you set, to check
+/// that it's a Python interpreter that meets the version requirement.
+///
+/// # Example
+///
+/// ```no_run
+/// use python_config::PythonConfig;
+/// use std::time::Duration;
+///
+/// let cfg = PythonConfig::builder()
+///     .interpreter("/usr/bin/python3")
+///     .min_version((3, 9))
+///     .embed(true)
+///     .cache(true)
+///     .timeout(Duration::from_secs(30))
+///     .env_clear()
+///     .build()
+///     .unwrap();
+/// println!("{}", cfg.ldflags().unwrap()); // Links libpython
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PythonConfigBuilder {
+    interpreter: Option<PathBuf>,
+    version: Option<Version>,
+    /// Requirements that the interpreter's version must meet, all of them
+    reqs: Vec<semver::VersionReq>,
+    embed: bool,
+    cache: bool,
+    cache_dir: Option<PathBuf>,
+    timeout: Option<Duration>,
+    retry: Option<RetryPolicy>,
+    env_clear: bool,
+    /// Variables to set, or to remove if the value is `None`
+    env: Vec<(OsString, Option<OsString>)>,
+    current_dir: Option<PathBuf>,
+}
+
+impl PythonConfigBuilder {
+    /// Use the interpreter at the path `interpreter`
+    pub fn interpreter<P: Into<PathBuf>>(mut self, interpreter: P) -> Self {
+        self.interpreter = Some(interpreter.into());
+        self
+    }
+
+    /// Use the system installed Python of version `version`, like
+    /// [`PythonConfig::version`](struct.PythonConfig.html#method.version)
+    ///
+    /// This doesn't apply if you name an [`interpreter`](#method.interpreter),
+    /// or set a version requirement.
+    pub fn version(mut self, version: Version) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Require an interpreter whose version matches `req`
+    ///
+    /// Requirements add up, so this also keeps the requirement of
+    /// [`min_version`](#method.min_version).
+    pub fn version_req(mut self, req: semver::VersionReq) -> Self {
+        self.reqs.push(req);
+        self
+    }
+
+    /// Require Python `min`, like `(3, 9)`, or later
+    pub fn min_version(self, (major, minor): (u8, u8)) -> Self {
+        let req = semver::VersionReq::parse(&format!(">={}.{}", major, minor))
+            .expect("a minimum version is a valid requirement");
+        self.version_req(req)
+    }
+
+    /// Returns `true` if `version` meets every requirement
+    fn matches(&self, version: &semver::Version) -> bool {
+        self.reqs.iter().all(|req| req.matches(version))
+    }
+
+    /// Describes the requirements, like `>= 3.9, < 3.13`
+    fn requirement(&self) -> String {
+        let reqs: Vec<String> = self.reqs.iter().map(ToString::to_string).collect();
+        reqs.join(", ")
+    }
+
+    /// Answer [`libs`](struct.PythonConfig.html#method.libs),
+    /// [`ldflags`](struct.PythonConfig.html#method.ldflags), and the methods
+    /// built on them for embedding Python in an application, like
+    /// `python3-config --embed`
+    pub fn embed(mut self, embed: bool) -> Self {
+        self.embed = embed;
+        self
+    }
+
+    /// Remember each query's response, like
+    /// [`cached`](struct.PythonConfig.html#method.cached)
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Remember each query's response in files under `dir`, like
+    /// [`cached_on_disk`](struct.PythonConfig.html#method.cached_on_disk)
+    ///
+    /// This implies [`cache`](#method.cache).
+    pub fn cache_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.cache = true;
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Fail a query that runs for longer than `timeout`, like
+    /// [`with_timeout`](struct.PythonConfig.html#method.with_timeout)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry transient failures to spawn the interpreter, like
+    /// [`with_retry`](struct.PythonConfig.html#method.with_retry)
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Set the environment variable `key` to `value` when running the
+    /// interpreter
+    pub fn env<K: Into<OsString>, V: Into<OsString>>(mut self, key: K, value: V) -> Self {
+        self.env.push((key.into(), Some(value.into())));
+        self
+    }
+
+    /// Remove the environment variable `key` when running the interpreter
+    pub fn env_remove<K: Into<OsString>>(mut self, key: K) -> Self {
+        self.env.push((key.into(), None));
+        self
+    }
+
+    /// Run the interpreter with an empty environment, except for the
+    /// variables that you [`env`](#method.env)
+    ///
+    /// We still find a named interpreter, like `python3`, on our `PATH`.
+    pub fn env_clear(mut self) -> Self {
+        self.env_clear = true;
+        self
+    }
+
+    /// Protect queries from environment variables that change how the
+    /// interpreter starts, like
+    /// [`with_clean_env`](struct.PythonConfig.html#method.with_clean_env)
+    pub fn clean_env(self) -> Self {
+        self.env_remove("PYTHONSTARTUP")
+            .env_remove("PYTHONHOME")
+            .env_remove("PYTHONPATH")
+            .env("PYTHONIOENCODING", "utf-8")
+    }
+
+    /// Run the interpreter in the directory `dir`, like
+    /// [`with_current_dir`](struct.PythonConfig.html#method.with_current_dir)
+    pub fn current_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Applies our options to `cmdr`
+    fn configure(&self, cmdr: &mut SysCommand) {
+        if let Some(retry) = self.retry {
+            cmdr.set_retry(retry);
+        }
+        if let Some(timeout) = self.timeout {
+            cmdr.set_timeout(timeout);
+        }
+        if self.env_clear {
+            cmdr.clear_env();
+        }
+        for (key, value) in &self.env {
+            cmdr.set_env(key.clone(), value.clone());
+        }
+        if let Some(ref dir) = self.current_dir {
+            cmdr.set_current_dir(dir.clone());
+        }
+        match (self.cache, &self.cache_dir) {
+            (_, Some(dir)) => cmdr.set_cache(Cache::on_disk(dir.clone())),
+            (true, None) => cmdr.set_cache(Cache::in_memory()),
+            (false, None) => {}
+        }
+    }
+
+    /// Creates the `PythonConfig`
+    ///
+    /// This fails if the interpreter that we choose isn't a Python
+    /// interpreter, or if it doesn't meet the version requirement. See
+    /// [`PythonConfigBuilder`](struct.PythonConfigBuilder.html) for how we
+    /// choose.
+    pub fn build(self) -> PyResult<PythonConfig> {
+        let interpreter = match self.interpreter {
+            Some(ref path) => Some(path.clone()),
+            None if !self.reqs.is_empty() => {
+                let found = discover::all();
+                let newest = found
+                    .iter()
+                    .filter(|interpreter| self.matches(&interpreter.version))
+                    .max_by(|left, right| left.version.cmp(&right.version));
+                match newest {
+                    Some(interpreter) => Some(interpreter.path.clone()),
+                    None => {
+                        return Err(Error::NoMatchingInterpreter {
+                            requirement: self.requirement(),
+                            found,
+                        })
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let mut cfg = match interpreter {
+            Some(ref path) => {
+                let mut cmdr = SysCommand::new(
+                    path.to_str()
+                        .ok_or_else(|| other_err("unable to coerce interpreter path to string"))?,
+                );
+                self.configure(&mut cmdr);
+                PythonConfig::probe_interpreter(cmdr)?
+            }
+            None => {
+                let mut cfg = match self.version {
+                    Some(version) => PythonConfig::version(version),
+                    None => PythonConfig::from_env(),
+                };
+                self.configure(&mut cfg.cmdr);
+                cfg
+            }
+        };
+
+        if let Some(ref info) = cfg.info {
+            let version = semver::Version::from(info.version().clone());
+            if !self.matches(&version) {
+                return Err(Error::Other(
+                    format!(
+                        "Python {} at {} doesn't match '{}'",
+                        version,
+                        info.executable().display(),
+                        self.requirement()
+                    )
+                    .into(),
+                ));
+            }
+        }
+        cfg.embed = self.embed;
+        Ok(cfg)
+    }
+}
+
+impl PythonConfig {
+    /// Returns a builder that collects the options for a `PythonConfig`,
+    /// like the interpreter, a version requirement, embed mode, caching,
+    /// a timeout, and the environment
+    ///
+    /// See [`PythonConfigBuilder`](struct.PythonConfigBuilder.html).
+    pub fn builder() -> PythonConfigBuilder {
+        PythonConfigBuilder::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, PythonConfig};
+
+    #[test]
+    fn interpreter() {
+        let executable = PythonConfig::new().executable_path().unwrap();
+        let cfg = PythonConfig::builder()
+            .interpreter(&executable)
+            .min_version((3, 0))
+            .embed(true)
+            .cache(true)
+            .env_clear()
+            .build()
+            .unwrap();
+        assert!(cfg.is_embedding());
+        assert_eq!(cfg.interpreter_info().unwrap().executable(), executable);
+        assert_eq!(cfg.ldflags().unwrap(), cfg.ldflags_embed().unwrap());
+        assert_eq!(cfg.libs().unwrap(), cfg.libs_embed().unwrap());
+        assert_eq!(cfg.link_args().unwrap(), cfg.link_args_embed().unwrap());
+
+        let cfg = PythonConfig::builder().build().unwrap();
+        assert!(!cfg.is_embedding());
+        assert!(cfg.interpreter_info().is_none());
+        assert_eq!(
+            cfg.ldflags().unwrap(),
+            PythonConfig::new().ldflags().unwrap()
+        );
+    }
+
+    #[test]
+    fn version_requirements() {
+        let executable = PythonConfig::new().executable_path().unwrap();
+        match PythonConfig::builder()
+            .interpreter(&executable)
+            .min_version((3, 0))
+            .version_req("<3".parse().unwrap())
+            .build()
+        {
+            Err(Error::Other(why)) => assert!(why.contains("doesn't match"), "{}", why),
+            other => panic!("{:?}", other.map(|cfg| cfg.executable())),
+        }
+
+        let cfg = PythonConfig::builder().min_version((3, 0)).build().unwrap();
+        assert!(cfg.interpreter_info().is_some());
+        assert!(matches!(
+            PythonConfig::builder().min_version((99, 0)).build(),
+            Err(Error::NoMatchingInterpreter { .. })
+        ));
+    }
+
+    #[test]
+    fn environment() {
+        let cfg = PythonConfig::builder()
+            .env_clear()
+            .env("PYTHON_CONFIG_BUILDER_TEST", "1")
+            .build()
+            .unwrap();
+        let environ = cfg
+            .run_python("print(sorted(k for k in os.environ if k != 'LC_CTYPE'))")
+            .unwrap();
+        assert_eq!(environ, "['PYTHON_CONFIG_BUILDER_TEST']");
+    }
+}
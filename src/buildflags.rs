@@ -0,0 +1,102 @@
+//! Build options that change the ABI
+
+/// The build options that change the ABI of a Python distribution
+///
+/// Each field is named for the macro that the option defines. These
+/// are the options that PyO3's build script gathers, so FFI bindings can
+/// use [`cargo_cfgs`](#method.cargo_cfgs) to compile against differently
+/// configured Pythons.
+///
+/// See [`PythonConfig::build_flags`](struct.PythonConfig.html#method.build_flags).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BuildFlags {
+    /// `Py_DEBUG`, a debug build, configured with `--with-pydebug`
+    pub py_debug: bool,
+    /// `Py_REF_DEBUG`, which tracks the total reference count. Debug
+    /// builds define it.
+    pub py_ref_debug: bool,
+    /// `Py_TRACE_REFS`, which links every object into a list, and changes
+    /// the layout of `PyObject`
+    pub py_trace_refs: bool,
+    /// `COUNT_ALLOCS`, which counts allocations per type. Removed in
+    /// Python 3.9.
+    pub count_allocs: bool,
+    /// `WITH_PYMALLOC`, Python's small object allocator
+    pub with_pymalloc: bool,
+    /// `Py_GIL_DISABLED`, a free-threaded build
+    pub py_gil_disabled: bool,
+}
+
+impl BuildFlags {
+    /// Returns the names of the options that are set, like `Py_DEBUG`
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            (self.py_debug, "Py_DEBUG"),
+            (self.py_ref_debug, "Py_REF_DEBUG"),
+            (self.py_trace_refs, "Py_TRACE_REFS"),
+            (self.count_allocs, "COUNT_ALLOCS"),
+            (self.with_pymalloc, "WITH_PYMALLOC"),
+            (self.py_gil_disabled, "Py_GIL_DISABLED"),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, name)| *name)
+        .collect()
+    }
+
+    /// Returns a `cargo:rustc-cfg` directive for each option that's set,
+    /// like `cargo:rustc-cfg=py_sys_config="Py_DEBUG"`, the same as PyO3
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// // build.rs
+    /// for line in PythonConfig::new().build_flags().unwrap().cargo_cfgs() {
+    ///     println!("{}", line);
+    /// }
+    /// ```
+    pub fn cargo_cfgs(&self) -> Vec<String> {
+        self.names()
+            .iter()
+            .map(|name| format!("cargo:rustc-cfg=py_sys_config=\"{}\"", name))
+            .collect()
+    }
+}
+
+/// Parses the names of the options that are set, one per line
+pub(crate) fn parse(resp: &str) -> BuildFlags {
+    let mut flags = BuildFlags::default();
+    for name in resp.lines() {
+        match name {
+            "Py_DEBUG" => flags.py_debug = true,
+            "Py_REF_DEBUG" => flags.py_ref_debug = true,
+            "Py_TRACE_REFS" => flags.py_trace_refs = true,
+            "COUNT_ALLOCS" => flags.count_allocs = true,
+            "WITH_PYMALLOC" => flags.with_pymalloc = true,
+            "Py_GIL_DISABLED" => flags.py_gil_disabled = true,
+            _ => (),
+        }
+    }
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn names_round_trip() {
+        let flags = parse("Py_DEBUG\nPy_REF_DEBUG\nWITH_PYMALLOC\nSOMETHING_ELSE");
+        assert!(flags.py_debug && flags.py_ref_debug && flags.with_pymalloc);
+        assert!(!flags.py_trace_refs && !flags.count_allocs && !flags.py_gil_disabled);
+        assert_eq!(flags.names(), ["Py_DEBUG", "Py_REF_DEBUG", "WITH_PYMALLOC"]);
+        assert_eq!(
+            flags.cargo_cfgs()[0],
+            "cargo:rustc-cfg=py_sys_config=\"Py_DEBUG\""
+        );
+        assert!(parse("").names().is_empty());
+    }
+}
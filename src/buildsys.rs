@@ -0,0 +1,310 @@
+//! Describing the interpreter to CMake and Meson
+
+use crate::{Commander, LinkArgs, PyResult, PyVersion, PythonConfig};
+
+use std::path::{Path, PathBuf};
+
+/// What CMake and Meson need to know about the interpreter
+struct Description {
+    executable: String,
+    version: PyVersion,
+    include_dirs: Vec<PathBuf>,
+    cflags: Vec<String>,
+    ldflags: Vec<String>,
+    /// The arguments that link libpython, for embedding
+    embed: LinkArgs,
+    soabi: Option<String>,
+    ext_suffix: Option<String>,
+    purelib: String,
+    platlib: String,
+    stdlib: PathBuf,
+    platstdlib: PathBuf,
+}
+
+/// Quotes `value` as a CMake quoted argument. Semicolons are escaped, so
+/// that CMake doesn't split the value into a list.
+fn cmake_quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        if let '\\' | '"' | '$' | ';' = c {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Renders `values` as a quoted CMake list
+fn cmake_list<I, S>(values: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let items: Vec<String> = values
+        .into_iter()
+        .map(|value| {
+            let quoted = cmake_quote(value.as_ref());
+            quoted[1..quoted.len() - 1].to_owned()
+        })
+        .collect();
+    format!("\"{}\"", items.join(";"))
+}
+
+/// Quotes `value` as a Meson string
+fn meson_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Renders `values` as a Meson array of strings
+fn meson_array<I, S>(values: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let items: Vec<String> = values
+        .into_iter()
+        .map(|value| meson_quote(value.as_ref()))
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
+
+impl Description {
+    fn cmake(&self) -> String {
+        let mut libraries: Vec<String> = self.embed.libs.clone();
+        for framework in &self.embed.frameworks {
+            libraries.push(format!("-framework {}", framework));
+        }
+        let mut vars = vec![
+            ("Python3_FOUND", "TRUE".to_owned()),
+            ("Python3_EXECUTABLE", cmake_quote(&self.executable)),
+            ("Python3_Interpreter_FOUND", "TRUE".to_owned()),
+            ("Python3_VERSION", cmake_quote(&self.version.to_string())),
+            ("Python3_VERSION_MAJOR", self.version.major.to_string()),
+            ("Python3_VERSION_MINOR", self.version.minor.to_string()),
+            ("Python3_VERSION_PATCH", self.version.micro.to_string()),
+            (
+                "Python3_INCLUDE_DIRS",
+                cmake_list(self.include_dirs.iter().map(|dir| display(dir))),
+            ),
+            ("Python3_LIBRARIES", cmake_list(&libraries)),
+            (
+                "Python3_LIBRARY_DIRS",
+                cmake_list(self.embed.lib_dirs.iter().map(|dir| display(dir))),
+            ),
+            ("Python3_LINK_OPTIONS", cmake_list(&self.embed.other_flags)),
+            ("Python3_STDLIB", cmake_quote(&display(&self.stdlib))),
+            ("Python3_STDARCH", cmake_quote(&display(&self.platstdlib))),
+            ("Python3_SITELIB", cmake_quote(&self.purelib)),
+            ("Python3_SITEARCH", cmake_quote(&self.platlib)),
+        ];
+        if let Some(ref soabi) = self.soabi {
+            vars.push(("Python3_SOABI", cmake_quote(soabi)));
+        }
+        let mut snippet = format!(
+            "# The Python at {}, described by python-config-rs\n",
+            self.executable
+        );
+        for (name, value) in vars {
+            snippet.push_str(&format!("set({} {})\n", name, value));
+        }
+        snippet
+    }
+
+    fn meson(&self) -> String {
+        let mut properties = vec![
+            ("python_version", meson_quote(&self.version.to_string())),
+            (
+                "python_include_dirs",
+                meson_array(self.include_dirs.iter().map(|dir| display(dir))),
+            ),
+            ("python_c_args", meson_array(&self.cflags)),
+            ("python_link_args", meson_array(&self.ldflags)),
+        ];
+        if let Some(ref ext_suffix) = self.ext_suffix {
+            properties.push(("python_ext_suffix", meson_quote(ext_suffix)));
+        }
+        let executable = meson_quote(&self.executable);
+        let mut file = format!(
+            "# The Python at {}, described by python-config-rs\n\
+             [binaries]\n\
+             python = {}\n\
+             python3 = {}\n\
+             \n\
+             [properties]\n",
+            self.executable, executable, executable
+        );
+        for (name, value) in properties {
+            file.push_str(&format!("{} = {}\n", name, value));
+        }
+        file
+    }
+}
+
+impl<C: Commander> PythonConfig<C> {
+    fn describe(&self) -> PyResult<Description> {
+        Ok(Description {
+            executable: self.executable()?,
+            version: self.version_info()?,
+            include_dirs: self.include_paths_dedup()?,
+            cflags: self.cflags_split()?,
+            ldflags: self.ldflags_split()?,
+            embed: self.link_args_embed()?,
+            soabi: self.soabi()?,
+            ext_suffix: self.try_extension_suffix().unwrap_or(None),
+            purelib: self.purelib()?,
+            platlib: self.platlib()?,
+            stdlib: self.stdlib_dir()?,
+            platstdlib: self.platstdlib_dir()?,
+        })
+    }
+
+    /// Returns CMake code that sets the variables that CMake's
+    /// `FindPython3` module sets, like `Python3_INCLUDE_DIRS` and
+    /// `Python3_LIBRARIES`, for this interpreter
+    ///
+    /// Include the snippet instead of calling `find_package(Python3)`, so
+    /// that CMake builds against the same Python as the rest of your
+    /// build. Like `FindPython3`, `Python3_LIBRARIES` links libpython, for
+    /// embedding. Library directories are in `Python3_LIBRARY_DIRS`, and
+    /// other linker flags are in `Python3_LINK_OPTIONS`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let snippet = PythonConfig::new().cmake_snippet().unwrap();
+    /// std::fs::write("build/python.cmake", snippet).unwrap();
+    /// // CMakeLists.txt: include(build/python.cmake)
+    /// ```
+    pub fn cmake_snippet(&self) -> PyResult<String> {
+        self.describe().map(|description| description.cmake())
+    }
+
+    /// Returns a Meson machine file, for `--native-file` or `--cross-file`,
+    /// that names this interpreter in its `[binaries]`, and describes it in
+    /// its `[properties]`
+    ///
+    /// Meson's `python` module finds the interpreter through `[binaries]`.
+    /// The properties are `python_version`, `python_include_dirs`,
+    /// `python_c_args`, `python_link_args`, and, if the interpreter loads
+    /// extension modules, `python_ext_suffix`. Read them with
+    /// `meson.get_external_property()`. The link arguments follow
+    /// [`ldflags_split`](#method.ldflags_split), so they only link
+    /// libpython if this `PythonConfig` answers for embedding; see
+    /// [`PythonConfigBuilder::embed`](struct.PythonConfigBuilder.html#method.embed).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let file = PythonConfig::new().meson_machine_file().unwrap();
+    /// std::fs::write("python.ini", file).unwrap();
+    /// // $ meson setup --native-file python.ini build
+    /// ```
+    pub fn meson_machine_file(&self) -> PyResult<String> {
+        self.describe().map(|description| description.meson())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cmake_list, cmake_quote, meson_array, meson_quote, Description};
+    use crate::{LinkArgs, PyVersion, PythonConfig, ReleaseLevel};
+    use std::path::PathBuf;
+
+    fn description() -> Description {
+        Description {
+            executable: "/usr/bin/python3.11".to_owned(),
+            version: PyVersion {
+                major: 3,
+                minor: 11,
+                micro: 7,
+                releaselevel: ReleaseLevel::Final,
+                serial: 0,
+            },
+            include_dirs: vec![PathBuf::from("/usr/include/python3.11")],
+            cflags: vec!["-I/usr/include/python3.11".to_owned(), "-O2".to_owned()],
+            ldflags: vec!["-ldl".to_owned()],
+            embed: LinkArgs {
+                lib_dirs: vec![PathBuf::from("/usr/lib")],
+                libs: vec!["python3.11".to_owned(), "dl".to_owned()],
+                frameworks: Vec::new(),
+                other_flags: vec!["-Xlinker".to_owned(), "-export-dynamic".to_owned()],
+            },
+            soabi: Some("cpython-311-x86_64-linux-gnu".to_owned()),
+            ext_suffix: Some(".cpython-311-x86_64-linux-gnu.so".to_owned()),
+            purelib: "/usr/lib/python3.11/site-packages".to_owned(),
+            platlib: "/usr/lib/python3.11/site-packages".to_owned(),
+            stdlib: PathBuf::from("/usr/lib/python3.11"),
+            platstdlib: PathBuf::from("/usr/lib/python3.11"),
+        }
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(
+            cmake_quote(r#"C:\a "b" ${c};d"#),
+            r#""C:\\a \"b\" \${c}\;d""#
+        );
+        assert_eq!(cmake_list(["a;b", "c"]), r#""a\;b;c""#);
+        assert_eq!(meson_quote(r"it's C:\a"), r"'it\'s C:\\a'");
+        assert_eq!(meson_array(["a", "b"]), "['a', 'b']");
+        assert_eq!(meson_array(Vec::<String>::new()), "[]");
+    }
+
+    #[test]
+    fn cmake() {
+        let snippet = description().cmake();
+        assert!(snippet.contains("set(Python3_EXECUTABLE \"/usr/bin/python3.11\")\n"));
+        assert!(snippet.contains("set(Python3_VERSION_MINOR 11)\n"));
+        assert!(snippet.contains("set(Python3_INCLUDE_DIRS \"/usr/include/python3.11\")\n"));
+        assert!(snippet.contains("set(Python3_LIBRARIES \"python3.11;dl\")\n"));
+        assert!(snippet.contains("set(Python3_LINK_OPTIONS \"-Xlinker;-export-dynamic\")\n"));
+        assert!(snippet.contains("set(Python3_SOABI \"cpython-311-x86_64-linux-gnu\")\n"));
+    }
+
+    #[test]
+    fn meson() {
+        let file = description().meson();
+        assert!(file.contains("[binaries]\npython = '/usr/bin/python3.11'\n"));
+        assert!(file.contains("\n[properties]\npython_version = '3.11.7'\n"));
+        assert!(file.contains("python_c_args = ['-I/usr/include/python3.11', '-O2']\n"));
+        assert!(file.ends_with("python_ext_suffix = '.cpython-311-x86_64-linux-gnu.so'\n"));
+    }
+
+    #[test]
+    fn live() {
+        let cfg = PythonConfig::new();
+        let executable = cfg.executable().unwrap();
+        assert!(cfg.cmake_snippet().unwrap().contains(&executable));
+        assert!(cfg.meson_machine_file().unwrap().contains(&executable));
+    }
+
+    #[test]
+    fn meson_embed() {
+        let link_args = |cfg: &PythonConfig| {
+            let file = cfg.meson_machine_file().unwrap();
+            file.lines()
+                .find(|line| line.starts_with("python_link_args = "))
+                .unwrap()
+                .to_owned()
+        };
+        let cfg = PythonConfig::new();
+        let libpython = format!(
+            "'-lpython{}'",
+            cfg.config_var("LDVERSION").unwrap().unwrap()
+        );
+        let embedding = PythonConfig::builder().embed(true).build().unwrap();
+        assert!(link_args(&embedding).contains(&libpython));
+        if cfg.version_info().unwrap().at_least(3, 8) {
+            assert!(!link_args(&cfg).contains(&libpython));
+        }
+    }
+}
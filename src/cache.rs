@@ -0,0 +1,176 @@
+//! Memoizing interpreter responses
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::UNIX_EPOCH;
+
+/// Remembers the responses to commands, in memory and, optionally,
+/// in a directory
+pub struct Cache {
+    memory: Mutex<HashMap<Vec<String>, String>>,
+    dir: Option<PathBuf>,
+}
+
+impl Cache {
+    /// A cache that only remembers responses for the life of the process
+    pub fn in_memory() -> Self {
+        Cache {
+            memory: Mutex::new(HashMap::new()),
+            dir: None,
+        }
+    }
+
+    /// A cache that also remembers responses in files under `dir`
+    pub fn on_disk(dir: PathBuf) -> Self {
+        Cache {
+            dir: Some(dir),
+            ..Cache::in_memory()
+        }
+    }
+
+    /// The responses stay usable if another thread panicked while
+    /// holding the lock; we never leave the map half-updated.
+    fn memory(&self) -> MutexGuard<'_, HashMap<Vec<String>, String>> {
+        self.memory.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the remembered response to `key`, from `interpreter`
+    ///
+    /// The key holds the command, and whatever else changes the
+    /// response, like the environment.
+    pub fn get(&self, interpreter: &Path, key: &[String]) -> Option<String> {
+        let entry = memory_key(interpreter, key);
+        if let Some(resp) = self.memory().get(&entry) {
+            return Some(resp.clone());
+        }
+        let path = self.file(interpreter, key)?;
+        let resp = fs::read_to_string(path).ok()?;
+        self.memory().insert(entry, resp.clone());
+        Some(resp)
+    }
+
+    /// Remembers `resp` as the response to `key`, from `interpreter`
+    ///
+    /// Failing to write the response to disk is not an error; we'll
+    /// just run the command again next time.
+    pub fn put(&self, interpreter: &Path, key: &[String], resp: &str) {
+        self.memory()
+            .insert(memory_key(interpreter, key), resp.to_owned());
+        if let Some(path) = self.file(interpreter, key) {
+            let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+            let written = fs::create_dir_all(path.parent().unwrap())
+                .and_then(|_| fs::write(&tmp, resp))
+                .and_then(|_| fs::rename(&tmp, &path));
+            if written.is_err() {
+                let _ = fs::remove_file(&tmp);
+            }
+        }
+    }
+
+    /// The file that holds the response to `key`, from `interpreter`
+    ///
+    /// The file name is a hash of the key and the interpreter's path,
+    /// modification time, and size, so reinstalling or upgrading the
+    /// interpreter invalidates the file. Returns `None` if we're not
+    /// caching on disk, or if we can't find the interpreter.
+    fn file(&self, interpreter: &Path, key: &[String]) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        let interpreter = fs::canonicalize(interpreter).ok()?;
+        let metadata = fs::metadata(&interpreter).ok()?;
+        let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+        let mut hasher = DefaultHasher::new();
+        interpreter.hash(&mut hasher);
+        mtime.hash(&mut hasher);
+        metadata.len().hash(&mut hasher);
+        key.hash(&mut hasher);
+        Some(dir.join(format!("{:016x}.txt", hasher.finish())))
+    }
+}
+
+/// The key of the in-memory cache, which also names the interpreter
+fn memory_key(interpreter: &Path, key: &[String]) -> Vec<String> {
+    std::iter::once(interpreter.to_string_lossy().into_owned())
+        .chain(key.iter().cloned())
+        .collect()
+}
+
+/// Finds `program` like the OS would when spawning it: as a path, if it
+/// has more than one component, or by searching the `PATH`
+pub(crate) fn find_program(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return Some(path.to_owned());
+    }
+    let names = if cfg!(windows) && path.extension().is_none() {
+        vec![path.with_extension("exe")]
+    } else {
+        vec![path.to_owned()]
+    };
+    env::split_paths(&env::var_os("PATH")?)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_program, Cache};
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    fn key(cmd: &[&str]) -> Vec<String> {
+        cmd.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn in_memory() {
+        let cache = Cache::in_memory();
+        let python = Path::new("python3");
+        assert_eq!(cache.get(python, &key(&["-c", "print(1)"])), None);
+        cache.put(python, &key(&["-c", "print(1)"]), "1");
+        assert_eq!(
+            cache.get(python, &key(&["-c", "print(1)"])).as_deref(),
+            Some("1")
+        );
+        assert_eq!(cache.get(python, &key(&["-c", "print(2)"])), None);
+        assert_eq!(
+            cache.get(Path::new("python3.11"), &key(&["-c", "print(1)"])),
+            None
+        );
+    }
+
+    #[test]
+    fn on_disk() {
+        let dir = env::temp_dir().join(format!("python-config-rs-cache-{}", std::process::id()));
+        let program = dir.join("python3");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&program, "v1").unwrap();
+        let version = key(&["--version"]);
+
+        Cache::on_disk(dir.join("cache")).put(&program, &version, "Python 3.11.7");
+        let cache = Cache::on_disk(dir.join("cache"));
+        assert_eq!(
+            cache.get(&program, &version).as_deref(),
+            Some("Python 3.11.7")
+        );
+        assert_eq!(cache.get(&program, &key(&["-V"])), None);
+
+        // A different interpreter, at the same path, has a different size
+        fs::write(&program, "version 2").unwrap();
+        let cache = Cache::on_disk(dir.join("cache"));
+        assert_eq!(cache.get(&program, &version), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_program() {
+        assert_eq!(find_program("this-program-does-not-exist-python"), None);
+    }
+}
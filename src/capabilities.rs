@@ -0,0 +1,250 @@
+//! What an interpreter's implementation supports
+
+use crate::{query, Commander, Error, PyResult, PythonConfig};
+
+use std::fmt;
+use std::iter::FromIterator;
+
+/// Something that a Python implementation may not support, like building
+/// extension modules, or embedding the interpreter
+///
+/// When the interpreter doesn't support a capability, the methods that
+/// need it return [`Error::Unsupported`](enum.Error.html#variant.Unsupported).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Capability {
+    /// Building extension modules, which needs an extension suffix, like
+    /// `.cpython-311-x86_64-linux-gnu.so`. Jython doesn't load them.
+    ExtensionModules,
+    /// Embedding the interpreter in an application, which needs a library
+    /// with the C API, like libpython. CPython, PyPy, and GraalPy have one.
+    Embedding,
+    /// The limited API, and stable ABI, of CPython. Free-threaded builds,
+    /// PyPy, and GraalPy don't support it.
+    LimitedApi,
+    /// A config dir, `LIBPL`, with the static libpython and the build's
+    /// `Makefile`. Windows distributions, PyPy, and GraalPy don't have one.
+    ConfigDir,
+    /// pkg-config files, which CPython generates outside of Windows
+    PkgConfig,
+}
+
+impl Capability {
+    /// Every capability, in the order of their bits
+    const ALL: [Capability; 5] = [
+        Capability::ExtensionModules,
+        Capability::Embedding,
+        Capability::LimitedApi,
+        Capability::ConfigDir,
+        Capability::PkgConfig,
+    ];
+
+    /// Returns the capability's name in the `CAPABILITIES` query, like
+    /// `extension_modules`
+    pub fn name(self) -> &'static str {
+        match self {
+            Capability::ExtensionModules => "extension_modules",
+            Capability::Embedding => "embedding",
+            Capability::LimitedApi => "limited_api",
+            Capability::ConfigDir => "config_dir",
+            Capability::PkgConfig => "pkg_config",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << Capability::ALL
+            .iter()
+            .position(|cap| *cap == self)
+            .expect("every capability is in ALL")
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Capability::ExtensionModules => "extension modules",
+            Capability::Embedding => "embedding",
+            Capability::LimitedApi => "the limited API",
+            Capability::ConfigDir => "a config dir (LIBPL)",
+            Capability::PkgConfig => "pkg-config files",
+        })
+    }
+}
+
+/// The set of [`Capability`](enum.Capability.html) that an interpreter
+/// supports
+///
+/// See [`PythonConfig::capabilities`](struct.PythonConfig.html#method.capabilities).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capabilities {
+    bits: u8,
+}
+
+impl Capabilities {
+    /// Returns a set without any capabilities
+    pub fn empty() -> Self {
+        Capabilities::default()
+    }
+
+    /// Returns a set with every capability
+    pub fn all() -> Self {
+        Capability::ALL.iter().cloned().collect()
+    }
+
+    /// Returns `true` if the set holds `capability`
+    pub fn contains(self, capability: Capability) -> bool {
+        self.bits & capability.bit() != 0
+    }
+
+    /// Adds `capability` to the set
+    pub fn insert(&mut self, capability: Capability) {
+        self.bits |= capability.bit();
+    }
+
+    /// Removes `capability` from the set
+    pub fn remove(&mut self, capability: Capability) {
+        self.bits &= !capability.bit();
+    }
+
+    /// Returns `true` if the set doesn't hold any capabilities
+    pub fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    /// Returns an iterator over the capabilities in the set
+    pub fn iter(self) -> impl Iterator<Item = Capability> {
+        Capability::ALL
+            .iter()
+            .cloned()
+            .filter(move |cap| self.contains(*cap))
+    }
+
+    /// Parses the names printed by the `CAPABILITIES` query. Names that
+    /// we don't know are ignored.
+    pub(crate) fn parse(resp: &str) -> Self {
+        resp.split_whitespace()
+            .filter_map(|name| Capability::ALL.iter().find(|cap| cap.name() == name))
+            .cloned()
+            .collect()
+    }
+}
+
+impl fmt::Debug for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<Capability> for Capabilities {
+    fn from_iter<I: IntoIterator<Item = Capability>>(iter: I) -> Self {
+        let mut caps = Capabilities::empty();
+        for cap in iter {
+            caps.insert(cap);
+        }
+        caps
+    }
+}
+
+impl<C: Commander> PythonConfig<C> {
+    /// Returns what the interpreter's implementation supports, like
+    /// building extension modules, or embedding the interpreter
+    ///
+    /// Methods that need a capability fail with
+    /// [`Error::Unsupported`](enum.Error.html#variant.Unsupported) when the
+    /// interpreter doesn't have it, rather than returning an empty, or
+    /// meaningless, value. Check the capabilities first to take another
+    /// path. We query the interpreter once, then remember the answer.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::{Capability, PythonConfig};
+    ///
+    /// let cfg = PythonConfig::new();
+    /// if cfg.capabilities().unwrap().contains(Capability::LimitedApi) {
+    ///     println!("cargo:rustc-cfg=Py_LIMITED_API");
+    /// }
+    /// ```
+    pub fn capabilities(&self) -> PyResult<Capabilities> {
+        if let Some(caps) = self.caps.get() {
+            return Ok(*caps);
+        }
+        let caps = Capabilities::parse(&self.script(query::CAPABILITIES)?);
+        Ok(*self.caps.get_or_init(|| caps))
+    }
+
+    /// Fails with `Error::Unsupported` if the interpreter doesn't support
+    /// `capability`
+    pub(crate) fn require(&self, capability: Capability) -> PyResult<()> {
+        if self.capabilities()?.contains(capability) {
+            Ok(())
+        } else {
+            Err(Error::Unsupported(capability))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Capabilities, Capability};
+    use crate::{Commander, Error, PyResult, PythonConfig, Version};
+    use std::cell::Cell;
+
+    #[test]
+    fn bitset() {
+        let mut caps = Capabilities::parse("embedding config_dir unknown");
+        assert!(caps.contains(Capability::Embedding));
+        assert!(!caps.contains(Capability::PkgConfig));
+        caps.remove(Capability::Embedding);
+        assert_eq!(caps.iter().collect::<Vec<_>>(), [Capability::ConfigDir]);
+        assert_eq!(format!("{:?}", caps), "{ConfigDir}");
+        assert!(Capabilities::empty().is_empty());
+        assert_eq!(Capabilities::all().iter().count(), Capability::ALL.len());
+    }
+
+    /// Answers like Jython, which supports nothing, and counts queries
+    struct Jython(Cell<usize>);
+
+    impl Commander for Jython {
+        fn commands(&self, _: &[&str]) -> PyResult<String> {
+            self.0.set(self.0.get() + 1);
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn unsupported() {
+        let jython = Jython(Cell::new(0));
+        let cfg = PythonConfig::with_commander(Version::Three, &jython);
+        assert!(cfg.capabilities().unwrap().is_empty());
+        match cfg.extension_suffix() {
+            Err(err @ Error::Unsupported(Capability::ExtensionModules)) => assert_eq!(
+                err.to_string(),
+                "the interpreter doesn't support extension modules"
+            ),
+            other => panic!("{:?}", other),
+        }
+        assert!(matches!(
+            cfg.config_dir(),
+            Err(Error::Unsupported(Capability::ConfigDir))
+        ));
+        assert!(matches!(
+            cfg.libpython_path(),
+            Err(Error::Unsupported(Capability::Embedding))
+        ));
+        assert!(matches!(
+            cfg.pkg_config(),
+            Err(Error::Unsupported(Capability::PkgConfig))
+        ));
+        assert_eq!(jython.0.get(), 1);
+    }
+
+    #[test]
+    fn live() {
+        let caps = PythonConfig::new().capabilities().unwrap();
+        assert!(caps.contains(Capability::ExtensionModules));
+        assert!(caps.contains(Capability::Embedding));
+    }
+}
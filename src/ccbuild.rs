@@ -0,0 +1,123 @@
+//! Configures a `cc::Build` for a Python distribution
+
+use crate::{Commander, PyResult, PythonConfig};
+
+/// The compiler flags from `cflags`, sorted by how `cc::Build` takes them
+#[derive(Debug, Default, PartialEq, Eq)]
+struct CompileArgs {
+    /// Preprocessor definitions, from `-D`, with their values
+    defines: Vec<(String, Option<String>)>,
+    /// Every other flag, except the include directories
+    flags: Vec<String>,
+}
+
+/// Sorts the `cflags` into a `CompileArgs`
+///
+/// The `-I` flags are dropped, since their paths may contain spaces.
+/// We take the include directories from `include_paths` instead.
+fn parse(cflags: &str) -> CompileArgs {
+    let mut args = CompileArgs::default();
+    for flag in cflags.split_whitespace() {
+        if flag.starts_with("-I") {
+            continue;
+        } else if let Some(define) = flag.strip_prefix("-D") {
+            args.defines.push(match define.split_once('=') {
+                Some((name, value)) => (name.to_owned(), Some(value.to_owned())),
+                None => (define.to_owned(), None),
+            });
+        } else {
+            args.flags.push(flag.to_owned());
+        }
+    }
+    args
+}
+
+impl<C: Commander> PythonConfig<C> {
+    /// Adds the include directories, preprocessor definitions, and compiler
+    /// flags of [`cflags`](#method.cflags) to `build`
+    ///
+    /// Use it to compile C code, like an extension module or an embedding
+    /// application, for this Python distribution. The include directories
+    /// are those of [`include_paths_dedup`](#method.include_paths_dedup),
+    /// so paths with spaces stay whole.
+    ///
+    /// Requires the `cc` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// // build.rs
+    /// use python_config::PythonConfig;
+    ///
+    /// let mut build = cc::Build::new();
+    /// PythonConfig::new().apply_to_build(&mut build).unwrap();
+    /// build.file("src/extension.c").compile("extension");
+    /// ```
+    pub fn apply_to_build(&self, build: &mut cc::Build) -> PyResult<()> {
+        for dir in self.include_paths_dedup()? {
+            build.include(dir);
+        }
+        let args = parse(&self.cflags()?);
+        for (name, value) in &args.defines {
+            build.define(name, value.as_deref());
+        }
+        for flag in &args.flags {
+            build.flag(flag);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, CompileArgs};
+    use crate::PythonConfig;
+    use std::path::Path;
+
+    #[test]
+    fn sorted_by_kind() {
+        let args = parse("-I/usr/include/python3.11 -DNDEBUG -DFOO=1 -g -fwrapv -O3 -Wall");
+        assert_eq!(
+            args,
+            CompileArgs {
+                defines: vec![
+                    ("NDEBUG".to_owned(), None),
+                    ("FOO".to_owned(), Some("1".to_owned()))
+                ],
+                flags: vec![
+                    "-g".to_owned(),
+                    "-fwrapv".to_owned(),
+                    "-O3".to_owned(),
+                    "-Wall".to_owned()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn apply_to_build() {
+        let cfg = PythonConfig::new();
+        let mut build = cc::Build::new();
+        cfg.apply_to_build(&mut build).unwrap();
+
+        // Asking for the compiler needs a target, which tests don't have
+        if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+            let tool = build
+                .cargo_metadata(false)
+                .opt_level(0)
+                .host("x86_64-unknown-linux-gnu")
+                .target("x86_64-unknown-linux-gnu")
+                .try_get_compiler()
+                .unwrap();
+            let args: Vec<&str> = tool.args().iter().filter_map(|arg| arg.to_str()).collect();
+            for dir in cfg.include_paths_dedup().unwrap() {
+                let idx = args.iter().position(|arg| Path::new(arg) == dir).unwrap();
+                assert_eq!(args[idx - 1], "-I");
+            }
+            for flag in parse(&cfg.cflags().unwrap()).flags {
+                assert!(args.contains(&flag.as_str()), "{} not in {:?}", flag, args);
+            }
+            assert!(!args.iter().any(|arg| arg.starts_with("-I/")));
+        }
+    }
+}
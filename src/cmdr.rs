@@ -1,13 +1,195 @@
 //! A commander provides a terminal-like input/output interface
 
-use std::io;
+use crate::cache::{find_program, Cache};
+use crate::{Error, PyResult};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::process;
 use std::str;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Describes how to retry spawning the interpreter after a
+/// transient failure
+///
+/// Spawning a process can fail for reasons that go away on their
+/// own: the system is temporarily out of process slots (`EAGAIN`),
+/// the executable is still being written (`ETXTBSY`), or, on Windows,
+/// an antivirus scanner has the executable locked. A retry policy
+/// lets you ride out those failures instead of aborting a build.
+///
+/// Only failures to spawn the interpreter are retried. If the
+/// interpreter runs, but our script fails, we return the error
+/// immediately.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetryPolicy {
+    /// The total number of attempts, including the first. An attempt
+    /// count of 0 or 1 means that we never retry.
+    pub attempts: u32,
+    /// How long to wait before the first retry. The delay doubles
+    /// before each subsequent retry.
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// A policy that makes up to `attempts` attempts, starting with
+    /// a delay of `backoff` between attempts
+    pub fn new(attempts: u32, backoff: Duration) -> Self {
+        RetryPolicy { attempts, backoff }
+    }
+
+    /// A policy that never retries
+    pub fn never() -> Self {
+        RetryPolicy::new(1, Duration::from_millis(0))
+    }
+
+    /// Returns the delays before each retry, doubling from `backoff`.
+    /// The delays saturate, rather than overflow, for large attempt
+    /// counts.
+    fn delays(&self) -> impl Iterator<Item = Duration> {
+        let retries = self.attempts.saturating_sub(1) as usize;
+        std::iter::successors(Some(self.backoff), |delay| Some(delay.saturating_mul(2)))
+            .take(retries)
+    }
+}
+
+/// The default policy never retries
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::never()
+    }
+}
+
+/// Returns `true` if a failure to spawn a process might
+/// succeed if we try again
+fn is_transient(err: &io::Error) -> bool {
+    // Raw OS error codes for 'resource temporarily unavailable'
+    // and 'text file busy' on Unix, or sharing and lock violations on
+    // Windows. Access denied is permanent, so we don't retry it.
+    #[cfg(target_os = "linux")]
+    const TRANSIENT: &[i32] = &[11, 26];
+    #[cfg(all(unix, not(target_os = "linux")))]
+    const TRANSIENT: &[i32] = &[35, 26];
+    #[cfg(windows)]
+    const TRANSIENT: &[i32] = &[32, 33];
+    #[cfg(not(any(unix, windows)))]
+    const TRANSIENT: &[i32] = &[];
+
+    match err.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted => true,
+        _ => err
+            .raw_os_error()
+            .map(|code| TRANSIENT.contains(&code))
+            .unwrap_or(false),
+    }
+}
+
+/// Turns the output of a program that ran the interpreter into our
+/// response: what it printed to stdout, or, if it failed, an error that
+/// holds the status and whatever it printed to stderr
+pub(crate) fn response(out: process::Output) -> PyResult<String> {
+    if !out.status.success() {
+        return Err(Error::Interpreter {
+            status: out.status.code(),
+            stderr: String::from_utf8_lossy(&out.stderr).trim().to_owned(),
+        });
+    }
+    let resp = str::from_utf8(&out.stdout).map_err(io::Error::other)?;
+    Ok(resp.trim().to_owned())
+}
+
+/// Returns how long to wait before trying again, if `out` is a transient
+/// failure and we have retries left in `delays`
+fn retry_after<T>(
+    out: &io::Result<T>,
+    delays: &mut impl Iterator<Item = Duration>,
+) -> Option<Duration> {
+    match out {
+        Err(err) if is_transient(err) => delays.next(),
+        _ => None,
+    }
+}
+
+/// Reads everything from `pipe` on another thread
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Answers the queries of a [`PythonConfig`](struct.PythonConfig.html)
+///
+/// A commander receives the arguments that we'd pass to a Python
+/// interpreter, like `["-c", "<script>"]` or `["--version"]`, and
+/// returns what the interpreter would print to stdout, without
+/// surrounding whitespace. [`SysCommand`](struct.SysCommand.html),
+/// the default, runs an interpreter. Implement the trait to answer
+/// queries some other way, like from canned responses in a test, or
+/// by running the interpreter on another machine.
+///
+/// # Example
+///
+/// ```
+/// use python_config::{Commander, PyResult, PythonConfig, Version};
+///
+/// /// Claims that every installation lives in /opt/python
+/// struct Canned;
+///
+/// impl Commander for Canned {
+///     fn commands(&self, _args: &[&str]) -> PyResult<String> {
+///         Ok("/opt/python".to_owned())
+///     }
+/// }
+///
+/// let cfg = PythonConfig::with_commander(Version::Three, Canned);
+/// assert_eq!(cfg.prefix().unwrap(), "/opt/python");
+/// ```
+pub trait Commander {
+    /// Runs the interpreter with the arguments `args`, returning what it
+    /// printed to stdout
+    fn commands(&self, args: &[&str]) -> PyResult<String>;
+}
+
+impl<C: Commander + ?Sized> Commander for Box<C> {
+    fn commands(&self, args: &[&str]) -> PyResult<String> {
+        (**self).commands(args)
+    }
+}
+
+impl<C: Commander + ?Sized> Commander for &C {
+    fn commands(&self, args: &[&str]) -> PyResult<String> {
+        (**self).commands(args)
+    }
+}
 
 /// A command that calls a system
 /// program to spawn a process
+///
+/// This is the default [`Commander`](trait.Commander.html). With the
+/// `log` feature, each run of the program is logged at the debug level,
+/// with its arguments, duration, and exit status. Scripts are shortened
+/// to their first line.
 pub struct SysCommand {
     program: String,
+    /// Arguments that precede every command, like the `-3` in `py -3`
+    args: Vec<String>,
+    retry: RetryPolicy,
+    cache: Option<Cache>,
+    timeout: Option<Duration>,
+    /// Start the program with an empty environment, before applying `env`
+    env_clear: bool,
+    /// Variables to set, or to remove if the value is `None`
+    env: Vec<(OsString, Option<OsString>)>,
+    cwd: Option<PathBuf>,
 }
 
 impl SysCommand {
@@ -15,24 +197,459 @@ impl SysCommand {
     pub fn new(program: &str) -> SysCommand {
         SysCommand {
             program: program.to_owned(),
+            args: Vec::new(),
+            retry: RetryPolicy::default(),
+            cache: None,
+            timeout: None,
+            env_clear: false,
+            env: Vec::new(),
+            cwd: None,
         }
     }
 
-    pub fn commands(&self, cmd: &[&str]) -> io::Result<String> {
-        process::Command::new(&self.program)
-            .args(cmd)
-            .output()
-            .and_then(|out| {
-                if !out.status.success() {
+    /// Returns the program that we run
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+
+    /// Passes `args` to the program before the arguments of every command
+    pub(crate) fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
+    /// Kills the program if it runs for longer than `timeout`
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// Sets the environment variable `key` to `value` for the program,
+    /// or removes it from the program's environment if `value` is `None`
+    pub fn set_env(&mut self, key: OsString, value: Option<OsString>) {
+        self.env.push((key, value));
+    }
+
+    /// Starts the program with an empty environment. Variables set with
+    /// [`set_env`](#method.set_env) are still set.
+    pub fn clear_env(&mut self) {
+        self.env_clear = true;
+    }
+
+    /// Runs the program in `cwd`, rather than in our working directory
+    pub fn set_current_dir(&mut self, cwd: PathBuf) {
+        self.cwd = Some(cwd);
+    }
+
+    /// Remembers successful responses in `cache`, rather than running
+    /// the same command twice
+    pub(crate) fn set_cache(&mut self, cache: Cache) {
+        self.cache = Some(cache);
+    }
+
+    /// Sets the policy for retrying transient spawn failures
+    pub fn set_retry(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    /// Runs the program, retrying transient spawn failures
+    /// according to our retry policy
+    fn output(&self, cmd: &[&str]) -> io::Result<process::Output> {
+        let mut delays = self.retry.delays();
+        loop {
+            let out = self.output_once(cmd);
+            match retry_after(&out, &mut delays) {
+                Some(delay) => thread::sleep(delay),
+                None => return out,
+            }
+        }
+    }
+
+    /// Prepares the program to run `cmd`, in our environment and working
+    /// directory
+    pub(crate) fn command(&self, cmd: &[&str]) -> process::Command {
+        let mut command = process::Command::new(&self.program);
+        command.args(cmd);
+        if self.env_clear {
+            command.env_clear();
+        }
+        for (key, value) in &self.env {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+        if let Some(ref cwd) = self.cwd {
+            command.current_dir(cwd);
+        }
+        command
+    }
+
+    /// Runs the program once, killing it if it exceeds our timeout
+    fn output_once(&self, cmd: &[&str]) -> io::Result<process::Output> {
+        let mut command = self.command(cmd);
+        let timeout = match self.timeout {
+            Some(timeout) => timeout,
+            None => return command.output(),
+        };
+
+        let mut child = command
+            .stdin(process::Stdio::null())
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn()?;
+        // Drain the pipes while we wait, so that the program can't block
+        // on a full pipe.
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("the interpreter did not finish within {:?}", timeout),
+                ));
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        Ok(process::Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+}
+
+/// If the program doesn't exist, the error is `InterpreterNotFound`. If
+/// the program exits with a non-zero status, the error holds the status
+/// and whatever the program printed to stderr.
+impl Commander for SysCommand {
+    fn commands(&self, cmd: &[&str]) -> PyResult<String> {
+        let args = self.full_args(cmd);
+        if let Some(resp) = self.cached(&args) {
+            return Ok(resp);
+        }
+        let start = Instant::now();
+        let out = self.output(&args);
+        self.log(&args, start, &out);
+        self.respond(&args, out)
+    }
+}
+
+/// Shortens an argument for a log message: a script becomes its first
+/// line, and long arguments are cut at 60 characters
+#[cfg(feature = "log")]
+fn truncate(arg: &str) -> String {
+    const MAX: usize = 60;
+    let line = arg.lines().next().unwrap_or("");
+    if line.len() < arg.len() || line.chars().count() > MAX {
+        let short: String = line.chars().take(MAX).collect();
+        format!("{}...", short)
+    } else {
+        line.to_owned()
+    }
+}
+
+impl SysCommand {
+    /// Prepends our arguments to `cmd`
+    pub(crate) fn full_args<'a>(&'a self, cmd: &[&'a str]) -> Vec<&'a str> {
+        let mut args: Vec<&str> = self.args.iter().map(String::as_str).collect();
+        args.extend_from_slice(cmd);
+        args
+    }
+
+    /// Returns the interpreter that runs `cmd`, and the key of its
+    /// response in our cache
+    ///
+    /// Besides the command, the key holds the working directory, and the
+    /// environment variables that Python reads, like `PYTHONHOME` and
+    /// `VIRTUAL_ENV`, as the program would see them.
+    fn cache_key(&self, cmd: &[&str]) -> (PathBuf, Vec<String>) {
+        let cwd = self
+            .cwd
+            .clone()
+            .or_else(|| env::current_dir().ok())
+            .unwrap_or_default();
+        let program = Path::new(&self.program);
+        let interpreter = if program.is_relative() && program.components().count() > 1 {
+            cwd.join(program)
+        } else {
+            find_program(&self.program).unwrap_or_else(|| program.to_owned())
+        };
+
+        let keyed = |name: &OsStr| {
+            name.to_str().is_some_and(|name| {
+                name.starts_with("PYTHON") || name == "VIRTUAL_ENV" || name == "CONDA_PREFIX"
+            })
+        };
+        let mut vars: BTreeMap<OsString, OsString> = BTreeMap::new();
+        if !self.env_clear {
+            vars.extend(env::vars_os().filter(|(name, _)| keyed(name)));
+        }
+        for (name, value) in self.env.iter().filter(|(name, _)| keyed(name)) {
+            match value {
+                Some(value) => vars.insert(name.clone(), value.clone()),
+                None => vars.remove(name),
+            };
+        }
+
+        let mut key = vec![format!("cwd={}", cwd.display())];
+        key.extend(vars.iter().map(|(name, value)| {
+            format!("{}={}", name.to_string_lossy(), value.to_string_lossy())
+        }));
+        key.extend(cmd.iter().map(|arg| arg.to_string()));
+        (interpreter, key)
+    }
+
+    fn cached(&self, cmd: &[&str]) -> Option<String> {
+        let resp = self.cache.as_ref().and_then(|cache| {
+            let (interpreter, key) = self.cache_key(cmd);
+            cache.get(&interpreter, &key)
+        });
+        #[cfg(feature = "log")]
+        if resp.is_some() {
+            log::trace!(
+                "cached response for {} {:?}",
+                self.program,
+                cmd.iter().map(|arg| truncate(arg)).collect::<Vec<_>>()
+            );
+        }
+        resp
+    }
+
+    /// Logs that we ran the program with `cmd`, and how it went, if the
+    /// `log` feature is enabled
+    #[cfg(feature = "log")]
+    fn log(&self, cmd: &[&str], start: Instant, out: &io::Result<process::Output>) {
+        let args: Vec<String> = cmd.iter().map(|arg| truncate(arg)).collect();
+        let elapsed = start.elapsed();
+        match out {
+            Ok(out) => log::debug!(
+                "ran {} {:?} in {:?}: {}",
+                self.program,
+                args,
+                elapsed,
+                out.status
+            ),
+            Err(err) => log::debug!(
+                "failed to run {} {:?} after {:?}: {}",
+                self.program,
+                args,
+                elapsed,
+                err
+            ),
+        }
+    }
+
+    #[cfg(not(feature = "log"))]
+    fn log(&self, _: &[&str], _: Instant, _: &io::Result<process::Output>) {}
+
+    /// Turns the program's output into our response, remembering it if
+    /// we have a cache
+    fn respond(&self, cmd: &[&str], out: io::Result<process::Output>) -> PyResult<String> {
+        let out = match out {
+            // Spawning also fails with `NotFound` if the working directory
+            // doesn't exist.
+            Err(ref err)
+                if err.kind() == io::ErrorKind::NotFound
+                    && self.cwd.as_ref().is_none_or(|cwd| cwd.is_dir()) =>
+            {
+                return Err(Error::InterpreterNotFound {
+                    interpreter: self.program.clone(),
+                })
+            }
+            out => out?,
+        };
+        let resp = response(out)?;
+        if let Some(ref cache) = self.cache {
+            let (interpreter, key) = self.cache_key(cmd);
+            cache.put(&interpreter, &key, &resp);
+        }
+        Ok(resp)
+    }
+
+    /// Like [`commands`](trait.Commander.html#tymethod.commands), but
+    /// runs the program without blocking the thread
+    ///
+    /// We honor the same retry policy, timeout, environment, working
+    /// directory, and cache.
+    #[cfg(feature = "tokio")]
+    pub(crate) async fn commands_async(&self, cmd: &[&str]) -> PyResult<String> {
+        let args = self.full_args(cmd);
+        if let Some(resp) = self.cached(&args) {
+            return Ok(resp);
+        }
+        let start = Instant::now();
+        let mut delays = self.retry.delays();
+        let out = loop {
+            let out = self.output_async(&args).await;
+            match retry_after(&out, &mut delays) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => break out,
+            }
+        };
+        self.log(&args, start, &out);
+        self.respond(&args, out)
+    }
+
+    #[cfg(feature = "tokio")]
+    async fn output_async(&self, cmd: &[&str]) -> io::Result<process::Output> {
+        let mut command = tokio::process::Command::from(self.command(cmd));
+        // Dropping the future, like when the timeout expires, kills the
+        // program.
+        let output = command
+            .stdin(process::Stdio::null())
+            .kill_on_drop(true)
+            .output();
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, output)
+                .await
+                .unwrap_or_else(|_| {
                     Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        str::from_utf8(&out.stderr).unwrap(),
+                        io::ErrorKind::TimedOut,
+                        format!("the interpreter did not finish within {:?}", timeout),
                     ))
-                } else {
-                    str::from_utf8(&out.stdout)
-                        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
-                        .map(|s| s.trim().to_owned())
-                }
-            })
+                }),
+            None => output.await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_transient, Commander, RetryPolicy, SysCommand};
+    use crate::Error;
+    use std::io;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn transient_errors() {
+        assert!(is_transient(&io::Error::from(io::ErrorKind::WouldBlock)));
+        assert!(!is_transient(&io::Error::from(io::ErrorKind::NotFound)));
+    }
+
+    #[test]
+    fn backoff() {
+        let delays: Vec<Duration> = RetryPolicy::new(4, Duration::from_millis(10))
+            .delays()
+            .collect();
+        assert_eq!(delays, [10, 20, 40].map(Duration::from_millis));
+        assert_eq!(RetryPolicy::never().delays().count(), 0);
+        assert_eq!(
+            RetryPolicy::new(0, Duration::from_secs(1)).delays().count(),
+            0
+        );
+
+        let last = RetryPolicy::new(200, Duration::from_secs(1))
+            .delays()
+            .last()
+            .unwrap();
+        assert_eq!(last, Duration::MAX);
+    }
+
+    #[test]
+    fn permanent_errors_are_not_retried() {
+        let mut cmdr = SysCommand::new("this-program-does-not-exist-python");
+        cmdr.set_retry(RetryPolicy::new(5, Duration::from_secs(10)));
+        let start = Instant::now();
+        match cmdr.commands(&["--version"]) {
+            Err(Error::InterpreterNotFound { interpreter }) => {
+                assert_eq!(interpreter, "this-program-does-not-exist-python")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn timeout() {
+        let mut cmdr = SysCommand::new("python3");
+        cmdr.set_timeout(Duration::from_millis(200));
+        let start = Instant::now();
+        match cmdr.commands(&["-c", "import time\ntime.sleep(30)"]) {
+            Err(Error::IO(err)) => assert_eq!(err.kind(), io::ErrorKind::TimedOut),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        let resp = cmdr.commands(&["-c", "print('x' * 100000)"]).unwrap();
+        assert_eq!(resp.len(), 100000);
+    }
+
+    #[test]
+    fn environment() {
+        let mut cmdr = SysCommand::new("python3");
+        let script =
+            "import os\nprint(os.environ.get('PYTHON_CONFIG_RS_TEST', '-'))\nprint(os.getcwd())";
+        cmdr.set_env("PYTHON_CONFIG_RS_TEST".into(), Some("set".into()));
+        cmdr.set_current_dir(std::env::temp_dir());
+        let resp = cmdr.commands(&["-c", script]).unwrap();
+        let mut lines = resp.lines();
+        assert_eq!(lines.next(), Some("set"));
+        assert_eq!(
+            std::fs::canonicalize(lines.next().unwrap()).unwrap(),
+            std::fs::canonicalize(std::env::temp_dir()).unwrap()
+        );
+
+        cmdr.set_env("PYTHON_CONFIG_RS_TEST".into(), None);
+        assert!(cmdr.commands(&["-c", script]).unwrap().starts_with("-\n"));
+    }
+
+    #[test]
+    fn cache_keys() {
+        use crate::cache::Cache;
+
+        let mut cmdr = SysCommand::new("python3");
+        cmdr.set_cache(Cache::in_memory());
+        let script = "import os\nprint(os.environ.get('VIRTUAL_ENV', '-'))\nprint(os.getcwd())";
+        cmdr.set_env("VIRTUAL_ENV".into(), Some("/a".into()));
+        let first = cmdr.commands(&["-c", script]).unwrap();
+        assert!(first.starts_with("/a\n"));
+        assert_eq!(cmdr.commands(&["-c", script]).unwrap(), first);
+
+        cmdr.set_env("VIRTUAL_ENV".into(), Some("/b".into()));
+        assert!(cmdr.commands(&["-c", script]).unwrap().starts_with("/b\n"));
+
+        cmdr.set_current_dir(std::env::temp_dir());
+        let moved = cmdr.commands(&["-c", script]).unwrap();
+        assert_eq!(
+            std::fs::canonicalize(moved.lines().nth(1).unwrap()).unwrap(),
+            std::fs::canonicalize(std::env::temp_dir()).unwrap()
+        );
+
+        // A relative interpreter is relative to the working directory
+        let mut relative = SysCommand::new("bin/python3");
+        relative.set_current_dir(std::env::temp_dir());
+        let (interpreter, _) = relative.cache_key(&[]);
+        assert_eq!(interpreter, std::env::temp_dir().join("bin/python3"));
+        let (_, key) = cmdr.cache_key(&["-V"]);
+        assert!(key.contains(&"VIRTUAL_ENV=/b".to_owned()));
+        assert_eq!(key.last().map(String::as_str), Some("-V"));
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn truncated_args() {
+        use super::truncate;
+        assert_eq!(truncate("--version"), "--version");
+        assert_eq!(truncate("import sys\nprint(sys.prefix)"), "import sys...");
+        assert_eq!(truncate(&"x".repeat(100)), format!("{}...", "x".repeat(60)));
+    }
+
+    #[test]
+    fn failed_script() {
+        let cmdr = SysCommand::new("python3");
+        let script = "import sys\nsys.stderr.write('no module named x\\n')\nsys.exit(3)";
+        match cmdr.commands(&["-c", script]) {
+            Err(Error::Interpreter { status, stderr }) => {
+                assert_eq!(status, Some(3));
+                assert_eq!(stderr, "no module named x");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
     }
 }
@@ -0,0 +1,277 @@
+//! Finding conda environments
+
+use crate::{Error, PyResult};
+
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The parts of `conda info --json` that we need
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Info {
+    /// The base environment
+    pub root_prefix: Option<PathBuf>,
+    /// Every environment, including the base environment
+    pub envs: Vec<PathBuf>,
+}
+
+/// The conda executable. Activation sets `CONDA_EXE`, even if conda
+/// isn't on the `PATH`.
+fn program() -> OsString {
+    env::var_os("CONDA_EXE")
+        .filter(|exe| !exe.is_empty())
+        .unwrap_or_else(|| OsString::from("conda"))
+}
+
+/// Runs `conda info --json`
+pub(crate) fn info() -> PyResult<Info> {
+    let program = program();
+    let out = Command::new(&program)
+        .args(["info", "--json"])
+        .output()
+        .map_err(|err| {
+            Error::Other(format!("cannot run {}: {}", program.to_string_lossy(), err).into())
+        })?;
+    if !out.status.success() {
+        return Err(Error::Other(
+            format!(
+                "conda info failed: {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            )
+            .into(),
+        ));
+    }
+    Ok(parse_info(&String::from_utf8_lossy(&out.stdout)))
+}
+
+/// Finds the environment called `name_or_path`
+///
+/// An existing directory is taken as is. Otherwise, `base` is the root
+/// prefix, and any other name matches the last component of an
+/// environment's path, like `conda activate` does.
+pub(crate) fn resolve(info: &Info, name_or_path: &str) -> Option<PathBuf> {
+    let path = Path::new(name_or_path);
+    if path.is_dir() {
+        return Some(path.to_path_buf());
+    }
+    if name_or_path == "base" {
+        if let Some(ref root) = info.root_prefix {
+            return Some(root.clone());
+        }
+    }
+    info.envs
+        .iter()
+        .find(|env| env.file_name().is_some_and(|name| name == name_or_path))
+        .cloned()
+}
+
+/// Returns the path to the interpreter of the environment at `prefix`
+pub(crate) fn interpreter(prefix: &Path) -> PathBuf {
+    if cfg!(windows) {
+        prefix.join("python.exe")
+    } else {
+        prefix.join("bin").join("python")
+    }
+}
+
+/// Pulls the root prefix and environments out of the JSON `text`
+///
+/// This is just enough of a JSON tokenizer for `conda info`, so that we
+/// don't need a JSON parser. Only the members of the outer object count,
+/// so a key that's also in a string, or in a nested object, doesn't
+/// confuse us.
+fn parse_info(text: &str) -> Info {
+    let mut info = Info::default();
+    let mut rest = match text.trim_start().strip_prefix('{') {
+        Some(rest) => rest,
+        None => return info,
+    };
+    while let Some((key, after)) = string(rest.trim_start()) {
+        let value = match after.trim_start().strip_prefix(':') {
+            Some(value) => value.trim_start(),
+            None => break,
+        };
+        match key.as_str() {
+            "root_prefix" => info.root_prefix = string(value).map(|(root, _)| root.into()),
+            "envs" => info.envs = strings(value),
+            _ => {}
+        }
+        match skip(value).and_then(|after| after.trim_start().strip_prefix(',')) {
+            Some(after) => rest = after,
+            None => break,
+        }
+    }
+    info
+}
+
+/// Parses the JSON array of strings at the start of `text`
+fn strings(text: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(mut rest) = text.strip_prefix('[') {
+        while let Some((path, after)) = string(rest.trim_start()) {
+            paths.push(PathBuf::from(path));
+            rest = after.trim_start().strip_prefix(',').unwrap_or(after);
+        }
+    }
+    paths
+}
+
+/// Returns the text after the JSON value at the start of `text`
+fn skip(text: &str) -> Option<&str> {
+    match text.chars().next()? {
+        '"' => string(text).map(|(_, rest)| rest),
+        '{' | '[' => {
+            let mut depth = 0;
+            let mut rest = text;
+            loop {
+                let c = rest.chars().next()?;
+                match c {
+                    '"' => {
+                        rest = string(rest)?.1;
+                        continue;
+                    }
+                    '{' | '[' => depth += 1,
+                    '}' | ']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(&rest[1..]);
+                        }
+                    }
+                    _ => {}
+                }
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        // Numbers, `true`, `false`, and `null`
+        _ => Some(text.trim_start_matches(|c: char| !matches!(c, ',' | '}' | ']'))),
+    }
+}
+
+/// Parses the JSON string at the start of `text`, returning the string
+/// and the text that follows it
+fn string(text: &str) -> Option<(String, &str)> {
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    let mut out = String::new();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Some((out, &text[idx + 2..])),
+            '\\' => match chars.next()?.1 {
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                'r' => out.push('\r'),
+                'b' => out.push('\u{8}'),
+                'f' => out.push('\u{c}'),
+                'u' => {
+                    let unit = hex(&mut chars)?;
+                    // Characters outside the BMP are escaped as a UTF-16
+                    // surrogate pair
+                    let code = if (0xD800..0xDC00).contains(&unit) {
+                        if chars.next()?.1 != '\\' || chars.next()?.1 != 'u' {
+                            return None;
+                        }
+                        let low = hex(&mut chars)?;
+                        if !(0xDC00..0xE000).contains(&low) {
+                            return None;
+                        }
+                        0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+                    } else {
+                        unit
+                    };
+                    out.push(char::from_u32(code)?);
+                }
+                escaped => out.push(escaped),
+            },
+            c => out.push(c),
+        }
+    }
+    None
+}
+
+/// Parses the four hex digits of a `\u` escape
+fn hex(chars: &mut impl Iterator<Item = (usize, char)>) -> Option<u32> {
+    (0..4).try_fold(0, |code, _| Some(code * 16 + chars.next()?.1.to_digit(16)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_info, resolve, string, Info};
+    use std::path::PathBuf;
+
+    const INFO: &str = r#"{
+  "conda_version": "24.1.2",
+  "envs": [
+    "C:\\Users\\me\\miniconda3",
+    "C:\\Users\\me\\miniconda3\\envs\\py\u0033"
+  ],
+  "root_prefix": "C:\\Users\\me\\miniconda3"
+}"#;
+
+    #[test]
+    fn parse() {
+        let info = parse_info(INFO);
+        assert_eq!(
+            info,
+            Info {
+                root_prefix: Some(PathBuf::from(r"C:\Users\me\miniconda3")),
+                envs: vec![
+                    PathBuf::from(r"C:\Users\me\miniconda3"),
+                    PathBuf::from(r"C:\Users\me\miniconda3\envs\py3"),
+                ],
+            }
+        );
+        assert_eq!(parse_info("{}"), Info::default());
+    }
+
+    #[test]
+    fn only_outer_keys() {
+        let info = parse_info(
+            r#"{
+  "note": "\"root_prefix\": \"/wrong\"",
+  "config": {"envs": ["/wrong"], "count": 1},
+  "channels": ["envs", "root_prefix"],
+  "offline": false,
+  "envs": ["/opt/conda", "/opt/conda/envs/\ud83d\ude00"],
+  "root_prefix": "/opt/conda"
+}"#,
+        );
+        assert_eq!(
+            info,
+            Info {
+                root_prefix: Some(PathBuf::from("/opt/conda")),
+                envs: vec![
+                    PathBuf::from("/opt/conda"),
+                    PathBuf::from("/opt/conda/envs/\u{1F600}"),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn surrogates() {
+        assert_eq!(
+            string(r#""a\ud83d\ude00b" rest"#),
+            Some(("a\u{1F600}b".to_owned(), " rest"))
+        );
+        // A lone surrogate isn't a character
+        assert_eq!(string(r#""\ud83d""#), None);
+        assert_eq!(string(r#""\ud83d\u0041""#), None);
+    }
+
+    #[test]
+    fn names() {
+        let info = Info {
+            root_prefix: Some(PathBuf::from("/opt/conda")),
+            envs: vec![
+                PathBuf::from("/opt/conda"),
+                PathBuf::from("/opt/conda/envs/py311"),
+            ],
+        };
+        assert_eq!(resolve(&info, "base"), Some(PathBuf::from("/opt/conda")));
+        assert_eq!(
+            resolve(&info, "py311"),
+            Some(PathBuf::from("/opt/conda/envs/py311"))
+        );
+        assert_eq!(resolve(&info, "missing"), None);
+    }
+}
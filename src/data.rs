@@ -0,0 +1,432 @@
+//! A snapshot of configuration values, and comparisons between snapshots
+
+use crate::{query, Error, Libc, PyResult, PyVersion};
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Escapes backslashes and newlines, so that a value fits on one line
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Reverses `escape`
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some(c) => out.push(c),
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// The prefix of `name=value` lines that hold config vars
+const CONFIG_VAR_PREFIX: &str = "config_var.";
+
+/// The prefix of `!name=error` lines, for fields that the interpreter
+/// failed to compute
+const FAILED_PREFIX: &str = "!";
+
+/// A snapshot of a Python distribution's configuration
+///
+/// Each field holds the same value as the `PythonConfig` method of the
+/// same name. Once you have a snapshot, accessing its values does not
+/// query the interpreter.
+///
+/// A snapshot taken with [`PythonConfig::snapshot`](struct.PythonConfig.html#method.snapshot)
+/// also holds every defined `sysconfig` config var, which you can access
+/// with [`config_var`](#method.config_var).
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PythonConfigData {
+    /// See [`PythonConfig::version_info`](struct.PythonConfig.html#method.version_info)
+    pub version: PyVersion,
+    /// See [`PythonConfig::executable`](struct.PythonConfig.html#method.executable)
+    pub executable: String,
+    /// See [`PythonConfig::prefix`](struct.PythonConfig.html#method.prefix)
+    pub prefix: String,
+    /// See [`PythonConfig::exec_prefix`](struct.PythonConfig.html#method.exec_prefix)
+    pub exec_prefix: String,
+    /// See [`PythonConfig::includes`](struct.PythonConfig.html#method.includes)
+    pub includes: String,
+    /// See [`PythonConfig::cflags`](struct.PythonConfig.html#method.cflags)
+    pub cflags: String,
+    /// See [`PythonConfig::libs`](struct.PythonConfig.html#method.libs)
+    pub libs: String,
+    /// See [`PythonConfig::ldflags`](struct.PythonConfig.html#method.ldflags)
+    pub ldflags: String,
+    /// See [`PythonConfig::extension_suffix`](struct.PythonConfig.html#method.extension_suffix)
+    pub extension_suffix: String,
+    /// See [`PythonConfig::abi_flags`](struct.PythonConfig.html#method.abi_flags)
+    pub abi_flags: String,
+    /// See [`PythonConfig::config_dir`](struct.PythonConfig.html#method.config_dir)
+    pub config_dir: String,
+    /// See [`PythonConfig::libc`](struct.PythonConfig.html#method.libc)
+    pub libc: Option<Libc>,
+    /// Every defined `sysconfig` config var, by name. Values are in their
+    /// string form.
+    pub config_vars: BTreeMap<String, String>,
+}
+
+impl PythonConfigData {
+    /// Returns the value of the config var called `name`, like
+    /// [`PythonConfig::config_var`](struct.PythonConfig.html#method.config_var)
+    pub fn config_var(&self, name: &str) -> Option<&str> {
+        self.config_vars.get(name).map(String::as_str)
+    }
+
+    /// Returns the `LIBDIR` config var, like
+    /// [`PythonConfig::lib_dir`](struct.PythonConfig.html#method.lib_dir)
+    pub fn lib_dir(&self) -> Option<PathBuf> {
+        self.config_var("LIBDIR").map(PathBuf::from)
+    }
+
+    /// Returns the `LIBPL` config var, like
+    /// [`PythonConfig::config_lib_dir`](struct.PythonConfig.html#method.config_lib_dir)
+    pub fn config_lib_dir(&self) -> Option<PathBuf> {
+        self.config_var("LIBPL").map(PathBuf::from)
+    }
+
+    /// Returns the `LDLIBRARY` config var, like
+    /// [`PythonConfig::ld_library_name`](struct.PythonConfig.html#method.ld_library_name)
+    pub fn ld_library_name(&self) -> Option<&str> {
+        self.config_var("LDLIBRARY")
+    }
+
+    /// Returns the `INSTSONAME` config var, like
+    /// [`PythonConfig::inst_so_name`](struct.PythonConfig.html#method.inst_so_name)
+    pub fn inst_so_name(&self) -> Option<&str> {
+        self.config_var("INSTSONAME")
+    }
+
+    /// Returns the name and value of every field, in declaration order,
+    /// except for the config vars
+    ///
+    /// The names are the same as the field names.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("version", self.version.to_string()),
+            ("executable", self.executable.clone()),
+            ("prefix", self.prefix.clone()),
+            ("exec_prefix", self.exec_prefix.clone()),
+            ("includes", self.includes.clone()),
+            ("cflags", self.cflags.clone()),
+            ("libs", self.libs.clone()),
+            ("ldflags", self.ldflags.clone()),
+            ("extension_suffix", self.extension_suffix.clone()),
+            ("abi_flags", self.abi_flags.clone()),
+            ("config_dir", self.config_dir.clone()),
+            (
+                "libc",
+                self.libc.map(|libc| libc.to_string()).unwrap_or_default(),
+            ),
+        ]
+    }
+
+    /// Renders the snapshot as `name=value` lines, one per field, in the
+    /// order returned by [`fields`](#method.fields), followed by a
+    /// `config_var.NAME=value` line for each config var
+    ///
+    /// Backslashes and newlines in values are escaped as `\\` and `\n`.
+    /// [`parse`](#method.parse) turns the text back into a snapshot.
+    pub fn to_text(&self) -> String {
+        let config_vars = self
+            .config_vars
+            .iter()
+            .map(|(name, value)| (format!("{}{}", CONFIG_VAR_PREFIX, name), value.clone()));
+        self.fields()
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value))
+            .chain(config_vars)
+            .map(|(name, value)| format!("{}={}\n", name, escape(&value)))
+            .collect()
+    }
+
+    /// Parses a snapshot from the `name=value` lines produced by
+    /// [`to_text`](#method.to_text)
+    ///
+    /// Every field must be present, except for `libc`, which older
+    /// snapshots don't have. An empty `libc` means that there's no libc
+    /// to report. Blank lines, lines starting
+    /// with `#`, and unknown fields are ignored.
+    ///
+    /// Our scripts start each line with `__PCRS__ `. If any line does,
+    /// only those lines are parsed, so that warnings, and anything else
+    /// that the interpreter printed, are ignored. A `__PCRS__ !name=error`
+    /// line says that the interpreter failed to compute the field `name`.
+    pub fn parse(text: &str) -> PyResult<Self> {
+        let sentinel = format!("{} ", query::SENTINEL);
+        let keyed = text.lines().any(|line| line.starts_with(&sentinel));
+        let mut values = HashMap::new();
+        let mut failures = HashMap::new();
+        let mut config_vars = BTreeMap::new();
+        for line in text.lines() {
+            let line = match line.strip_prefix(&sentinel) {
+                Some(line) => line,
+                None if keyed => continue,
+                None => line,
+            };
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = line.split_once('=').ok_or_else(|| Error::ParseError {
+                expected: "expected snapshot lines resembling 'name=value'",
+                output: line.to_owned(),
+            })?;
+            let name = name.trim();
+            if let Some(name) = name.strip_prefix(FAILED_PREFIX) {
+                failures.insert(name, unescape(value));
+                continue;
+            }
+            match name.strip_prefix(CONFIG_VAR_PREFIX) {
+                Some(var) => {
+                    config_vars.insert(var.to_owned(), unescape(value));
+                }
+                None => {
+                    values.insert(name, unescape(value));
+                }
+            }
+        }
+        let libc = match values.remove("libc") {
+            Some(ref libc) if !libc.is_empty() => Some(libc.parse()?),
+            _ => None,
+        };
+        let mut take = |name: &str| {
+            values.remove(name).ok_or_else(|| match failures.get(name) {
+                Some(failure) => Error::Other(
+                    format!("the interpreter failed to compute {}: {}", name, failure).into(),
+                ),
+                None => Error::Other(format!("snapshot is missing the {} field", name).into()),
+            })
+        };
+        Ok(PythonConfigData {
+            version: take("version")?.parse()?,
+            executable: take("executable")?,
+            prefix: take("prefix")?,
+            exec_prefix: take("exec_prefix")?,
+            includes: take("includes")?,
+            cflags: take("cflags")?,
+            libs: take("libs")?,
+            ldflags: take("ldflags")?,
+            extension_suffix: take("extension_suffix")?,
+            abi_flags: take("abi_flags")?,
+            config_dir: take("config_dir")?,
+            libc,
+            config_vars,
+        })
+    }
+
+    /// Writes the snapshot to the file at `path`, in the format
+    /// described by [`to_text`](#method.to_text)
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /// Loads a snapshot previously written by [`save`](#method.save)
+    pub fn load<P: AsRef<Path>>(path: P) -> PyResult<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Compares this snapshot to `other`, returning the fields
+    /// that have different values
+    ///
+    /// Only the fields returned by [`fields`](#method.fields) are compared.
+    /// Config vars include build details, like the build date, that vary
+    /// between otherwise equivalent installations.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let ours = PythonConfig::new().snapshot().unwrap();
+    /// let theirs = PythonConfig::interpreter("/usr/bin/python3")
+    ///     .and_then(|cfg| cfg.snapshot())
+    ///     .unwrap();
+    /// for field in ours.diff(&theirs).iter() {
+    ///     println!("{}: '{}' != '{}'", field.name, field.left, field.right);
+    /// }
+    /// ```
+    pub fn diff(&self, other: &PythonConfigData) -> ConfigDiff {
+        let fields = self
+            .fields()
+            .into_iter()
+            .zip(other.fields())
+            .filter(|((_, left), (_, right))| left != right)
+            .map(|((name, left), (_, right))| FieldDiff { name, left, right })
+            .collect();
+        ConfigDiff { fields }
+    }
+}
+
+/// A field that differs between two [`PythonConfigData`](struct.PythonConfigData.html)
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldDiff {
+    /// The name of the field, as returned by
+    /// [`PythonConfigData::fields`](struct.PythonConfigData.html#method.fields)
+    pub name: &'static str,
+    /// The value of the field in the snapshot that was diffed
+    pub left: String,
+    /// The value of the field in the other snapshot
+    pub right: String,
+}
+
+/// The differences between two [`PythonConfigData`](struct.PythonConfigData.html),
+/// returned by [`PythonConfigData::diff`](struct.PythonConfigData.html#method.diff)
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConfigDiff {
+    fields: Vec<FieldDiff>,
+}
+
+impl ConfigDiff {
+    /// Returns `true` if the two snapshots are the same
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns `true` if the field called `name` differs
+    pub fn contains(&self, name: &str) -> bool {
+        self.fields.iter().any(|field| field.name == name)
+    }
+
+    /// Iterates over the differing fields, in declaration order
+    pub fn iter(&self) -> std::slice::Iter<'_, FieldDiff> {
+        self.fields.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ConfigDiff {
+    type Item = &'a FieldDiff;
+    type IntoIter = std::slice::Iter<'a, FieldDiff>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, unescape, PythonConfigData};
+    use crate::{Libc, PyVersion, ReleaseLevel};
+
+    fn data() -> PythonConfigData {
+        PythonConfigData {
+            version: PyVersion {
+                major: 3,
+                minor: 11,
+                micro: 7,
+                releaselevel: ReleaseLevel::Final,
+                serial: 0,
+            },
+            executable: "/usr/bin/python3".to_owned(),
+            prefix: "/usr".to_owned(),
+            exec_prefix: "/usr".to_owned(),
+            includes: "-I/usr/include/python3.11 -I/usr/include/python3.11".to_owned(),
+            cflags: "-I/usr/include/python3.11 -I/usr/include/python3.11".to_owned(),
+            libs: "-ldl -lm".to_owned(),
+            ldflags: "-L/usr/lib -ldl -lm".to_owned(),
+            extension_suffix: ".cpython-311-x86_64-linux-gnu.so".to_owned(),
+            abi_flags: "".to_owned(),
+            config_dir: "/usr/lib/python3.11/config-3.11-x86_64-linux-gnu".to_owned(),
+            libc: Some(Libc::Glibc {
+                major: 2,
+                minor: 35,
+            }),
+            config_vars: vec![
+                ("LDVERSION", "3.11"),
+                ("Py_ENABLE_SHARED", "1"),
+                ("ODD", "two\nlines \\ and a backslash"),
+            ]
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value.to_owned()))
+            .collect(),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let json = serde_json::to_string(&data()).unwrap();
+        let parsed: PythonConfigData = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, data());
+    }
+
+    #[test]
+    fn escaping() {
+        for value in &["", "plain", "a\nb", "back\\slash\\n", "trailing\\"] {
+            assert_eq!(unescape(&escape(value)), *value);
+            assert!(!escape(value).contains('\n'));
+        }
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let text = data().to_text();
+        assert!(text.starts_with("version=3.11.7\nexecutable=/usr/bin/python3\n"));
+        assert!(text.contains("config_var.ODD=two\\nlines \\\\ and a backslash\n"));
+        let parsed = PythonConfigData::parse(&text).unwrap();
+        assert_eq!(parsed, data());
+        assert_eq!(parsed.config_var("LDVERSION"), Some("3.11"));
+        assert!(PythonConfigData::parse("version=3.11.7\n").is_err());
+    }
+
+    #[test]
+    fn keyed_lines() {
+        let keyed: String = data()
+            .to_text()
+            .lines()
+            .map(|line| format!("__PCRS__ {}\nDeprecationWarning: noise\n", line))
+            .collect();
+        assert_eq!(PythonConfigData::parse(&keyed).unwrap(), data());
+
+        let failed = keyed.replace(
+            "__PCRS__ libc=glibc 2.35",
+            "__PCRS__ !libc=OSError: no loader",
+        );
+        assert_eq!(PythonConfigData::parse(&failed).unwrap().libc, None);
+
+        let failed = keyed.replace("__PCRS__ prefix=/usr\n", "__PCRS__ !prefix=KeyError\n");
+        let err = PythonConfigData::parse(&failed).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the interpreter failed to compute prefix: KeyError"
+        );
+    }
+
+    #[test]
+    fn without_libc() {
+        let text = data().to_text().replace("libc=glibc 2.35\n", "");
+        assert_eq!(PythonConfigData::parse(&text).unwrap().libc, None);
+        let text = data().to_text().replace("glibc 2.35", "");
+        assert_eq!(PythonConfigData::parse(&text).unwrap().libc, None);
+    }
+
+    #[test]
+    fn no_differences() {
+        assert!(data().diff(&data()).is_empty());
+    }
+
+    #[test]
+    fn differences() {
+        let left = data();
+        let mut right = data();
+        right.version.minor = 12;
+        right.abi_flags = "t".to_owned();
+        let diff = left.diff(&right);
+        let names: Vec<&str> = diff.iter().map(|field| field.name).collect();
+        assert_eq!(names, ["version", "abi_flags"]);
+        assert!(diff.contains("version"));
+        assert!(!diff.contains("prefix"));
+    }
+}
@@ -0,0 +1,293 @@
+//! Checking that a Python distribution is ready to build against
+
+use crate::{
+    origin, parse_bool, parse_err, query, Commander, InstallOrigin, PyResult, PythonConfig,
+};
+
+use std::fmt;
+use std::fs;
+
+/// Something that [`PythonConfig::diagnose`](struct.PythonConfig.html#method.diagnose)
+/// checks
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Check {
+    /// The interpreter exists, and it runs
+    Interpreter,
+    /// `Python.h` is in the reported include directory
+    Headers,
+    /// libpython is in one of the library directories
+    Libpython,
+    /// The OS package that holds the development files, like
+    /// `python3.11-dev`, is installed. We only check OS packages.
+    DevPackage,
+}
+
+impl Check {
+    /// Returns a short name for the check, like `headers`
+    pub fn name(self) -> &'static str {
+        match self {
+            Check::Interpreter => "interpreter",
+            Check::Headers => "headers",
+            Check::Libpython => "libpython",
+            Check::DevPackage => "dev package",
+        }
+    }
+}
+
+/// The result of one [`Check`](enum.Check.html)
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    /// What we checked
+    pub check: Check,
+    /// Whether the check passed
+    pub passed: bool,
+    /// What we found, like the interpreter's path, or the file that's
+    /// missing
+    pub detail: String,
+    /// What to do about a failed check, like which package to install
+    pub hint: Option<String>,
+}
+
+/// The results of [`PythonConfig::diagnose`](struct.PythonConfig.html#method.diagnose)
+///
+/// The `Display` implementation prints one line per check, and a hint
+/// under each failed check.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostics {
+    /// The results, in the order that we checked. If the interpreter
+    /// doesn't run, that's the only result.
+    pub checks: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Returns `true` if every check passed
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(|diagnostic| diagnostic.passed)
+    }
+
+    /// Returns the checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.checks.iter().filter(|diagnostic| !diagnostic.passed)
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for diagnostic in &self.checks {
+            let status = if diagnostic.passed { "ok" } else { "FAILED" };
+            writeln!(
+                f,
+                "{:<6}  {}: {}",
+                status,
+                diagnostic.check.name(),
+                diagnostic.detail
+            )?;
+            if let Some(ref hint) = diagnostic.hint {
+                writeln!(f, "{:<6}  hint: {}", "", hint)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where a distribution keeps its development files, and whether they
+/// exist
+pub(crate) struct DevFiles {
+    /// The directory that should hold `Python.h`
+    pub include: String,
+    pub has_headers: bool,
+    pub has_libpython: bool,
+    /// The distribution's `pythonX.Y-config` script, if it defines a
+    /// `BINDIR`
+    pub config_script: Option<String>,
+    pub has_config_script: bool,
+    /// The `X.Y.Z` version of the interpreter
+    pub version: String,
+}
+
+/// Returns the content of the host's `os-release` file, if any
+pub(crate) fn os_release() -> Option<String> {
+    fs::read_to_string("/etc/os-release")
+        .or_else(|_| fs::read_to_string("/usr/lib/os-release"))
+        .ok()
+}
+
+impl<C: Commander> PythonConfig<C> {
+    /// Runs the `DEV_FILES` query
+    pub(crate) fn dev_files(&self) -> PyResult<DevFiles> {
+        let resp = self.script(query::DEV_FILES)?;
+        let lines: Vec<&str> = resp.lines().collect();
+        if let [include, has_headers, has_libpython, config_script, has_config_script, version] =
+            lines[..]
+        {
+            Ok(DevFiles {
+                include: include.to_owned(),
+                has_headers: parse_bool(has_headers)?,
+                has_libpython: parse_bool(has_libpython)?,
+                config_script: Some(config_script.to_owned()).filter(|script| !script.is_empty()),
+                has_config_script: parse_bool(has_config_script)?,
+                version: version.to_owned(),
+            })
+        } else {
+            Err(parse_err(
+                "expected six lines describing the headers and libraries",
+                &resp,
+            ))
+        }
+    }
+
+    /// Checks that we can build against this Python distribution: that the
+    /// interpreter runs, that `Python.h` is in the reported include
+    /// directory, that libpython is where the distribution says, and,
+    /// for OS packages, that the development package is installed
+    ///
+    /// Failed checks come with a hint, like the package to install. Unlike
+    /// the other methods, this doesn't fail; a failed query is a failed
+    /// check. Also see [`missing_headers_hint`](#method.missing_headers_hint).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let diagnostics = PythonConfig::new().diagnose();
+    /// if !diagnostics.is_healthy() {
+    ///     eprint!("{}", diagnostics);
+    /// }
+    /// ```
+    pub fn diagnose(&self) -> Diagnostics {
+        let mut checks = Vec::new();
+        let interpreter = self
+            .executable()
+            .and_then(|executable| Ok((executable, self.version_info()?)));
+        match interpreter {
+            Ok((executable, version)) => checks.push(Diagnostic {
+                check: Check::Interpreter,
+                passed: true,
+                detail: format!("{} (Python {})", executable, version),
+                hint: None,
+            }),
+            Err(err) => {
+                checks.push(Diagnostic {
+                    check: Check::Interpreter,
+                    passed: false,
+                    detail: err.to_string(),
+                    hint: Some(
+                        "install Python 3, or set PYTHON_CONFIG_INTERPRETER to use another interpreter"
+                            .to_owned(),
+                    ),
+                });
+                return Diagnostics { checks };
+            }
+        }
+
+        let files = match self.dev_files() {
+            Ok(files) => files,
+            Err(err) => {
+                for check in [Check::Headers, Check::Libpython] {
+                    checks.push(Diagnostic {
+                        check,
+                        passed: false,
+                        detail: err.to_string(),
+                        hint: None,
+                    });
+                }
+                return Diagnostics { checks };
+            }
+        };
+        let install_origin = self.install_origin().unwrap_or(InstallOrigin::Unknown);
+        let os_release = os_release();
+        let hint = || origin::install_hint(install_origin, os_release.as_deref(), &files.version);
+
+        checks.push(Diagnostic {
+            check: Check::Headers,
+            passed: files.has_headers,
+            detail: if files.has_headers {
+                format!("Python.h found in {}", files.include)
+            } else {
+                format!("Python.h not found in {}", files.include)
+            },
+            hint: Some(hint()).filter(|_| !files.has_headers),
+        });
+        checks.push(Diagnostic {
+            check: Check::Libpython,
+            passed: files.has_libpython,
+            detail: if files.has_libpython {
+                format!("libpython for Python {} found", files.version)
+            } else {
+                format!("libpython for Python {} not found", files.version)
+            },
+            hint: Some(hint()).filter(|_| !files.has_libpython),
+        });
+        if let (InstallOrigin::OsPackage, Some(script)) = (install_origin, &files.config_script) {
+            checks.push(Diagnostic {
+                check: Check::DevPackage,
+                passed: files.has_config_script,
+                detail: if files.has_config_script {
+                    format!("{} found", script)
+                } else {
+                    format!("{} not found", script)
+                },
+                hint: Some(hint()).filter(|_| !files.has_config_script),
+            });
+        }
+        Diagnostics { checks }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Check, Diagnostic, Diagnostics};
+    use crate::{PythonConfig, SysCommand, Version};
+
+    #[test]
+    fn display() {
+        let diagnostics = Diagnostics {
+            checks: vec![
+                Diagnostic {
+                    check: Check::Interpreter,
+                    passed: true,
+                    detail: "/usr/bin/python3 (Python 3.11.7)".to_owned(),
+                    hint: None,
+                },
+                Diagnostic {
+                    check: Check::Headers,
+                    passed: false,
+                    detail: "Python.h not found in /usr/include/python3.11".to_owned(),
+                    hint: Some("install the python3.11-dev package".to_owned()),
+                },
+            ],
+        };
+        assert!(!diagnostics.is_healthy());
+        assert_eq!(diagnostics.failures().count(), 1);
+        assert_eq!(
+            diagnostics.to_string(),
+            "ok      interpreter: /usr/bin/python3 (Python 3.11.7)\n\
+             FAILED  headers: Python.h not found in /usr/include/python3.11\n\
+             \x20       hint: install the python3.11-dev package\n"
+        );
+    }
+
+    #[test]
+    fn healthy() {
+        let diagnostics = PythonConfig::new().diagnose();
+        assert!(diagnostics.is_healthy(), "{}", diagnostics);
+        assert_eq!(diagnostics.checks[0].check, Check::Interpreter);
+        assert!(diagnostics.checks.len() >= 3);
+    }
+
+    #[test]
+    fn missing_interpreter() {
+        let cfg = PythonConfig::with_commander(
+            Version::Three,
+            SysCommand::new("this-program-does-not-exist-python"),
+        );
+        let diagnostics = cfg.diagnose();
+        assert_eq!(diagnostics.checks.len(), 1);
+        assert!(!diagnostics.checks[0].passed);
+        assert!(diagnostics.checks[0].hint.is_some());
+    }
+}
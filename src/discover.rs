@@ -0,0 +1,1395 @@
+//! Finding the Python interpreters installed on this system
+
+use crate::cache::find_program;
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Describes how we found a [`DiscoveredInterpreter`](struct.DiscoveredInterpreter.html)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Source {
+    /// The GitHub Actions tool cache used by `setup-python`
+    /// (`RUNNER_TOOL_CACHE`)
+    ToolCache,
+    /// An installation managed by the asdf version manager
+    Asdf,
+    /// An installation managed by uv (`uv python install`)
+    Uv,
+    /// An interpreter on the `PATH`
+    Path,
+    /// An interpreter in a well-known system directory, like `/usr/bin`
+    System,
+    /// An installation managed by pyenv
+    Pyenv,
+    /// An interpreter listed by the Windows `py` launcher
+    PyLauncher,
+    /// A Homebrew keg in the Cellar
+    Homebrew,
+    /// An installation registered in the Windows registry, per PEP 514
+    Registry,
+}
+
+/// A Python interpreter found on this system
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiscoveredInterpreter {
+    /// The path to the interpreter
+    pub path: PathBuf,
+    /// The interpreter's version
+    pub version: semver::Version,
+    /// How we found the interpreter
+    pub source: Source,
+}
+
+/// Parses a version from a directory name, like `3.11.7` or `3.13.0-rc.2`.
+/// Two-component versions, like `3.12`, are treated as `3.12.0`.
+fn parse_dir_version(name: &str) -> Option<semver::Version> {
+    semver::Version::parse(name)
+        .or_else(|_| semver::Version::parse(&format!("{}.0", name)))
+        .ok()
+}
+
+/// The name of the interpreter executable within an installation
+fn executable_in(install: &Path) -> PathBuf {
+    if cfg!(windows) {
+        install.join("python.exe")
+    } else {
+        install.join("bin").join("python3")
+    }
+}
+
+/// The tool cache's name for the architecture of this host
+fn toolcache_arch() -> &'static str {
+    if cfg!(target_arch = "aarch64") {
+        "arm64"
+    } else if cfg!(target_arch = "x86") {
+        "x86"
+    } else {
+        "x64"
+    }
+}
+
+/// Enumerates the `Python/<version>/<arch>` installations in the tool
+/// cache at `root`, skipping any that `setup-python` did not finish
+/// installing.
+fn toolcache_in(root: &Path) -> Vec<DiscoveredInterpreter> {
+    let arch = toolcache_arch();
+    let entries = match fs::read_dir(root.join("Python")) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut found: Vec<DiscoveredInterpreter> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let version = parse_dir_version(entry.file_name().to_str()?)?;
+            let install = entry.path().join(arch);
+            let complete = entry.path().join(format!("{}.complete", arch));
+            let path = executable_in(&install);
+            if complete.is_file() && path.is_file() {
+                Some(DiscoveredInterpreter {
+                    path,
+                    version,
+                    source: Source::ToolCache,
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    sort_newest_first(&mut found);
+    found
+}
+
+fn sort_newest_first(found: &mut [DiscoveredInterpreter]) {
+    found.sort_by(|left, right| right.version.cmp(&left.version));
+}
+
+/// Enumerates the Pythons installed by `setup-python` into the GitHub
+/// Actions tool cache, newest first
+///
+/// The tool cache is found through the `RUNNER_TOOL_CACHE` environment
+/// variable, falling back to `AGENT_TOOLSDIRECTORY`. Only installations
+/// matching the host's architecture are returned. Outside of GitHub
+/// Actions, the return is empty.
+pub fn hostedtoolcache() -> Vec<DiscoveredInterpreter> {
+    env::var_os("RUNNER_TOOL_CACHE")
+        .or_else(|| env::var_os("AGENT_TOOLSDIRECTORY"))
+        .map(|root| toolcache_in(Path::new(&root)))
+        .unwrap_or_default()
+}
+
+/// The user's home directory
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// The asdf data directory, `ASDF_DATA_DIR` or `~/.asdf`
+fn asdf_data_dir() -> Option<PathBuf> {
+    env::var_os("ASDF_DATA_DIR")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".asdf")))
+}
+
+/// Returns the Python versions listed in a `.tool-versions` file,
+/// most preferred first. The special `system` version is skipped.
+fn tool_versions_python(text: &str) -> Vec<&str> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or(""))
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            if words.next() == Some("python") {
+                Some(words)
+            } else {
+                None
+            }
+        })
+        .flatten()
+        .filter(|version| *version != "system")
+        .collect()
+}
+
+/// Finds the Python versions selected by the nearest `.tool-versions`
+/// file, searching from `dir` up to the root, then the home directory.
+fn tool_versions_from(dir: &Path) -> Vec<String> {
+    let home = home_dir().map(|home| home.join(".tool-versions"));
+    dir.ancestors()
+        .map(|dir| dir.join(".tool-versions"))
+        .chain(home)
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .map(|text| {
+            tool_versions_python(&text)
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        })
+        .find(|versions| !versions.is_empty())
+        .unwrap_or_default()
+}
+
+/// Enumerates the Pythons installed by asdf, newest first
+///
+/// Installations are found under `$ASDF_DATA_DIR/installs/python`, or
+/// `~/.asdf/installs/python` if `ASDF_DATA_DIR` is not set. Installations
+/// that aren't named by a CPython version, like `pypy3.10-7.3.15`, are
+/// skipped.
+pub fn asdf() -> Vec<DiscoveredInterpreter> {
+    let installs = match asdf_data_dir() {
+        Some(data) => data.join("installs").join("python"),
+        None => return Vec::new(),
+    };
+    let entries = match fs::read_dir(installs) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut found: Vec<DiscoveredInterpreter> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let version = parse_dir_version(entry.file_name().to_str()?)?;
+            let path = executable_in(&entry.path());
+            if path.is_file() {
+                Some(DiscoveredInterpreter {
+                    path,
+                    version,
+                    source: Source::Asdf,
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    sort_newest_first(&mut found);
+    found
+}
+
+/// Returns the asdf-managed Python selected for the current directory
+///
+/// We first ask asdf itself, with `asdf which python3`. If asdf is not
+/// on the `PATH`, we respect the `ASDF_PYTHON_VERSION` environment
+/// variable, then the nearest `.tool-versions` file, choosing the
+/// first listed version that's installed.
+pub fn asdf_current() -> Option<DiscoveredInterpreter> {
+    let installed = asdf();
+    if let Some(path) = manager_which("asdf") {
+        return installed.into_iter().find(|i| i.path == path);
+    }
+
+    let versions = match env::var("ASDF_PYTHON_VERSION") {
+        Ok(version) => vec![version],
+        Err(_) => tool_versions_from(&env::current_dir().ok()?),
+    };
+    versions.iter().find_map(|version| {
+        let version = parse_dir_version(version)?;
+        installed.iter().find(|i| i.version == version).cloned()
+    })
+}
+
+/// Asks a version manager, like asdf or pyenv, for the real path of the
+/// selected `python3`
+fn manager_which(manager: &str) -> Option<PathBuf> {
+    let out = Command::new(manager)
+        .args(["which", "python3"])
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let path = String::from_utf8(out.stdout).ok()?;
+    let path = path.trim();
+    if path.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+/// If `path` is an asdf shim, returns the real interpreter that the shim
+/// would run. Otherwise, returns `path` unchanged.
+///
+/// Shims are scripts in `$ASDF_DATA_DIR/shims` that dispatch to the
+/// version selected for the current directory, so their path says
+/// nothing about the interpreter.
+pub fn resolve_shim(path: &Path) -> PathBuf {
+    let is_shim = asdf_data_dir()
+        .map(|data| path.starts_with(data.join("shims")))
+        .unwrap_or(false);
+    if is_shim {
+        if let Some(current) = asdf_current() {
+            return current.path;
+        }
+    }
+    path.to_owned()
+}
+
+/// The directory where uv installs Pythons
+fn uv_python_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("UV_PYTHON_INSTALL_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        env::var_os("APPDATA")
+            .map(|appdata| Path::new(&appdata).join("uv").join("data").join("python"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|home| home.join(".local").join("share")))
+            .map(|data| data.join("uv").join("python"))
+    }
+}
+
+/// Parses the version of a uv installation from its directory name, like
+/// `cpython-3.12.1-linux-x86_64-gnu`. Variants, like the `freethreaded`
+/// in `cpython-3.13.0+freethreaded-linux-x86_64-gnu`, become semver build
+/// metadata. Returns `None` for other implementations.
+fn parse_uv_dir(name: &str) -> Option<semver::Version> {
+    let mut parts = name.split('-');
+    if parts.next()? != "cpython" {
+        return None;
+    }
+    let version = parts.next()?;
+    let (version, variant) = match version.split_once('+') {
+        Some((version, variant)) => (version, Some(variant)),
+        None => (version, None),
+    };
+    let mut version: semver::Version = version.parse::<crate::PyVersion>().ok()?.into();
+    if let Some(variant) = variant {
+        version
+            .build
+            .push(semver::Identifier::AlphaNumeric(variant.to_owned()));
+    }
+    Some(version)
+}
+
+/// Enumerates the CPython interpreters installed by `uv python install`,
+/// newest first
+///
+/// Installations are found in `UV_PYTHON_INSTALL_DIR`, if set. Otherwise,
+/// we look in `$XDG_DATA_HOME/uv/python` or `~/.local/share/uv/python`,
+/// or `%APPDATA%\uv\data\python` on Windows.
+pub fn uv() -> Vec<DiscoveredInterpreter> {
+    let entries = match uv_python_dir().map(fs::read_dir) {
+        Some(Ok(entries)) => entries,
+        _ => return Vec::new(),
+    };
+    let mut found: Vec<DiscoveredInterpreter> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let version = parse_uv_dir(entry.file_name().to_str()?)?;
+            let path = executable_in(&entry.path());
+            if path.is_file() {
+                Some(DiscoveredInterpreter {
+                    path,
+                    version,
+                    source: Source::Uv,
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    sort_newest_first(&mut found);
+    found
+}
+
+/// Returns `true` if `name` looks like the file name of a Python
+/// interpreter, like `python`, `python3`, or `python3.12`
+fn is_python_name(name: &str) -> bool {
+    let name = if cfg!(windows) {
+        match name.strip_suffix(".exe") {
+            Some(name) => name,
+            None => return false,
+        }
+    } else {
+        name
+    };
+    let version = match name.strip_prefix("python") {
+        Some(version) => version,
+        None => return false,
+    };
+    let mut parts = version.splitn(2, '.');
+    let major = parts.next().unwrap_or("");
+    let minor = parts.next();
+    major.chars().all(|c| c.is_ascii_digit())
+        && minor
+            .map(|minor| {
+                !major.is_empty() && !minor.is_empty() && minor.chars().all(|c| c.is_ascii_digit())
+            })
+            .unwrap_or(true)
+}
+
+/// Lists the files in `dir` that look like interpreters, sorted by name
+fn interpreters_in(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .map(is_python_name)
+                    .unwrap_or(false)
+            })
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths
+}
+
+/// Runs the interpreter at `path`, returning its version and the
+/// interpreter that it reports in `sys.executable`
+fn probe(path: &Path) -> Option<(semver::Version, PathBuf)> {
+    let out = Command::new(path)
+        .args([
+            "-c",
+            "import sys\nprint(' '.join(str(v) for v in sys.version_info[:5]))\nprint(sys.executable)",
+        ])
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let out = String::from_utf8(out.stdout).ok()?;
+    let mut lines = out.lines();
+    let version = crate::pyversion::parse(lines.next()?)?;
+    let executable = lines.next().map(str::trim).filter(|exe| !exe.is_empty());
+    Some((
+        version.into(),
+        executable
+            .map(PathBuf::from)
+            .unwrap_or_else(|| path.to_owned()),
+    ))
+}
+
+/// Resolves symlinks in `path`, or returns `path` if it doesn't exist
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+/// Probes every candidate, skipping candidates that resolve to an
+/// interpreter that's already in `seen`
+///
+/// Interpreters are the same if they resolve to the same file, so
+/// `python3`, and the `python3.12` that it links to, are reported once,
+/// with the first name. The interpreter's `sys.executable` counts, too,
+/// so a pyenv shim and the interpreter that it selects are reported once.
+fn probe_all<I>(
+    candidates: I,
+    source: Source,
+    seen: &mut HashSet<PathBuf>,
+) -> Vec<DiscoveredInterpreter>
+where
+    I: IntoIterator<Item = PathBuf>,
+{
+    let mut found = Vec::new();
+    for path in candidates {
+        let resolved = canonical(&path);
+        if seen.contains(&resolved) {
+            continue;
+        }
+        if let Some((version, executable)) = probe(&path) {
+            if seen.insert(canonical(&executable)) {
+                found.push(DiscoveredInterpreter {
+                    path,
+                    version,
+                    source,
+                });
+            }
+        }
+        seen.insert(resolved);
+    }
+    found
+}
+
+/// Enumerates the interpreters in the directories on the `PATH`, in `PATH`
+/// order
+///
+/// Each interpreter is run to learn its version. Names that resolve to
+/// the same interpreter, like `python3` and `python3.12`, or a pyenv shim
+/// and the interpreter it selects, are reported once.
+pub fn path() -> Vec<DiscoveredInterpreter> {
+    probe_all(path_candidates(), Source::Path, &mut HashSet::new())
+}
+
+/// The interpreters in the directories on the `PATH`
+fn path_candidates() -> Vec<PathBuf> {
+    env::var_os("PATH")
+        .map(|path| {
+            env::split_paths(&path)
+                .flat_map(|dir| interpreters_in(&dir))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Picks the program that runs a Python `major` interpreter when the
+/// user didn't name one
+///
+/// We try `pythonX`, then the `py -X` launcher, then the newest versioned
+/// `pythonX.Y` on the `PATH`, then `python`. Returns the program and the
+/// arguments that select the version, or `None` if none of them are on
+/// the `PATH`. We don't run the programs, so an unversioned `python` is
+/// assumed to be the right major version.
+pub(crate) fn default_command(major: u64) -> Option<(String, Vec<String>)> {
+    choose_command(
+        major,
+        |name| find_program(name).is_some(),
+        path_candidates(),
+    )
+}
+
+/// Picks the program that runs a Python `major.minor` interpreter
+///
+/// We try `pythonX.Y`, then the `py -X.Y` launcher. If neither is on the
+/// `PATH`, we return `pythonX.Y`, so that the failure names it.
+pub(crate) fn versioned_command(major: u64, minor: u64) -> (String, Vec<String>) {
+    choose_versioned_command(major, minor, |name| find_program(name).is_some())
+}
+
+/// Implements `versioned_command`, given a test for whether a program is
+/// on the `PATH`
+fn choose_versioned_command<F>(major: u64, minor: u64, on_path: F) -> (String, Vec<String>)
+where
+    F: Fn(&str) -> bool,
+{
+    let name = format!("python{}.{}", major, minor);
+    if !on_path(&name) && on_path("py") {
+        ("py".to_owned(), vec![format!("-{}.{}", major, minor)])
+    } else {
+        (name, Vec::new())
+    }
+}
+
+/// Implements `default_command`, given a test for whether a program is on
+/// the `PATH`, and the interpreters on the `PATH`
+fn choose_command<F>(
+    major: u64,
+    on_path: F,
+    candidates: Vec<PathBuf>,
+) -> Option<(String, Vec<String>)>
+where
+    F: Fn(&str) -> bool,
+{
+    let name = format!("python{}", major);
+    if on_path(&name) {
+        return Some((name, Vec::new()));
+    }
+    if on_path("py") {
+        return Some(("py".to_owned(), vec![format!("-{}", major)]));
+    }
+    let prefix = format!("{}.", name);
+    let newest = candidates
+        .into_iter()
+        .filter_map(|path| {
+            let file = path.file_name()?.to_str()?;
+            let stem = file.strip_suffix(".exe").unwrap_or(file);
+            let minor: u64 = stem.strip_prefix(&prefix)?.parse().ok()?;
+            Some((minor, stem.to_owned()))
+        })
+        .fold(
+            None,
+            |newest: Option<(u64, String)>, (minor, stem)| match newest {
+                Some((newest_minor, _)) if newest_minor >= minor => newest,
+                _ => Some((minor, stem)),
+            },
+        );
+    if let Some((_, stem)) = newest {
+        return Some((stem, Vec::new()));
+    }
+    if on_path("python") {
+        Some(("python".to_owned(), Vec::new()))
+    } else {
+        None
+    }
+}
+
+/// The interpreters in the well-known directories that hold system
+/// interpreters
+fn system_candidates() -> Vec<PathBuf> {
+    if cfg!(windows) {
+        Vec::new()
+    } else {
+        ["/usr/bin", "/usr/local/bin", "/bin"]
+            .iter()
+            .flat_map(|dir| interpreters_in(Path::new(dir)))
+            .collect()
+    }
+}
+
+/// Enumerates the interpreters in well-known system directories, like
+/// `/usr/bin` and `/usr/local/bin`, whether or not they're on the `PATH`
+pub fn system() -> Vec<DiscoveredInterpreter> {
+    probe_all(system_candidates(), Source::System, &mut HashSet::new())
+}
+
+/// The pyenv root directory, `PYENV_ROOT` or `~/.pyenv`
+fn pyenv_root() -> Option<PathBuf> {
+    env::var_os("PYENV_ROOT")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".pyenv")))
+}
+
+/// Enumerates the `versions/<version>` installations under `root`
+fn pyenv_in(root: &Path) -> Vec<DiscoveredInterpreter> {
+    let entries = match fs::read_dir(root.join("versions")) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut found: Vec<DiscoveredInterpreter> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let version = parse_dir_version(entry.file_name().to_str()?)?;
+            let path = executable_in(&entry.path());
+            if path.is_file() {
+                Some(DiscoveredInterpreter {
+                    path,
+                    version,
+                    source: Source::Pyenv,
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    sort_newest_first(&mut found);
+    found
+}
+
+/// Enumerates the Pythons installed by pyenv, newest first
+///
+/// Installations are found under `$PYENV_ROOT/versions`, or
+/// `~/.pyenv/versions` if `PYENV_ROOT` is not set. Rather than the shims,
+/// which dispatch to whichever version is selected for the current
+/// directory, we return the installed interpreters. Installations that
+/// aren't named by a CPython version, like `miniconda3-latest`, are
+/// skipped.
+pub fn pyenv() -> Vec<DiscoveredInterpreter> {
+    pyenv_root().map(|root| pyenv_in(&root)).unwrap_or_default()
+}
+
+/// Returns the versions listed in a `.python-version` file, most
+/// preferred first. Versions may be separated by any whitespace, and
+/// the special `system` version is skipped.
+fn python_version_file(text: &str) -> Vec<&str> {
+    text.lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(str::split_whitespace)
+        .filter(|version| *version != "system")
+        .collect()
+}
+
+/// Finds the versions selected by the nearest `.python-version` file,
+/// searching from `dir` up to the root, then pyenv's global `version`
+/// file under `root`
+fn pyenv_versions_from(dir: &Path, root: &Path) -> Vec<String> {
+    dir.ancestors()
+        .map(|dir| dir.join(".python-version"))
+        .chain(Some(root.join("version")))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .map(|text| {
+            python_version_file(&text)
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        })
+        .find(|versions| !versions.is_empty())
+        .unwrap_or_default()
+}
+
+/// Returns the installation in `installed` that pyenv would run for the
+/// version `name`
+///
+/// Besides full versions, pyenv accepts prefixes, like `3.12`, which
+/// select the newest matching installation.
+fn pyenv_select<'a>(
+    installed: &'a [DiscoveredInterpreter],
+    root: &Path,
+    name: &str,
+) -> Option<&'a DiscoveredInterpreter> {
+    let path = executable_in(&root.join("versions").join(name));
+    let prefix = format!("{}.", name);
+    installed.iter().find(|i| i.path == path).or_else(|| {
+        installed
+            .iter()
+            .filter(|i| i.version.to_string().starts_with(&prefix))
+            .max_by(|left, right| left.version.cmp(&right.version))
+    })
+}
+
+/// Returns the pyenv-managed Python selected for the current directory
+///
+/// We first ask pyenv itself, with `pyenv which python3`. If pyenv is not
+/// on the `PATH`, we respect the `PYENV_VERSION` environment variable,
+/// then the nearest `.python-version` file, then pyenv's global version,
+/// choosing the first listed version that's installed.
+pub fn pyenv_current() -> Option<DiscoveredInterpreter> {
+    let root = pyenv_root()?;
+    let installed = pyenv_in(&root);
+    if let Some(path) = manager_which("pyenv") {
+        return installed.into_iter().find(|i| i.path == path);
+    }
+
+    let versions: Vec<String> = match env::var("PYENV_VERSION") {
+        Ok(versions) => versions.split(':').map(String::from).collect(),
+        Err(_) => pyenv_versions_from(&env::current_dir().ok()?, &root),
+    };
+    versions
+        .iter()
+        .find_map(|name| pyenv_select(&installed, &root, name))
+        .cloned()
+}
+
+/// Parses the interpreter path from a line of `py -0p` output
+///
+/// Newer launchers print lines like ` -V:3.12 *        C:\Python312\python.exe`,
+/// and older launchers print lines like ` -3.12-64        C:\Python312\python.exe`.
+/// The `*` marks the default interpreter.
+fn parse_py_launcher_line(line: &str) -> Option<PathBuf> {
+    let line = line.trim();
+    if !line.starts_with('-') {
+        return None;
+    }
+    let (_, path) = line.split_once(char::is_whitespace)?;
+    let path = path.trim_start().trim_start_matches('*').trim();
+    if path.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+/// Enumerates the interpreters known to the Windows `py` launcher, in the
+/// launcher's order
+///
+/// The return is empty if the launcher isn't installed, which is always
+/// the case on other platforms.
+pub fn py_launcher() -> Vec<DiscoveredInterpreter> {
+    probe_all(
+        py_launcher_candidates(),
+        Source::PyLauncher,
+        &mut HashSet::new(),
+    )
+}
+
+/// The interpreters listed by `py -0p`
+fn py_launcher_candidates() -> Vec<PathBuf> {
+    match Command::new("py").arg("-0p").output() {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter_map(parse_py_launcher_line)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A Python installation registered in the Windows registry
+///
+/// PEP 514 describes how installers register Pythons under
+/// `Software\Python\<Company>\<Tag>`. The company is `PythonCore` for the
+/// python.org installers; other distributions use their own.
+///
+/// See [`registry`](fn.registry.html).
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegisteredInterpreter {
+    /// The distributor's company key, like `PythonCore` or `ContinuumAnalytics`
+    pub company: String,
+    /// The installation's tag, like `3.12` or `3.12-32`
+    pub tag: String,
+    /// The installation directory, from the `InstallPath` key
+    pub install_path: PathBuf,
+    /// The interpreter, from `ExecutablePath`, or `python.exe` in the
+    /// installation directory
+    pub executable: PathBuf,
+    /// The version from `Version` or `SysVersion`, or from a `PythonCore`
+    /// tag. `None` if the registration doesn't say.
+    pub version: Option<semver::Version>,
+    /// The name to show users, from `DisplayName`
+    pub display_name: Option<String>,
+}
+
+impl RegisteredInterpreter {
+    /// Returns the registration as a `DiscoveredInterpreter`, if we know
+    /// its version
+    fn discovered(&self) -> Option<DiscoveredInterpreter> {
+        Some(DiscoveredInterpreter {
+            path: self.executable.clone(),
+            version: self.version.clone()?,
+            source: Source::Registry,
+        })
+    }
+}
+
+/// The registry keys that hold PEP 514 registrations, in the order that
+/// PEP 514 says to search them
+const REGISTRY_ROOTS: &[&str] = &[
+    "HKEY_CURRENT_USER\\Software\\Python",
+    "HKEY_LOCAL_MACHINE\\Software\\Python",
+    "HKEY_LOCAL_MACHINE\\Software\\WOW6432Node\\Python",
+];
+
+/// The values of a registration's keys, as `(name, data)` pairs
+type RegistryValues = Vec<(String, String)>;
+
+/// Parses the output of `reg query <root> /s` into the registrations
+/// under `root`
+///
+/// The output is a key on its own line, followed by the key's values, like
+/// `    ExecutablePath    REG_SZ    C:\Python312\python.exe`. Registrations
+/// without an `InstallPath` are skipped, as are those of the `PyLauncher`
+/// company, which registers the launcher rather than an interpreter.
+///
+/// `reg` translates the name of a key's default value, like `(Standard)`
+/// in German, but always lists it first, in parentheses. We name it
+/// `(Default)`.
+fn parse_reg_query(root: &str, text: &str) -> Vec<RegisteredInterpreter> {
+    // The values of each (company, tag), in registry order
+    let mut tags: Vec<((String, String), RegistryValues)> = Vec::new();
+    let mut key: Option<Vec<String>> = None;
+    let mut first = false;
+    for line in text.lines() {
+        if !line.starts_with(' ') {
+            first = true;
+            key = line
+                .trim()
+                .strip_prefix(root)
+                .and_then(|rest| rest.strip_prefix('\\'))
+                .map(|rest| rest.split('\\').map(String::from).collect());
+            continue;
+        }
+        let (company, tag, subkey) = match key.as_deref() {
+            Some([company, tag]) => (company, tag, ""),
+            Some([company, tag, subkey]) => (company, tag, subkey.as_str()),
+            _ => continue,
+        };
+        let mut fields = line.trim().splitn(3, "    ");
+        let (name, data) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(name), Some(_), Some(data)) => (name, data.trim()),
+            _ => continue,
+        };
+        let default = first && name.starts_with('(') && name.ends_with(')');
+        let name = if default { "(Default)" } else { name };
+        first = false;
+        // Values of the InstallPath key are prefixed, so the default
+        // value doesn't collide with the tag's default value.
+        let name = if subkey.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}\\{}", subkey, name)
+        };
+        let id = (company.clone(), tag.clone());
+        match tags.iter_mut().find(|(existing, _)| *existing == id) {
+            Some((_, values)) => values.push((name, data.to_owned())),
+            None => tags.push((id, vec![(name, data.to_owned())])),
+        }
+    }
+
+    tags.into_iter()
+        .filter(|((company, _), _)| company != "PyLauncher")
+        .filter_map(|((company, tag), values)| {
+            let value = |name: &str| {
+                values
+                    .iter()
+                    .find(|(value, _)| value.eq_ignore_ascii_case(name))
+                    .map(|(_, data)| data.as_str())
+                    .filter(|data| !data.is_empty())
+            };
+            let install_path = PathBuf::from(value("InstallPath\\(Default)")?);
+            let executable = value("InstallPath\\ExecutablePath")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| install_path.join("python.exe"));
+            let core_tag = if company == "PythonCore" {
+                tag.split('-').next()
+            } else {
+                None
+            };
+            let version = value("Version")
+                .or_else(|| value("SysVersion"))
+                .or(core_tag)
+                .and_then(parse_dir_version);
+            let display_name = value("DisplayName").map(String::from);
+            Some(RegisteredInterpreter {
+                company,
+                tag,
+                install_path,
+                executable,
+                version,
+                display_name,
+            })
+        })
+        .collect()
+}
+
+/// Enumerates the Pythons registered in the Windows registry, per PEP 514
+///
+/// We search the current user's registrations, then the machine's 64-bit
+/// and 32-bit registrations. This is the sanctioned way to find Pythons on
+/// Windows, where interpreters are rarely on the `PATH`. The return is
+/// always empty on other platforms.
+///
+/// # Example
+///
+/// ```no_run
+/// for found in python_config::discover::registry() {
+///     println!("{}\\{}: {}", found.company, found.tag, found.executable.display());
+/// }
+/// ```
+pub fn registry() -> Vec<RegisteredInterpreter> {
+    if !cfg!(windows) {
+        return Vec::new();
+    }
+    let mut found: Vec<RegisteredInterpreter> = Vec::new();
+    for root in REGISTRY_ROOTS {
+        let out = match Command::new("reg").args(["query", root, "/s"]).output() {
+            Ok(out) if out.status.success() => out,
+            _ => continue,
+        };
+        for interpreter in parse_reg_query(root, &String::from_utf8_lossy(&out.stdout)) {
+            if !found.iter().any(|f| f.executable == interpreter.executable) {
+                found.push(interpreter);
+            }
+        }
+    }
+    found
+}
+
+/// The Homebrew prefixes to search, `HOMEBREW_PREFIX` then the default
+/// prefixes for Apple silicon, Intel macOS, and Linux
+fn homebrew_prefixes() -> Vec<PathBuf> {
+    env::var_os("HOMEBREW_PREFIX")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(
+            ["/opt/homebrew", "/usr/local", "/home/linuxbrew/.linuxbrew"]
+                .iter()
+                .map(PathBuf::from),
+        )
+        .collect()
+}
+
+/// Parses the version of a Homebrew keg from its directory name, like
+/// `3.12.1` or `3.12.1_1`. The `_1` is a Homebrew revision, not part
+/// of the Python version.
+fn parse_keg_version(name: &str) -> Option<semver::Version> {
+    let version = name.split('_').next()?;
+    parse_dir_version(version)
+}
+
+/// Enumerates the `python@X.Y` kegs in the Cellar under `prefix`
+fn homebrew_in(prefix: &Path) -> Vec<DiscoveredInterpreter> {
+    let formulae = match fs::read_dir(prefix.join("Cellar")) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let kegs = formulae
+        .filter_map(Result::ok)
+        .filter(|formula| {
+            let name = formula.file_name();
+            let name = name.to_str().unwrap_or("");
+            name == "python" || name.starts_with("python@")
+        })
+        .filter_map(|formula| fs::read_dir(formula.path()).ok())
+        .flatten()
+        .filter_map(Result::ok);
+    kegs.filter_map(|keg| {
+        let version = parse_keg_version(keg.file_name().to_str()?)?;
+        let name = format!("python{}.{}", version.major, version.minor);
+        let path = keg.path().join("bin").join(name);
+        if path.is_file() {
+            Some(DiscoveredInterpreter {
+                path,
+                version,
+                source: Source::Homebrew,
+            })
+        } else {
+            None
+        }
+    })
+    .collect()
+}
+
+/// Enumerates the Pythons installed in the Homebrew Cellar, newest first
+///
+/// We search the Cellar under `HOMEBREW_PREFIX`, and under the default
+/// Homebrew prefixes.
+pub fn homebrew() -> Vec<DiscoveredInterpreter> {
+    let mut found: Vec<DiscoveredInterpreter> = Vec::new();
+    for prefix in homebrew_prefixes() {
+        for interpreter in homebrew_in(&prefix) {
+            if !found.iter().any(|f| f.path == interpreter.path) {
+                found.push(interpreter);
+            }
+        }
+    }
+    sort_newest_first(&mut found);
+    found
+}
+
+/// Enumerates every interpreter that we know how to find
+///
+/// This combines the `PATH`, the system directories, pyenv, asdf, uv,
+/// the Homebrew Cellar, the GitHub Actions tool cache, the Windows
+/// registry, and the `py` launcher. An interpreter found in more than
+/// one place is reported once, with the first source in that list.
+///
+/// This runs every interpreter on the `PATH` and in the system
+/// directories, so it's not free. If you know where your interpreters
+/// live, prefer a more specific function.
+///
+/// # Example
+///
+/// ```no_run
+/// for found in python_config::discover::all() {
+///     println!("{} {} ({:?})", found.version, found.path.display(), found.source);
+/// }
+/// ```
+pub fn all() -> Vec<DiscoveredInterpreter> {
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+    found.extend(probe_all(path_candidates(), Source::Path, &mut seen));
+    found.extend(probe_all(system_candidates(), Source::System, &mut seen));
+    let managed = pyenv()
+        .into_iter()
+        .chain(asdf())
+        .chain(uv())
+        .chain(homebrew())
+        .chain(hostedtoolcache());
+    for interpreter in managed {
+        let resolved = canonical(&interpreter.path);
+        if seen.insert(resolved) {
+            found.push(interpreter);
+        }
+    }
+    let (known, unknown): (Vec<_>, Vec<_>) = registry()
+        .into_iter()
+        .partition(|registered| registered.version.is_some());
+    for interpreter in known.iter().filter_map(RegisteredInterpreter::discovered) {
+        let resolved = canonical(&interpreter.path);
+        if interpreter.path.is_file() && seen.insert(resolved) {
+            found.push(interpreter);
+        }
+    }
+    found.extend(probe_all(
+        unknown.into_iter().map(|registered| registered.executable),
+        Source::Registry,
+        &mut seen,
+    ));
+    found.extend(probe_all(
+        py_launcher_candidates(),
+        Source::PyLauncher,
+        &mut seen,
+    ));
+    found
+}
+
+/// Returns the newest interpreter in `found` whose version matches `req`
+pub fn best_match<'a, I>(found: I, req: &semver::VersionReq) -> Option<DiscoveredInterpreter>
+where
+    I: IntoIterator<Item = &'a DiscoveredInterpreter>,
+{
+    found
+        .into_iter()
+        .filter(|interpreter| req.matches(&interpreter.version))
+        .max_by(|left, right| left.version.cmp(&right.version))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch directory that's removed when dropped
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let path =
+                env::temp_dir().join(format!("python-config-rs-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            Scratch(path)
+        }
+
+        fn touch(&self, relative: &str) {
+            let path = self.0.join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn install(cache: &Scratch, version: &str, complete: bool) {
+        let arch = toolcache_arch();
+        let exe = if cfg!(windows) {
+            "python.exe"
+        } else {
+            "bin/python3"
+        };
+        cache.touch(&format!("Python/{}/{}/{}", version, arch, exe));
+        if complete {
+            cache.touch(&format!("Python/{}/{}.complete", version, arch));
+        }
+    }
+
+    #[test]
+    fn toolcache_layout() {
+        let cache = Scratch::new("toolcache");
+        install(&cache, "3.9.18", true);
+        install(&cache, "3.12.1", true);
+        install(&cache, "3.13.0-rc.2", true);
+        install(&cache, "3.11.7", false);
+
+        let found = toolcache_in(&cache.0);
+        let versions: Vec<String> = found.iter().map(|i| i.version.to_string()).collect();
+        assert_eq!(versions, ["3.13.0-rc.2", "3.12.1", "3.9.18"]);
+        assert!(found.iter().all(|i| i.source == Source::ToolCache));
+
+        let req = semver::VersionReq::parse(">=3.9, <3.13").unwrap();
+        assert_eq!(
+            best_match(&found, &req).unwrap().version.to_string(),
+            "3.12.1"
+        );
+    }
+
+    #[test]
+    fn tool_versions() {
+        let text = "nodejs 20.1.0\npython 3.11.7 system 3.10.4 # pinned\n# python 2.7.18\n";
+        assert_eq!(tool_versions_python(text), ["3.11.7", "3.10.4"]);
+
+        let project = Scratch::new("tool-versions");
+        fs::write(project.0.join(".tool-versions"), "python 3.12.1\n").unwrap();
+        let nested = project.0.join("src").join("module");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(tool_versions_from(&nested), ["3.12.1"]);
+    }
+
+    #[test]
+    fn uv_dirs() {
+        let parse = |name| parse_uv_dir(name).map(|v| v.to_string());
+        assert_eq!(
+            parse("cpython-3.12.1-linux-x86_64-gnu").as_deref(),
+            Some("3.12.1")
+        );
+        assert_eq!(
+            parse("cpython-3.13.0+freethreaded-linux-x86_64-gnu").as_deref(),
+            Some("3.13.0+freethreaded")
+        );
+        assert_eq!(
+            parse("cpython-3.14.0a1-macos-aarch64-none").as_deref(),
+            Some("3.14.0-a.1")
+        );
+        assert_eq!(parse("pypy-3.10.14-linux-x86_64-gnu"), None);
+        assert_eq!(parse(".lock"), None);
+    }
+
+    #[test]
+    fn python_names() {
+        let names = ["python", "python3", "python3.12", "python2.7"];
+        let not_names = [
+            "python3-config",
+            "python3.12m",
+            "python3.",
+            "python.3",
+            "pythonw",
+            "pip3",
+        ];
+        if cfg!(windows) {
+            assert!(names
+                .iter()
+                .all(|name| is_python_name(&format!("{}.exe", name))));
+            assert!(!is_python_name("python3"));
+        } else {
+            assert!(names.iter().all(|name| is_python_name(name)));
+        }
+        assert!(!not_names.iter().any(|name| is_python_name(name)));
+    }
+
+    #[test]
+    fn py_launcher_lines() {
+        assert_eq!(
+            parse_py_launcher_line(" -V:3.12 *        C:\\Python312\\python.exe"),
+            Some(PathBuf::from("C:\\Python312\\python.exe"))
+        );
+        assert_eq!(
+            parse_py_launcher_line(" -3.9-64         C:\\Program Files\\Python39\\python.exe"),
+            Some(PathBuf::from("C:\\Program Files\\Python39\\python.exe"))
+        );
+        assert_eq!(
+            parse_py_launcher_line("Installed Pythons found by py Launcher"),
+            None
+        );
+    }
+
+    #[test]
+    fn pyenv_layout() {
+        let root = Scratch::new("pyenv");
+        let exe = if cfg!(windows) {
+            "python.exe"
+        } else {
+            "bin/python3"
+        };
+        root.touch(&format!("versions/3.10.13/{}", exe));
+        root.touch(&format!("versions/3.12.1/{}", exe));
+        root.touch(&format!("versions/miniconda3-latest/{}", exe));
+        let versions: Vec<String> = pyenv_in(&root.0)
+            .iter()
+            .map(|i| i.version.to_string())
+            .collect();
+        assert_eq!(versions, ["3.12.1", "3.10.13"]);
+    }
+
+    #[test]
+    fn pyenv_versions() {
+        let text = "# pinned\n3.12.1 system\n3.11\n";
+        assert_eq!(python_version_file(text), ["3.12.1", "3.11"]);
+
+        let root = Scratch::new("pyenv-version");
+        let exe = if cfg!(windows) {
+            "python.exe"
+        } else {
+            "bin/python3"
+        };
+        root.touch(&format!("versions/3.11.4/{}", exe));
+        root.touch(&format!("versions/3.11.7/{}", exe));
+        root.touch(&format!("versions/3.12.1/{}", exe));
+        fs::write(root.0.join("version"), "3.12.1\n").unwrap();
+        let project = root.0.join("project");
+        let nested = project.join("src");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(pyenv_versions_from(&nested, &root.0), ["3.12.1"]);
+        fs::write(project.join(".python-version"), "3.10.0 3.11\n").unwrap();
+        assert_eq!(pyenv_versions_from(&nested, &root.0), ["3.10.0", "3.11"]);
+
+        let installed = pyenv_in(&root.0);
+        let select = |name| pyenv_select(&installed, &root.0, name).map(|i| i.version.to_string());
+        assert_eq!(select("3.11.4").as_deref(), Some("3.11.4"));
+        assert_eq!(select("3.11").as_deref(), Some("3.11.7"));
+        assert_eq!(select("3.1"), None);
+        assert_eq!(select("3.10.0"), None);
+    }
+
+    #[test]
+    fn homebrew_layout() {
+        assert_eq!(parse_keg_version("3.12.1_1").unwrap().to_string(), "3.12.1");
+        let prefix = Scratch::new("homebrew");
+        prefix.touch("Cellar/python@3.12/3.12.1_1/bin/python3.12");
+        prefix.touch("Cellar/python@3.11/3.11.7/bin/python3.11");
+        prefix.touch("Cellar/pyenv/2.3.35/bin/pyenv");
+        let mut found = homebrew_in(&prefix.0);
+        sort_newest_first(&mut found);
+        let versions: Vec<String> = found.iter().map(|i| i.version.to_string()).collect();
+        assert_eq!(versions, ["3.12.1", "3.11.7"]);
+        assert!(found.iter().all(|i| i.source == Source::Homebrew));
+    }
+
+    #[test]
+    fn path_interpreters_are_unique() {
+        let found = path();
+        assert!(!found.is_empty());
+        let mut resolved: Vec<PathBuf> = found
+            .iter()
+            .map(|i| fs::canonicalize(&i.path).unwrap())
+            .collect();
+        resolved.sort();
+        resolved.dedup();
+        assert_eq!(resolved.len(), found.len());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_interpreters() {
+        let real = fs::canonicalize(crate::PythonConfig::new().executable().unwrap()).unwrap();
+        let dir = Scratch::new("symlinks");
+        std::os::unix::fs::symlink(&real, dir.0.join("python3")).unwrap();
+        std::os::unix::fs::symlink(&real, dir.0.join("python3.99")).unwrap();
+
+        let found = probe_all(interpreters_in(&dir.0), Source::Path, &mut HashSet::new());
+        assert_eq!(found.len(), 1, "{:?}", found);
+        assert_eq!(found[0].path, dir.0.join("python3"));
+
+        let mut seen = HashSet::new();
+        seen.insert(real);
+        assert!(probe_all(interpreters_in(&dir.0), Source::Path, &mut seen).is_empty());
+    }
+
+    #[test]
+    fn missing_toolcache() {
+        assert!(toolcache_in(Path::new("/this/path/does/not/exist")).is_empty());
+    }
+
+    #[test]
+    fn default_commands() {
+        let versioned = || {
+            vec![
+                PathBuf::from("/usr/bin/python3.9"),
+                PathBuf::from("/opt/bin/python3.12"),
+                PathBuf::from("/usr/bin/python3.12"),
+                PathBuf::from("/usr/bin/python3.10"),
+                PathBuf::from("/usr/bin/python2.7"),
+            ]
+        };
+        let command = |program: &str, args: &[&str]| {
+            Some((
+                program.to_owned(),
+                args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>(),
+            ))
+        };
+        assert_eq!(
+            choose_command(3, |_| true, versioned()),
+            command("python3", &[])
+        );
+        assert_eq!(
+            choose_command(3, |name| name == "py", versioned()),
+            command("py", &["-3"])
+        );
+        assert_eq!(
+            choose_command(3, |name| name == "python", versioned()),
+            command("python3.12", &[])
+        );
+        assert_eq!(
+            choose_command(2, |_| false, versioned()),
+            command("python2.7", &[])
+        );
+        assert_eq!(
+            choose_command(3, |name| name == "python", Vec::new()),
+            command("python", &[])
+        );
+        assert_eq!(choose_command(3, |_| false, Vec::new()), None);
+    }
+
+    #[test]
+    fn versioned_commands() {
+        assert_eq!(
+            choose_versioned_command(3, 11, |_| true),
+            ("python3.11".to_owned(), Vec::new())
+        );
+        assert_eq!(
+            choose_versioned_command(3, 11, |name| name == "py"),
+            ("py".to_owned(), vec!["-3.11".to_owned()])
+        );
+        assert_eq!(
+            choose_versioned_command(3, 11, |_| false),
+            ("python3.11".to_owned(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn reg_query() {
+        let root = "HKEY_CURRENT_USER\\Software\\Python";
+        let text = r"
+HKEY_CURRENT_USER\Software\Python\PythonCore
+    DisplayName    REG_SZ    Python Software Foundation
+
+HKEY_CURRENT_USER\Software\Python\PythonCore\3.12
+    DisplayName    REG_SZ    Python 3.12 (64-bit)
+    Version    REG_SZ    3.12.1
+    SysVersion    REG_SZ    3.12
+
+HKEY_CURRENT_USER\Software\Python\PythonCore\3.12\InstallPath
+    (Default)    REG_SZ    C:\Users\me\Python312\
+    ExecutablePath    REG_SZ    C:\Users\me\Python312\python.exe
+
+HKEY_CURRENT_USER\Software\Python\PythonCore\3.11-32\InstallPath
+    (Par défaut)    REG_SZ    C:\Python311-32
+
+HKEY_CURRENT_USER\Software\Python\ContinuumAnalytics\Anaconda
+    DisplayName    REG_SZ    Anaconda
+
+HKEY_CURRENT_USER\Software\Python\ContinuumAnalytics\Anaconda\InstallPath
+    (Standard)    REG_SZ    C:\Anaconda3
+    ExecutablePath    REG_SZ    C:\Anaconda3\python.exe
+
+HKEY_CURRENT_USER\Software\Python\PyLauncher\InstallPath
+    (Default)    REG_SZ    C:\Windows\py.exe
+
+HKEY_CURRENT_USER\Software\Python\Broken\1.0
+    DisplayName    REG_SZ    No install path
+
+HKEY_CURRENT_USER\Software\Python\Broken\1.0\InstallPath
+    ExecutablePath    REG_SZ    C:\Broken\python.exe
+    (Standard)    REG_SZ    C:\Broken
+";
+        let found = parse_reg_query(root, text);
+        assert_eq!(found.len(), 3);
+
+        assert_eq!(found[0].company, "PythonCore");
+        assert_eq!(found[0].tag, "3.12");
+        assert_eq!(
+            found[0].install_path,
+            PathBuf::from("C:\\Users\\me\\Python312\\")
+        );
+        assert_eq!(
+            found[0].executable,
+            PathBuf::from("C:\\Users\\me\\Python312\\python.exe")
+        );
+        assert_eq!(found[0].version.as_ref().unwrap().to_string(), "3.12.1");
+        assert_eq!(
+            found[0].display_name.as_deref(),
+            Some("Python 3.12 (64-bit)")
+        );
+        assert_eq!(found[0].discovered().unwrap().source, Source::Registry);
+
+        assert_eq!(found[1].tag, "3.11-32");
+        assert_eq!(
+            found[1].executable,
+            PathBuf::from("C:\\Python311-32").join("python.exe")
+        );
+        assert_eq!(found[1].version.as_ref().unwrap().to_string(), "3.11.0");
+
+        assert_eq!(found[2].company, "ContinuumAnalytics");
+        assert_eq!(found[2].version, None);
+        assert_eq!(found[2].discovered(), None);
+    }
+}
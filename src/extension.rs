@@ -0,0 +1,165 @@
+//! Extension module file names
+
+use crate::{other_err, query, Capability, Commander, Py3Only, PythonConfig};
+
+/// The ABI that an extension module targets, which decides its file
+/// name suffix
+///
+/// See [`PythonConfig::extension_filename_for`](struct.PythonConfig.html#method.extension_filename_for).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExtensionAbi {
+    /// The ABI of this interpreter's version, and build, like
+    /// `.cpython-312-x86_64-linux-gnu.so`, or `.cp312-win_amd64.pyd`
+    Version,
+    /// The stable ABI of the limited API, like `.abi3.so`, or `.pyd` on
+    /// Windows
+    Abi3,
+    /// The ABI of a free-threaded build of this interpreter's version,
+    /// like `.cpython-313t-x86_64-linux-gnu.so`. Only CPython 3.13, and
+    /// later, has free-threaded builds.
+    FreeThreaded,
+}
+
+impl<C: Commander> PythonConfig<C> {
+    /// Returns the file name suffix of extension modules that target
+    /// `abi`
+    ///
+    /// `ExtensionAbi::Version` is the same as
+    /// [`extension_suffix`](#method.extension_suffix), and
+    /// `ExtensionAbi::Abi3` is the same as
+    /// [`extension_suffix_abi3`](#method.extension_suffix_abi3). A
+    /// free-threaded build answers `ExtensionAbi::FreeThreaded` with its
+    /// own suffix. Other CPython 3.13 builds insert the `t` ABI flag into
+    /// theirs.
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    pub fn extension_suffix_for(&self, abi: ExtensionAbi) -> Py3Only<String> {
+        match abi {
+            ExtensionAbi::Version => self.extension_suffix(),
+            ExtensionAbi::Abi3 => self.extension_suffix_abi3(),
+            ExtensionAbi::FreeThreaded => {
+                self.is_py3()?;
+                self.require(Capability::ExtensionModules)?;
+                let suffix = self.script(query::FREE_THREADED_EXTENSION_SUFFIX)?;
+                if suffix.is_empty() {
+                    Err(other_err(
+                        "free-threaded extension modules need CPython 3.13 or later",
+                    ))
+                } else {
+                    Ok(suffix)
+                }
+            }
+        }
+    }
+
+    /// Returns the file name of the extension module `module`, like
+    /// `mymodule.cpython-312-x86_64-linux-gnu.so`, or `mymodule.cp312-win_amd64.pyd`
+    /// on Windows
+    ///
+    /// For a module in a package, like `pkg._speedups`, the file name
+    /// holds the last part of the name, `_speedups`. Install the file in
+    /// the package's directory.
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// let filename = cfg.extension_filename("mymodule").unwrap();
+    /// std::fs::copy("target/release/libmymodule.so", filename).unwrap();
+    /// ```
+    pub fn extension_filename(&self, module: &str) -> Py3Only<String> {
+        self.extension_filename_for(module, ExtensionAbi::Version)
+    }
+
+    /// Like [`extension_filename`](#method.extension_filename), but for an
+    /// extension module that targets `abi`, like `mymodule.abi3.so` for
+    /// `ExtensionAbi::Abi3`
+    ///
+    /// See [`extension_suffix_for`](#method.extension_suffix_for).
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    pub fn extension_filename_for(&self, module: &str, abi: ExtensionAbi) -> Py3Only<String> {
+        let name = module.rsplit('.').next().unwrap_or(module);
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return Err(other_err("expected a module name, like 'mymodule'"));
+        }
+        Ok(format!("{}{}", name, self.extension_suffix_for(abi)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExtensionAbi;
+    use crate::testing::{override_vars, rewritten, Rewrite};
+    use crate::PythonConfig;
+
+    #[test]
+    fn filenames() {
+        let cfg = PythonConfig::new();
+        let suffix = cfg.extension_suffix().unwrap();
+        assert_eq!(
+            cfg.extension_filename("spam").unwrap(),
+            format!("spam{}", suffix)
+        );
+        assert_eq!(
+            cfg.extension_filename("pkg._speedups").unwrap(),
+            format!("_speedups{}", suffix)
+        );
+        assert_eq!(
+            cfg.extension_filename_for("spam", ExtensionAbi::Abi3)
+                .unwrap(),
+            format!("spam{}", cfg.extension_suffix_abi3().unwrap())
+        );
+        assert!(cfg.extension_filename("pkg.").is_err());
+        assert!(cfg.extension_filename("../spam").is_err());
+    }
+
+    /// Runs the scripts as if the interpreter were a CPython 3.13 build with
+    /// the extension suffix `ext_suffix`
+    fn pretend_313(
+        ext_suffix: &'static str,
+        gil_disabled: bool,
+    ) -> PythonConfig<Rewrite<impl Fn(&str) -> String>> {
+        rewritten(move |script| {
+            let vars = format!(
+                "{{'EXT_SUFFIX': '{}', 'Py_GIL_DISABLED': {}, 'py_version_nodot': '313'}}",
+                ext_suffix,
+                if gil_disabled { 1 } else { 0 },
+            );
+            override_vars(script, &vars).replace("sys.version_info", "(3, 13, 0, 'final', 0)")
+        })
+    }
+
+    #[test]
+    fn free_threaded() {
+        let suffix = |ext_suffix, gil_disabled| {
+            pretend_313(ext_suffix, gil_disabled)
+                .extension_filename_for("spam", ExtensionAbi::FreeThreaded)
+                .unwrap()
+        };
+        assert_eq!(
+            suffix(".cpython-313-x86_64-linux-gnu.so", false),
+            "spam.cpython-313t-x86_64-linux-gnu.so"
+        );
+        assert_eq!(
+            suffix(".cp313-win_amd64.pyd", false),
+            "spam.cp313t-win_amd64.pyd"
+        );
+        assert_eq!(
+            suffix(".cpython-313t-darwin.so", true),
+            "spam.cpython-313t-darwin.so"
+        );
+
+        let cfg = PythonConfig::new();
+        if cfg.version_info().unwrap().minor < 13 {
+            assert!(cfg
+                .extension_suffix_for(ExtensionAbi::FreeThreaded)
+                .is_err());
+        }
+    }
+}
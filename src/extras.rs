@@ -0,0 +1,102 @@
+//! Queries about third-party packages installed for the interpreter
+
+use crate::{parse_err, query, Commander, Error, PyResult, PythonConfig};
+
+use std::path::PathBuf;
+
+impl<C: Commander> PythonConfig<C> {
+    /// Returns the directory that holds the C headers of the installed
+    /// package `name`, like NumPy's `numpy/core/include`
+    ///
+    /// We import the package, and call its `get_include()`, like NumPy and
+    /// pybind11 provide. Without `get_include()`, we look for an `include`
+    /// directory in the package. This fails if the package isn't installed,
+    /// or if we can't find its headers.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// // build.rs
+    /// let numpy = PythonConfig::new().package_include_dir("numpy").unwrap();
+    /// println!("cargo:include={}", numpy.display());
+    /// ```
+    pub fn package_include_dir(&self, name: &str) -> PyResult<PathBuf> {
+        let resp = self.script_args(query::PACKAGE_INCLUDE_DIR, &[name])?;
+        if resp.is_empty() {
+            Err(Error::Other(
+                format!("the package {} does not have a header directory", name).into(),
+            ))
+        } else {
+            Ok(PathBuf::from(resp))
+        }
+    }
+
+    /// Returns the version of the installed package `name`, like `1.26.4`
+    ///
+    /// The version comes from the package's metadata, or from its
+    /// `__version__` attribute. This fails if the package isn't installed.
+    pub fn package_version(&self, name: &str) -> PyResult<String> {
+        self.script_args(query::PACKAGE_VERSION, &[name])
+    }
+
+    /// Finds the module `name`, like `ensurepip` or `pip`, returning
+    /// `None` if the interpreter doesn't have it, and `Some` of the
+    /// module's file, if it has one
+    ///
+    /// We find the module without importing it, though finding a dotted
+    /// name, like `a.b`, imports `a`. A package's file is its
+    /// `__init__.py`, and a namespace package's file is its first
+    /// directory. Built-in and frozen modules, like `sys`, don't have a
+    /// file.
+    fn find_module(&self, name: &str) -> PyResult<Option<Option<PathBuf>>> {
+        let resp = self.script_args(query::MODULE, &[name])?;
+        match resp.split_once('\n') {
+            Some(("1", path)) => Ok(Some(Some(PathBuf::from(path)))),
+            None if resp == "1" => Ok(Some(None)),
+            None if resp == "0" => Ok(None),
+            _ => Err(parse_err("expected whether there's a module", &resp)),
+        }
+    }
+
+    /// Returns `true` if the interpreter can import the module `name`,
+    /// like `ensurepip`, `pip`, or `setuptools.command`
+    ///
+    /// Tools that install packages, or create virtual environments, can
+    /// check for what they need before they start. Debian and Ubuntu, for
+    /// instance, ship `venv` without `ensurepip` unless you install
+    /// `python3-venv`. We find the module without running it, so this
+    /// can't tell if the module fails when it's imported. See
+    /// [`can_create_venv`](#method.can_create_venv).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// if !cfg.module_available("pip").unwrap() {
+    ///     eprintln!("pip isn't installed; try 'python3 -m ensurepip'");
+    /// }
+    /// ```
+    pub fn module_available(&self, name: &str) -> PyResult<bool> {
+        self.find_module(name).map(|module| module.is_some())
+    }
+
+    /// Returns the file of the module `name`, like
+    /// `/usr/lib/python3.11/site-packages/pip/__init__.py`
+    ///
+    /// This is `None` if the interpreter can't import the module, or if
+    /// the module doesn't have a file, like the built-in `sys` module. A
+    /// namespace package's path is its first directory.
+    pub fn module_path(&self, name: &str) -> PyResult<Option<PathBuf>> {
+        self.find_module(name).map(Option::flatten)
+    }
+
+    /// Returns `true` if `python -m venv` can create a virtual environment
+    /// with pip in it, which needs both `venv` and `ensurepip`
+    pub fn can_create_venv(&self) -> PyResult<bool> {
+        Ok(self.module_available("venv")? && self.module_available("ensurepip")?)
+    }
+}
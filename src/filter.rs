@@ -0,0 +1,266 @@
+//! Removing compiler and linker flags that don't travel well
+
+use crate::{parse_err, Commander, Error, PyResult, PythonConfig};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Which flags to keep from [`cflags_filtered`](struct.PythonConfig.html#method.cflags_filtered)
+/// and [`ldflags_filtered`](struct.PythonConfig.html#method.ldflags_filtered)
+///
+/// The flags that built Python include the distribution's optimization
+/// settings, like `-O3` and `-flto=auto`, `-march` flags for the build
+/// machine, and hardening flags, like `-fstack-clash-protection`. Those
+/// flags often fail with another compiler, or another version of the
+/// same compiler, and they aren't needed to build against Python.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FilterPolicy {
+    /// Every flag, like [`cflags_split`](struct.PythonConfig.html#method.cflags_split)
+    Raw,
+    /// Every flag, except optimization, debug info, link-time optimization,
+    /// profiling, architecture, and hardening flags
+    NoOptimization,
+    /// Only the include directories of compiler flags, and only the
+    /// libraries, frameworks, and their search directories of linker flags
+    IncludeOnly,
+}
+
+/// Flags that take the next argument as their value
+const WITH_VALUE: &[&str] = &["-isystem", "-framework", "-Xlinker", "-arch", "-include"];
+
+/// Returns `true` if `flag` optimizes, adds debug info, profiles, targets
+/// a specific CPU, or hardens the build
+fn is_optimization(flag: &str) -> bool {
+    const PREFIXES: &[&str] = &[
+        "-O",
+        "-g",
+        "-flto",
+        "-fuse-linker-plugin",
+        "-ffat-lto-objects",
+        "-fno-semantic-interposition",
+        "-fprofile-",
+        "-fauto-profile",
+        "-march=",
+        "-mtune=",
+        "-mcpu=",
+        "-mbranch-protection=",
+        "-fstack-protector",
+        "-fstack-clash-protection",
+        "-fcf-protection",
+        "-D_FORTIFY_SOURCE",
+        "-U_FORTIFY_SOURCE",
+        "-Wp,-D_FORTIFY_SOURCE",
+        "-D_GLIBCXX_ASSERTIONS",
+        "-Wp,-D_GLIBCXX_ASSERTIONS",
+        "-Werror=",
+        "-specs=",
+        "-Wl,-O",
+        "-Wl,-z,relro",
+        "-Wl,-z,now",
+        "-Wl,--build-id",
+    ];
+    PREFIXES.iter().any(|prefix| flag.starts_with(prefix))
+}
+
+/// Returns `true` if `flag` names an include directory, a library, a
+/// framework, or a search directory for either
+fn is_include(flag: &str) -> bool {
+    const PREFIXES: &[&str] = &[
+        "-I",
+        "-isystem",
+        "-L",
+        "-l",
+        "-F",
+        "-framework",
+        "/LIBPATH:",
+    ];
+    PREFIXES.iter().any(|prefix| flag.starts_with(prefix)) || flag.ends_with(".lib")
+}
+
+impl FilterPolicy {
+    /// Returns the flags in `flags` that this policy keeps, in order
+    ///
+    /// A flag that takes the next argument as its value, like
+    /// `-framework CoreFoundation` or `-arch arm64`, is kept or removed
+    /// along with its value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use python_config::FilterPolicy;
+    ///
+    /// let flags = ["-I/usr/include/python3.11", "-O3", "-flto=auto", "-DNDEBUG"];
+    /// assert_eq!(
+    ///     FilterPolicy::NoOptimization.apply(&flags),
+    ///     ["-I/usr/include/python3.11", "-DNDEBUG"]
+    /// );
+    /// assert_eq!(
+    ///     FilterPolicy::IncludeOnly.apply(&flags),
+    ///     ["-I/usr/include/python3.11"]
+    /// );
+    /// ```
+    pub fn apply<S: AsRef<str>>(self, flags: &[S]) -> Vec<String> {
+        let mut kept = Vec::new();
+        let mut flags = flags.iter().map(AsRef::as_ref);
+        while let Some(flag) = flags.next() {
+            let value = if WITH_VALUE.contains(&flag) {
+                flags.next()
+            } else {
+                None
+            };
+            let keep = match self {
+                FilterPolicy::Raw => true,
+                FilterPolicy::NoOptimization => !is_optimization(flag) && flag != "-arch",
+                FilterPolicy::IncludeOnly => is_include(flag),
+            };
+            if keep {
+                kept.push(flag.to_owned());
+                kept.extend(value.map(str::to_owned));
+            }
+        }
+        kept
+    }
+}
+
+impl fmt::Display for FilterPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            FilterPolicy::Raw => "raw",
+            FilterPolicy::NoOptimization => "no-optimization",
+            FilterPolicy::IncludeOnly => "includes",
+        })
+    }
+}
+
+/// Parses the names printed by `Display`: `raw`, `no-optimization`, and
+/// `includes`
+impl FromStr for FilterPolicy {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(FilterPolicy::Raw),
+            "no-optimization" => Ok(FilterPolicy::NoOptimization),
+            "includes" => Ok(FilterPolicy::IncludeOnly),
+            _ => Err(parse_err(
+                "expected a filter policy: raw, no-optimization, or includes",
+                s,
+            )),
+        }
+    }
+}
+
+impl<C: Commander> PythonConfig<C> {
+    /// Like [`cflags_split`](#method.cflags_split), but only the flags that
+    /// `policy` keeps
+    ///
+    /// Use `FilterPolicy::NoOptimization` to build against Python with your
+    /// own optimization settings, or with a compiler that doesn't
+    /// understand the distribution's flags.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::{FilterPolicy, PythonConfig};
+    ///
+    /// let flags = PythonConfig::new()
+    ///     .cflags_filtered(FilterPolicy::NoOptimization)
+    ///     .unwrap();
+    /// assert!(!flags.iter().any(|flag| flag.starts_with("-O")));
+    /// ```
+    pub fn cflags_filtered(&self, policy: FilterPolicy) -> PyResult<Vec<String>> {
+        self.cflags_split().map(|flags| policy.apply(&flags))
+    }
+
+    /// Like [`ldflags_split`](#method.ldflags_split), but only the flags
+    /// that `policy` keeps
+    pub fn ldflags_filtered(&self, policy: FilterPolicy) -> PyResult<Vec<String>> {
+        self.ldflags_split().map(|flags| policy.apply(&flags))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilterPolicy;
+    use crate::PythonConfig;
+
+    #[test]
+    fn policies() {
+        let cflags = [
+            "-I/usr/include/python3.12",
+            "-fno-strict-overflow",
+            "-DNDEBUG",
+            "-g",
+            "-O3",
+            "-Wall",
+            "-march=x86-64-v2",
+            "-fstack-clash-protection",
+            "-Wp,-D_FORTIFY_SOURCE=3",
+            "-arch",
+            "arm64",
+        ];
+        assert_eq!(FilterPolicy::Raw.apply(&cflags), cflags);
+        assert_eq!(
+            FilterPolicy::NoOptimization.apply(&cflags),
+            [
+                "-I/usr/include/python3.12",
+                "-fno-strict-overflow",
+                "-DNDEBUG",
+                "-Wall"
+            ]
+        );
+        assert_eq!(
+            FilterPolicy::IncludeOnly.apply(&cflags),
+            ["-I/usr/include/python3.12"]
+        );
+
+        let ldflags = [
+            "-L/usr/lib",
+            "-lpython3.12",
+            "-Wl,-z,relro",
+            "-flto=auto",
+            "-framework",
+            "CoreFoundation",
+            "-Xlinker",
+            "-export-dynamic",
+        ];
+        assert_eq!(
+            FilterPolicy::NoOptimization.apply(&ldflags),
+            [
+                "-L/usr/lib",
+                "-lpython3.12",
+                "-framework",
+                "CoreFoundation",
+                "-Xlinker",
+                "-export-dynamic"
+            ]
+        );
+        assert_eq!(
+            FilterPolicy::IncludeOnly.apply(&ldflags),
+            ["-L/usr/lib", "-lpython3.12", "-framework", "CoreFoundation"]
+        );
+
+        for policy in &[
+            FilterPolicy::Raw,
+            FilterPolicy::NoOptimization,
+            FilterPolicy::IncludeOnly,
+        ] {
+            assert_eq!(policy.to_string().parse::<FilterPolicy>().unwrap(), *policy);
+        }
+        assert!("fast".parse::<FilterPolicy>().is_err());
+    }
+
+    #[test]
+    fn live() {
+        let cfg = PythonConfig::new();
+        let includes = cfg.cflags_filtered(FilterPolicy::IncludeOnly).unwrap();
+        assert!(!includes.is_empty());
+        assert!(includes.iter().all(|flag| flag.starts_with("-I")));
+        assert_eq!(
+            cfg.cflags_filtered(FilterPolicy::Raw).unwrap(),
+            cfg.cflags_split().unwrap()
+        );
+        let ldflags = cfg.ldflags_filtered(FilterPolicy::NoOptimization).unwrap();
+        assert!(ldflags.iter().all(|flag| !flag.starts_with("-O")));
+    }
+}
@@ -0,0 +1,354 @@
+//! Recording an interpreter's responses, and replaying them without Python
+
+use crate::{Commander, Error, ExtensionAbi, LinkMode, PyResult, PyVersion, PythonConfig, Version};
+
+use std::cell::RefCell;
+
+/// One recorded query, and the interpreter's response
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct FrozenResponse {
+    args: Vec<String>,
+    response: String,
+}
+
+/// The responses of a Python interpreter to our queries, recorded so that
+/// we can answer them later without running Python
+///
+/// Create one with [`PythonConfig::freeze`](struct.PythonConfig.html#method.freeze).
+/// A `FrozenConfig` is a [`Commander`](trait.Commander.html), so
+/// [`into_config`](#method.into_config) gives you a `PythonConfig` that
+/// answers queries from the recording. That's useful in hermetic builds,
+/// and in sandboxes that forbid subprocesses: freeze a known-good
+/// configuration once, check it in, and load it in the build.
+///
+/// Queries that we didn't record fail with
+/// [`Error::Other`](enum.Error.html#variant.Other). The recording holds
+/// our query scripts, so load it with the same version of this crate that
+/// froze it. Methods that also look at the file system, like
+/// [`libpython_path`](struct.PythonConfig.html#method.libpython_path),
+/// still look at this system's file system.
+///
+/// Save and load recordings as JSON with the `serde` feature, or as TOML
+/// with the `toml` feature.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrozenConfig {
+    version: PyVersion,
+    responses: Vec<FrozenResponse>,
+}
+
+impl FrozenConfig {
+    /// Returns the version of the interpreter that we recorded
+    pub fn version(&self) -> &PyVersion {
+        &self.version
+    }
+
+    /// Returns a `PythonConfig` that answers queries from this recording
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::{FrozenConfig, PythonConfig};
+    ///
+    /// let frozen: FrozenConfig = PythonConfig::new().freeze().unwrap();
+    /// // No more Python
+    /// let cfg = frozen.into_config();
+    /// println!("{}", cfg.includes().unwrap());
+    /// ```
+    ///
+    /// Like [`Version::Two`](enum.Version.html#variant.Two), a recording of
+    /// Python 2 needs the `python2` feature. Without it, the queries fail
+    /// with [`Error::UnsupportedVersion`](enum.Error.html#variant.UnsupportedVersion).
+    pub fn into_config(self) -> PythonConfig<FrozenConfig> {
+        let version = match self.version.major {
+            3 => Version::Three,
+            #[cfg(feature = "python2")]
+            2 => Version::Two,
+            major => Version::Custom(major as u8, self.version.minor as u8),
+        };
+        PythonConfig::with_commander(version, self)
+    }
+
+    /// Writes the recording to the file at `path`, as TOML if `path` ends
+    /// with `.toml`, or as JSON otherwise
+    ///
+    /// Requires the `serde` feature. TOML also requires the `toml` feature.
+    #[cfg(feature = "serde")]
+    pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> PyResult<()> {
+        let text = if is_toml(path.as_ref()) {
+            to_toml(self)?
+        } else {
+            serde_json::to_string_pretty(self).map_err(std::io::Error::other)?
+        };
+        std::fs::write(path, text).map_err(Error::from)
+    }
+
+    /// Loads a recording written by [`save`](#method.save), as TOML if
+    /// `path` ends with `.toml`, or as JSON otherwise
+    ///
+    /// Requires the `serde` feature. TOML also requires the `toml` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::{FrozenConfig, PythonConfig};
+    ///
+    /// // Once, in CI
+    /// PythonConfig::new().freeze().unwrap().save("python-config.json").unwrap();
+    ///
+    /// // In the build, without Python
+    /// let cfg = FrozenConfig::load("python-config.json").unwrap().into_config();
+    /// println!("{}", cfg.ldflags().unwrap());
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> PyResult<Self> {
+        let text = std::fs::read_to_string(path.as_ref())?;
+        if is_toml(path.as_ref()) {
+            from_toml(&text)
+        } else {
+            serde_json::from_str(&text)
+                .map_err(|_| crate::parse_err("expected a frozen configuration", &text))
+        }
+    }
+}
+
+/// Replays the recorded response to `args`
+impl Commander for FrozenConfig {
+    fn commands(&self, args: &[&str]) -> PyResult<String> {
+        self.responses
+            .iter()
+            .find(|recorded| {
+                recorded
+                    .args
+                    .iter()
+                    .map(String::as_str)
+                    .eq(args.iter().copied())
+            })
+            .map(|recorded| recorded.response.clone())
+            .ok_or_else(|| {
+                Error::Other("the frozen configuration has no response to this query".into())
+            })
+    }
+}
+
+#[cfg(feature = "serde")]
+fn is_toml(path: &std::path::Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "toml")
+}
+
+#[cfg(feature = "toml")]
+fn to_toml(frozen: &FrozenConfig) -> PyResult<String> {
+    toml::to_string(frozen).map_err(|err| Error::IO(std::io::Error::other(err)))
+}
+
+#[cfg(feature = "toml")]
+fn from_toml(text: &str) -> PyResult<FrozenConfig> {
+    toml::from_str(text).map_err(|_| crate::parse_err("expected a frozen configuration", text))
+}
+
+#[cfg(all(feature = "serde", not(feature = "toml")))]
+fn to_toml(_: &FrozenConfig) -> PyResult<String> {
+    Err(Error::Other("TOML requires the toml feature".into()))
+}
+
+#[cfg(all(feature = "serde", not(feature = "toml")))]
+fn from_toml(_: &str) -> PyResult<FrozenConfig> {
+    Err(Error::Other("TOML requires the toml feature".into()))
+}
+
+/// Passes queries to another commander, remembering each successful
+/// response
+struct Recorder<'a, C> {
+    cmdr: &'a C,
+    responses: RefCell<Vec<FrozenResponse>>,
+}
+
+impl<C: Commander> Commander for Recorder<'_, C> {
+    fn commands(&self, args: &[&str]) -> PyResult<String> {
+        let response = self.cmdr.commands(args)?;
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let mut responses = self.responses.borrow_mut();
+        if !responses.iter().any(|recorded| recorded.args == args) {
+            responses.push(FrozenResponse {
+                args,
+                response: response.clone(),
+            });
+        }
+        Ok(response)
+    }
+}
+
+impl<C: Commander> PythonConfig<C> {
+    /// Runs every query that doesn't take an argument, and records the
+    /// responses in a [`FrozenConfig`](struct.FrozenConfig.html)
+    ///
+    /// Queries that fail, like the Python 3 queries of a Python 2
+    /// interpreter, aren't recorded. This fails if we can't learn the
+    /// interpreter's version.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let frozen = PythonConfig::new().freeze().unwrap();
+    /// println!("Recorded Python {}", frozen.version());
+    /// ```
+    pub fn freeze(&self) -> PyResult<FrozenConfig> {
+        let recorder = Recorder {
+            cmdr: &self.cmdr,
+            responses: RefCell::new(Vec::new()),
+        };
+        let cfg = PythonConfig::with_commander(self.ver, &recorder);
+        let version = cfg.version_info()?;
+
+        macro_rules! record {
+            ($($query:ident($($arg:expr),*)),* $(,)?) => {
+                $(let _ = cfg.$query($($arg),*);)*
+            };
+        }
+        record!(
+            capabilities(),
+            version_raw(),
+            executable(),
+            purelib(),
+            platlib(),
+            scripts_dir(),
+            data_dir(),
+            stdlib_dir(),
+            platstdlib_dir(),
+            sys_path(),
+            can_create_venv(),
+            prefix(),
+            base_prefix(),
+            exec_prefix(),
+            includes(),
+            include_paths(),
+            include_dir(),
+            cflags(),
+            cflags_split(),
+            libs(),
+            libs_embed(),
+            libs_abi3(),
+            ldflags(),
+            ldflags_split(),
+            ldflags_embed(),
+            ldflags_with_rpath(),
+            ldflags_for(LinkMode::Static),
+            ldflags_for(LinkMode::Shared),
+            link_args(),
+            link_args_embed(),
+            python_framework(),
+            is_conda(),
+            is_shared(),
+            is_optimized_build(),
+            gil_disabled(),
+            is_debug_build(),
+            build_toolchain(),
+            build_flags(),
+            sanitizers(),
+            install_origin(),
+            extension_suffix(),
+            extension_suffix_abi3(),
+            extension_suffix_for(ExtensionAbi::FreeThreaded),
+            libpython_path(),
+            lib_dir(),
+            config_lib_dir(),
+            ld_library_name(),
+            inst_so_name(),
+            pkg_config(),
+            abi_flags(),
+            config_dir(),
+            build_env(),
+            cmake_snippet(),
+            meson_machine_file(),
+            soabi(),
+            platform(),
+            implementation(),
+            wheel_tag(),
+            architecture(),
+            pointer_width(),
+            libc(),
+            manylinux_compatibility(),
+            snapshot(),
+        );
+
+        Ok(FrozenConfig {
+            version,
+            responses: recorder.responses.into_inner(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, PythonConfig};
+
+    #[test]
+    fn replay() {
+        let cfg = PythonConfig::new();
+        let frozen = cfg.freeze().unwrap();
+        assert_eq!(frozen.version(), &cfg.version_info().unwrap());
+
+        let replayed = frozen.into_config();
+        assert_eq!(replayed.includes().unwrap(), cfg.includes().unwrap());
+        assert_eq!(
+            replayed.ldflags_split().unwrap(),
+            cfg.ldflags_split().unwrap()
+        );
+        assert_eq!(replayed.link_args().unwrap(), cfg.link_args().unwrap());
+        assert_eq!(replayed.lib_dir().unwrap(), cfg.lib_dir().unwrap());
+        assert_eq!(replayed.snapshot().unwrap(), cfg.snapshot().unwrap());
+        assert!(matches!(
+            replayed.config_var("LDVERSION"),
+            Err(Error::Other(_))
+        ));
+    }
+
+    #[cfg(not(feature = "python2"))]
+    #[test]
+    fn python2_needs_feature() {
+        use crate::{FrozenConfig, PyVersion, ReleaseLevel};
+
+        let frozen = FrozenConfig {
+            version: PyVersion {
+                major: 2,
+                minor: 7,
+                micro: 18,
+                releaselevel: ReleaseLevel::Final,
+                serial: 0,
+            },
+            responses: Vec::new(),
+        };
+        assert!(matches!(
+            frozen.into_config().prefix(),
+            Err(Error::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn save_and_load() {
+        use crate::FrozenConfig;
+        use std::env;
+
+        let frozen = PythonConfig::new().freeze().unwrap();
+        let formats: &[&str] = if cfg!(feature = "toml") {
+            &["json", "toml"]
+        } else {
+            &["json"]
+        };
+        for format in formats {
+            let path = env::temp_dir().join(format!(
+                "python-config-rs-frozen-{}.{}",
+                std::process::id(),
+                format
+            ));
+            frozen.save(&path).unwrap();
+            let loaded = FrozenConfig::load(&path);
+            let _ = std::fs::remove_file(&path);
+            assert_eq!(loaded.unwrap(), frozen);
+        }
+    }
+}
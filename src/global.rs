@@ -0,0 +1,68 @@
+//! A `PythonConfig` that's shared by the whole process
+
+use crate::{PyResult, PythonConfig, PythonConfigData};
+
+use std::sync::OnceLock;
+
+static GLOBAL: OnceLock<PythonConfig> = OnceLock::new();
+static SNAPSHOT: OnceLock<PythonConfigData> = OnceLock::new();
+
+/// Returns a `PythonConfig` that's shared by every caller in this process
+///
+/// We create it on the first call, like [`PythonConfig::new`](struct.PythonConfig.html#method.new),
+/// so it uses the interpreter named by the environment at that time. It
+/// [remembers](struct.PythonConfig.html#method.cached) each query's
+/// response, so a procedural macro, or a build script, that asks for the
+/// same value many times runs the interpreter once. It's safe to use from
+/// many threads.
+///
+/// # Example
+///
+/// ```no_run
+/// // Each expansion of the macro asks, but only the first runs Python
+/// let includes = python_config::global().includes().unwrap();
+/// ```
+pub fn global() -> &'static PythonConfig {
+    GLOBAL.get_or_init(|| PythonConfig::new().cached())
+}
+
+/// Returns a snapshot of the [`global`](fn.global.html) `PythonConfig`,
+/// taken on the first successful call
+///
+/// If taking the snapshot fails, we return the error, and try again on
+/// the next call.
+///
+/// # Example
+///
+/// ```no_run
+/// let data = python_config::global_snapshot().unwrap();
+/// println!("Python {} at {}", data.version, data.prefix);
+/// ```
+pub fn global_snapshot() -> PyResult<&'static PythonConfigData> {
+    if let Some(data) = SNAPSHOT.get() {
+        return Ok(data);
+    }
+    let data = global().snapshot()?;
+    Ok(SNAPSHOT.get_or_init(|| data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{global, global_snapshot};
+    use std::thread;
+
+    #[test]
+    fn shared() {
+        let addrs: Vec<usize> = (0..4)
+            .map(|_| thread::spawn(|| global() as *const _ as usize))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+        assert!(addrs.iter().all(|addr| *addr == addrs[0]));
+
+        let data = global_snapshot().unwrap();
+        assert!(std::ptr::eq(data, global_snapshot().unwrap()));
+        assert_eq!(global().includes().unwrap(), data.includes);
+    }
+}
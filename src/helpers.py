@@ -0,0 +1,40 @@
+# Functions that the query scripts share
+#
+# `build_script` runs these after the prelude, which defines `sys`, `os`,
+# `getvar`, `pyver`, and the platform flags, like `windows`.
+
+
+def library_name(abi3=False):
+    """The library that holds the C API, for `-l`
+
+    With `abi3`, this is the stable ABI library, `python3`. PyPy and
+    GraalPy name their libraries differently. PyPy 3.9 and newer install
+    a versioned `libpypy3.9-c`. CPython on Windows links pythonXY.lib (or
+    pythonXY_d.lib, for a debug build, and pythonXYt.lib, for a
+    free-threaded build).
+    """
+    impl = sys.implementation.name if hasattr(sys, 'implementation') else 'cpython'
+    if windows and impl == 'cpython':
+        version = '3' if abi3 else pyver + ('t' if getvar('Py_GIL_DISABLED') else '')
+        return 'python' + version + ('_d' if hasattr(sys, 'gettotalrefcount') else '')
+    if abi3:
+        return 'python3'
+    if impl == 'pypy':
+        if sys.version_info >= (3, 9):
+            return 'pypy%d.%d-c' % sys.version_info[:2]
+        return 'pypy3-c'
+    if impl == 'graalpy':
+        return 'python-native'
+    return 'python' + pyver + getattr(sys, 'abiflags', '')
+
+
+def is_conda():
+    """Whether the interpreter belongs to a conda environment
+
+    Activating a conda environment sets `CONDA_PREFIX`, but the
+    `conda-meta` directory is always there.
+    """
+    prefix = os.path.realpath(sys.prefix)
+    active = os.environ.get('CONDA_PREFIX')
+    return (os.path.isdir(os.path.join(prefix, 'conda-meta'))
+            or bool(active) and os.path.realpath(active) == prefix)
@@ -0,0 +1,50 @@
+//! Python implementations, like CPython and PyPy
+
+/// A Python implementation, as named by `sys.implementation.name`
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Implementation {
+    /// The reference implementation
+    CPython,
+    /// PyPy
+    PyPy,
+    /// GraalPy
+    GraalPy,
+    /// Another implementation, with its lowercase name
+    Other(String),
+}
+
+impl Implementation {
+    pub(crate) fn from_name(name: &str) -> Self {
+        match name {
+            "cpython" => Implementation::CPython,
+            "pypy" => Implementation::PyPy,
+            "graalpy" => Implementation::GraalPy,
+            other => Implementation::Other(other.to_owned()),
+        }
+    }
+
+    /// Returns the implementation's name, as reported by
+    /// `sys.implementation.name`
+    pub fn name(&self) -> &str {
+        match self {
+            Implementation::CPython => "cpython",
+            Implementation::PyPy => "pypy",
+            Implementation::GraalPy => "graalpy",
+            Implementation::Other(name) => name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Implementation;
+
+    #[test]
+    fn names() {
+        for name in &["cpython", "pypy", "graalpy", "ironpython"] {
+            assert_eq!(Implementation::from_name(name).name(), *name);
+        }
+        assert_eq!(Implementation::from_name("pypy"), Implementation::PyPy);
+    }
+}
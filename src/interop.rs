@@ -0,0 +1,79 @@
+//! Interoperability with other Python build tools
+
+use std::path::PathBuf;
+
+/// Describes the interpreter in the terms used by maturin and
+/// setuptools-rust
+///
+/// Use [`vars`](#method.vars) to set up the environment of a
+/// `std::process::Command` that runs one of those tools, or
+/// [`to_shell`](#method.to_shell) to `eval` the environment in a
+/// shell script. Either way, the tools build for the same interpreter
+/// that this crate describes.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BuildEnv {
+    /// The path to the interpreter
+    pub interpreter: PathBuf,
+    /// The wheel ABI tag for the interpreter, like `cp311` or `cp313t`
+    pub abi_tag: String,
+    /// The file extension for extension modules, like
+    /// `.cpython-311-x86_64-linux-gnu.so`
+    pub ext_suffix: String,
+}
+
+impl BuildEnv {
+    /// Returns the environment variables, and their values, that select
+    /// this interpreter
+    ///
+    /// - `PYO3_PYTHON` selects the interpreter for maturin and for
+    ///   setuptools-rust (through the pyo3 build script)
+    /// - `PYTHON_SYS_EXECUTABLE` selects the interpreter for rust-cpython,
+    ///   and for this crate
+    /// - `SETUPTOOLS_EXT_SUFFIX` overrides the extension suffix used by
+    ///   setuptools
+    /// - `PYTHON_CONFIG_ABI_TAG` is the wheel ABI tag, for scripts that
+    ///   name or check the wheels that the tools build
+    pub fn vars(&self) -> Vec<(&'static str, String)> {
+        let interpreter = self.interpreter.display().to_string();
+        vec![
+            ("PYO3_PYTHON", interpreter.clone()),
+            ("PYTHON_SYS_EXECUTABLE", interpreter),
+            ("SETUPTOOLS_EXT_SUFFIX", self.ext_suffix.clone()),
+            ("PYTHON_CONFIG_ABI_TAG", self.abi_tag.clone()),
+        ]
+    }
+
+    /// Renders [`vars`](#method.vars) as POSIX shell `export` statements,
+    /// one per line
+    pub fn to_shell(&self) -> String {
+        self.vars()
+            .into_iter()
+            .map(|(name, value)| format!("export {}='{}'\n", name, value.replace('\'', r"'\''")))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildEnv;
+
+    #[test]
+    fn shell_quoting() {
+        let env = BuildEnv {
+            interpreter: "/opt/it's/python3".into(),
+            abi_tag: "cp311".to_owned(),
+            ext_suffix: ".cpython-311-x86_64-linux-gnu.so".to_owned(),
+        };
+        assert_eq!(
+            env.to_shell(),
+            "export PYO3_PYTHON='/opt/it'\\''s/python3'\n\
+             export PYTHON_SYS_EXECUTABLE='/opt/it'\\''s/python3'\n\
+             export SETUPTOOLS_EXT_SUFFIX='.cpython-311-x86_64-linux-gnu.so'\n\
+             export PYTHON_CONFIG_ABI_TAG='cp311'\n"
+        );
+        assert!(env
+            .vars()
+            .contains(&("PYTHON_CONFIG_ABI_TAG", "cp311".to_owned())));
+    }
+}
@@ -0,0 +1,90 @@
+//! What we learn about an interpreter when we first run it
+
+use crate::{pyversion, Implementation, PyVersion};
+
+use std::path::{Path, PathBuf};
+
+/// Describes the interpreter behind a `PythonConfig`, as found when we
+/// created it
+///
+/// See [`PythonConfig::interpreter_info`](struct.PythonConfig.html#method.interpreter_info).
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterpreterInfo {
+    executable: PathBuf,
+    realpath: PathBuf,
+    version: PyVersion,
+    implementation: Implementation,
+    pointer_width: u32,
+}
+
+impl InterpreterInfo {
+    /// Returns the path to the interpreter, as reported by `sys.executable`
+    pub fn executable(&self) -> &Path {
+        &self.executable
+    }
+
+    /// Returns the interpreter's path with every symbolic link resolved,
+    /// like `/usr/bin/python3.11` for `/usr/bin/python3`
+    pub fn realpath(&self) -> &Path {
+        &self.realpath
+    }
+
+    /// Returns the interpreter's version, from `sys.version_info`
+    pub fn version(&self) -> &PyVersion {
+        &self.version
+    }
+
+    /// Returns the Python implementation, like CPython or PyPy
+    pub fn implementation(&self) -> &Implementation {
+        &self.implementation
+    }
+
+    /// Returns the size of a pointer, in bits, for the interpreter's
+    /// architecture: `32` or `64`
+    pub fn pointer_width(&self) -> u32 {
+        self.pointer_width
+    }
+
+    /// Parses the response of the `INTERPRETER_INFO` query
+    pub(crate) fn parse(resp: &str) -> Option<Self> {
+        let lines: Vec<&str> = resp.lines().collect();
+        if let [executable, realpath, version, implementation, pointer_width] = lines[..] {
+            Some(InterpreterInfo {
+                executable: PathBuf::from(executable),
+                realpath: PathBuf::from(realpath),
+                version: pyversion::parse(version)?,
+                implementation: Implementation::from_name(implementation),
+                pointer_width: pointer_width.parse().ok()?,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InterpreterInfo;
+    use crate::Implementation;
+    use std::path::Path;
+
+    #[test]
+    fn parse() {
+        let info = InterpreterInfo::parse(
+            "/usr/bin/python3\n/usr/bin/python3.11\n3 11 7 final 0\ncpython\n64",
+        )
+        .unwrap();
+        assert_eq!(info.executable(), Path::new("/usr/bin/python3"));
+        assert_eq!(info.realpath(), Path::new("/usr/bin/python3.11"));
+        assert_eq!(info.version().to_string(), "3.11.7");
+        assert_eq!(*info.implementation(), Implementation::CPython);
+        assert_eq!(info.pointer_width(), 64);
+
+        assert_eq!(InterpreterInfo::parse("-c print('hello')"), None);
+        assert_eq!(
+            InterpreterInfo::parse("/usr/bin/python3\n/usr/bin/python3.11\n3 11\ncpython\n64"),
+            None
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! A trait that abstracts over sources of Python configuration
+
+use crate::{Commander, Py3Only, PyResult, PyVersion, PythonConfig, PythonConfigData};
+
+use std::path::PathBuf;
+
+/// A source of Python configuration information
+///
+/// The trait is object safe, so downstream code can accept a
+/// `&dyn PythonIntrospection` and work with a live interpreter
+/// ([`PythonConfig`](struct.PythonConfig.html)), or a saved or
+/// hand-written snapshot ([`PythonConfigData`](struct.PythonConfigData.html)),
+/// without generic plumbing. A `PythonConfigData` with made-up values
+/// is a convenient mock in tests.
+///
+/// The methods have the same meaning as the `PythonConfig` methods
+/// of the same name.
+///
+/// # Example
+///
+/// ```no_run
+/// use python_config::{PythonConfig, PythonIntrospection};
+///
+/// fn print_includes(py: &dyn PythonIntrospection) {
+///     println!("{}", py.includes().unwrap());
+/// }
+///
+/// let cfg = PythonConfig::new();
+/// print_includes(&cfg);
+/// print_includes(&cfg.snapshot().unwrap());
+/// ```
+pub trait PythonIntrospection {
+    /// See [`PythonConfig::version_info`](struct.PythonConfig.html#method.version_info)
+    fn version_info(&self) -> PyResult<PyVersion>;
+    /// See [`PythonConfig::executable`](struct.PythonConfig.html#method.executable)
+    fn executable(&self) -> PyResult<String>;
+    /// See [`PythonConfig::prefix`](struct.PythonConfig.html#method.prefix)
+    fn prefix(&self) -> PyResult<String>;
+    /// See [`PythonConfig::exec_prefix`](struct.PythonConfig.html#method.exec_prefix)
+    fn exec_prefix(&self) -> PyResult<String>;
+    /// See [`PythonConfig::includes`](struct.PythonConfig.html#method.includes)
+    fn includes(&self) -> PyResult<String>;
+    /// See [`PythonConfig::cflags`](struct.PythonConfig.html#method.cflags)
+    fn cflags(&self) -> PyResult<String>;
+    /// See [`PythonConfig::libs`](struct.PythonConfig.html#method.libs)
+    fn libs(&self) -> PyResult<String>;
+    /// See [`PythonConfig::ldflags`](struct.PythonConfig.html#method.ldflags)
+    fn ldflags(&self) -> PyResult<String>;
+    /// See [`PythonConfig::extension_suffix`](struct.PythonConfig.html#method.extension_suffix)
+    fn extension_suffix(&self) -> Py3Only<String>;
+    /// See [`PythonConfig::abi_flags`](struct.PythonConfig.html#method.abi_flags)
+    fn abi_flags(&self) -> Py3Only<String>;
+    /// See [`PythonConfig::config_dir`](struct.PythonConfig.html#method.config_dir)
+    fn config_dir(&self) -> Py3Only<String>;
+
+    /// Like [`prefix`](#tymethod.prefix), but returns a `PathBuf`
+    fn prefix_path(&self) -> PyResult<PathBuf> {
+        self.prefix().map(PathBuf::from)
+    }
+
+    /// Like [`exec_prefix`](#tymethod.exec_prefix), but returns a `PathBuf`
+    fn exec_prefix_path(&self) -> PyResult<PathBuf> {
+        self.exec_prefix().map(PathBuf::from)
+    }
+
+    /// Like [`config_dir`](#tymethod.config_dir), but returns a `PathBuf`
+    fn config_dir_path(&self) -> Py3Only<PathBuf> {
+        self.config_dir().map(PathBuf::from)
+    }
+
+    /// Collects all of the values into a [`PythonConfigData`](struct.PythonConfigData.html)
+    ///
+    /// Unless a source says otherwise, the snapshot has no config vars,
+    /// and no libc.
+    fn snapshot(&self) -> Py3Only<PythonConfigData> {
+        Ok(PythonConfigData {
+            extension_suffix: self.extension_suffix()?,
+            abi_flags: self.abi_flags()?,
+            config_dir: self.config_dir()?,
+            version: self.version_info()?,
+            executable: self.executable()?,
+            prefix: self.prefix()?,
+            exec_prefix: self.exec_prefix()?,
+            includes: self.includes()?,
+            cflags: self.cflags()?,
+            libs: self.libs()?,
+            ldflags: self.ldflags()?,
+            libc: None,
+            config_vars: Default::default(),
+        })
+    }
+}
+
+impl<C: Commander> PythonIntrospection for PythonConfig<C> {
+    fn version_info(&self) -> PyResult<PyVersion> {
+        PythonConfig::version_info(self)
+    }
+    fn executable(&self) -> PyResult<String> {
+        PythonConfig::executable(self)
+    }
+    fn prefix(&self) -> PyResult<String> {
+        PythonConfig::prefix(self)
+    }
+    fn exec_prefix(&self) -> PyResult<String> {
+        PythonConfig::exec_prefix(self)
+    }
+    fn includes(&self) -> PyResult<String> {
+        PythonConfig::includes(self)
+    }
+    fn cflags(&self) -> PyResult<String> {
+        PythonConfig::cflags(self)
+    }
+    fn libs(&self) -> PyResult<String> {
+        PythonConfig::libs(self)
+    }
+    fn ldflags(&self) -> PyResult<String> {
+        PythonConfig::ldflags(self)
+    }
+    fn extension_suffix(&self) -> Py3Only<String> {
+        PythonConfig::extension_suffix(self)
+    }
+    fn abi_flags(&self) -> Py3Only<String> {
+        PythonConfig::abi_flags(self)
+    }
+    fn config_dir(&self) -> Py3Only<String> {
+        PythonConfig::config_dir(self)
+    }
+    fn snapshot(&self) -> Py3Only<PythonConfigData> {
+        PythonConfig::snapshot(self)
+    }
+}
+
+/// A snapshot answers every query from its saved values, without
+/// running an interpreter.
+impl PythonIntrospection for PythonConfigData {
+    fn version_info(&self) -> PyResult<PyVersion> {
+        Ok(self.version.clone())
+    }
+    fn executable(&self) -> PyResult<String> {
+        Ok(self.executable.clone())
+    }
+    fn prefix(&self) -> PyResult<String> {
+        Ok(self.prefix.clone())
+    }
+    fn exec_prefix(&self) -> PyResult<String> {
+        Ok(self.exec_prefix.clone())
+    }
+    fn includes(&self) -> PyResult<String> {
+        Ok(self.includes.clone())
+    }
+    fn cflags(&self) -> PyResult<String> {
+        Ok(self.cflags.clone())
+    }
+    fn libs(&self) -> PyResult<String> {
+        Ok(self.libs.clone())
+    }
+    fn ldflags(&self) -> PyResult<String> {
+        Ok(self.ldflags.clone())
+    }
+    fn extension_suffix(&self) -> Py3Only<String> {
+        Ok(self.extension_suffix.clone())
+    }
+    fn abi_flags(&self) -> Py3Only<String> {
+        Ok(self.abi_flags.clone())
+    }
+    fn config_dir(&self) -> Py3Only<String> {
+        Ok(self.config_dir.clone())
+    }
+    fn snapshot(&self) -> Py3Only<PythonConfigData> {
+        Ok(self.clone())
+    }
+}
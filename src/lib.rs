@@ -38,35 +38,189 @@
 //! ## 3 > 2
 //!
 //! We make the choice for you: by default, we favor Python 3
-//! over Python 2. If you need Python 2 support, use the more
-//! explicit interface to create the corresponding `PythonConfig`
+//! over Python 2. Python 2 support is deprecated, and it's only
+//! available with the `python2` feature. With the feature, use the
+//! more explicit interface to create the corresponding `PythonConfig`
 //! handle. Note that, while the Python 2 interface should work,
 //! it's gone through significantly less testing.
 //!
+//! To pin a minor version, use `Version::Custom`:
+//!
+//! ```no_run
+//! use python_config::{PythonConfig, Version};
+//!
+//! let cfg = PythonConfig::version(Version::Custom(3, 11)); // python3.11
+//! ```
+//!
 //! The `python3-config` binary in this crate is Python 3 only.
+//!
+//! ## Selecting an interpreter
+//!
+//! [`PythonConfig::new`](struct.PythonConfig.html#method.new) uses the
+//! interpreter named by the `PYTHON_CONFIG_INTERPRETER`, `PYO3_PYTHON`, or
+//! `PYTHON_SYS_EXECUTABLE` environment variables, if one is set. This lets
+//! the crate slot into pyo3 and rust-cpython workflows without new
+//! configuration. Otherwise, it uses `python3`. See
+//! [`PythonConfig::from_env`](struct.PythonConfig.html#method.from_env)
+//! for the precedence.
+//!
+//! To use the interpreter of the active virtual environment, regardless
+//! of the `PATH`, use
+//! [`PythonConfig::from_active_venv`](struct.PythonConfig.html#method.from_active_venv).
+//! To use the Python selected by pyenv, asdf, or Homebrew, rather than
+//! whichever shim comes first on the `PATH`, use
+//! [`PythonConfig::resolve`](struct.PythonConfig.html#method.resolve).
 
+mod arch;
+pub mod build;
+mod builder;
+mod buildflags;
+mod buildsys;
+mod cache;
+mod capabilities;
+#[cfg(feature = "cc")]
+mod ccbuild;
 mod cmdr;
+mod conda;
 #[macro_use]
 mod script;
+mod data;
+mod diagnose;
+pub mod discover;
+mod extension;
+mod extras;
+mod filter;
+mod frozen;
+mod global;
+mod implementation;
+mod interop;
+mod interpreter;
+mod introspect;
+mod libc;
+mod link;
+mod matrix;
+#[cfg(feature = "tokio")]
+mod nonblocking;
+mod origin;
+mod pkgconfig;
+mod pyversion;
+mod query;
+mod remote;
+mod report;
+mod resolve;
+mod sanitizer;
+mod session;
+mod sysconfigdata;
+#[cfg(test)]
+mod testing;
+mod toolchain;
 
-use cmdr::SysCommand;
-
-use semver;
+pub use arch::Arch;
+pub use builder::PythonConfigBuilder;
+pub use buildflags::BuildFlags;
+use cache::Cache;
+pub use capabilities::{Capabilities, Capability};
+pub use cmdr::{Commander, RetryPolicy, SysCommand};
+pub use data::{ConfigDiff, FieldDiff, PythonConfigData};
+pub use diagnose::{Check, Diagnostic, Diagnostics};
+pub use extension::ExtensionAbi;
+pub use filter::FilterPolicy;
+pub use frozen::FrozenConfig;
+pub use global::{global, global_snapshot};
+pub use implementation::Implementation;
+pub use interop::BuildEnv;
+pub use interpreter::InterpreterInfo;
+pub use introspect::PythonIntrospection;
+pub use libc::{Libc, ManylinuxCompatibility};
+pub use link::{ExportedSymbols, Framework, LinkArgs, LinkMode, ResponseFileFormat};
+pub use matrix::{MatrixEntry, PythonMatrix};
+#[cfg(feature = "tokio")]
+pub use nonblocking::AsyncPythonConfig;
+pub use origin::InstallOrigin;
+pub use pkgconfig::PkgConfigFile;
+pub use pyversion::{PyVersion, PythonVersion, ReleaseLevel};
+pub use remote::RemoteCommand;
+pub use report::{compare, InterpreterReport, InterpreterSummary};
+pub use resolve::ResolutionStrategy;
+pub use sanitizer::Sanitizer;
+pub use session::PersistentCommand;
+pub use toolchain::BuildToolchain;
 
+use std::borrow::Cow;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
 use std::io;
 use std::path::{self, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
 
 /// Selectable Python version
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Version {
     /// Python 3
     Three,
     /// Python 2
+    ///
+    /// Python 2 support is deprecated. Requires the `python2` feature.
+    #[cfg(feature = "python2")]
     Two,
+    /// A specific major and minor version, like `Custom(3, 11)`
+    ///
+    /// We run the versioned interpreter, like `python3.11`, or the
+    /// `py -3.11` launcher if there's no such interpreter on the `PATH`.
+    /// Like `Two`, a major version of 2 requires the `python2` feature;
+    /// without it, and for any other major version, queries fail with
+    /// [`Error::UnsupportedVersion`](enum.Error.html#variant.UnsupportedVersion).
+    Custom(u8, u8),
+}
+
+/// Describes the major versions that we support
+const SUPPORTED_VERSIONS: &str = if cfg!(feature = "python2") {
+    "Python 2 or 3"
+} else {
+    "Python 3"
+};
+
+impl Version {
+    /// The major version, like `3`
+    fn major(&self) -> u8 {
+        match self {
+            Version::Three => 3,
+            #[cfg(feature = "python2")]
+            Version::Two => 2,
+            Version::Custom(major, _) => *major,
+        }
+    }
+
+    /// Fails if we don't support the major version, like a
+    /// `Version::Custom(2, 7)` without the `python2` feature
+    fn supported(&self) -> PyResult<()> {
+        match (self.major(), self) {
+            (3, _) => Ok(()),
+            #[cfg(feature = "python2")]
+            (2, _) => Ok(()),
+            (major, Version::Custom(_, minor)) => Err(Error::UnsupportedVersion {
+                version: format!("{}.{}", major, minor),
+                required: SUPPORTED_VERSIONS,
+            }),
+            (major, _) => Err(Error::UnsupportedVersion {
+                version: major.to_string(),
+                required: SUPPORTED_VERSIONS,
+            }),
+        }
+    }
 }
 
 /// Describes a few possible errors from the `PythonConfig` interface
+///
+/// `Error` implements `std::error::Error`, so it composes with error
+/// handling crates like `anyhow`. New variants may be added in the
+/// future, so a `match` needs a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// An I/O error occured while interfacing the interpreter
     IO(io::Error),
@@ -76,7 +230,133 @@ pub enum Error {
     /// a [`Py3Only<T>`](type.Py3Only.html) type.
     Python3Only,
     /// Other, one-off errors, with reasoning provided as a string
-    Other(&'static str),
+    Other(Cow<'static, str>),
+    /// The interpreter ran, but exited with a non-zero status
+    ///
+    /// This usually means that our script failed, like when the
+    /// interpreter can't import a module. The interpreter's stderr
+    /// describes the failure.
+    Interpreter {
+        /// The exit code, or `None` if the interpreter was terminated
+        /// by a signal
+        status: Option<i32>,
+        /// Everything the interpreter printed to stderr, like a traceback
+        stderr: String,
+    },
+    /// The interpreter does not exist, or is not on the `PATH`
+    InterpreterNotFound {
+        /// The name or path of the interpreter that we tried to run
+        interpreter: String,
+    },
+    /// The interpreter ran, but we couldn't make sense of its output
+    ParseError {
+        /// Describes what we expected to find
+        expected: &'static str,
+        /// What we found instead
+        output: String,
+    },
+    /// The interpreter's version is not supported
+    UnsupportedVersion {
+        /// The interpreter's version
+        version: String,
+        /// Describes the versions that are supported, like `Python 2 or 3`
+        required: &'static str,
+    },
+    /// The program ran, but it's not a Python interpreter
+    NotAPython {
+        /// The name or path of the program that we ran
+        interpreter: String,
+    },
+    /// None of the interpreters that we found match the version requirement
+    NoMatchingInterpreter {
+        /// The version requirement, like `>=3.9, <3.13`
+        requirement: String,
+        /// The interpreters that we found, none of which match
+        found: Vec<discover::DiscoveredInterpreter>,
+    },
+    /// The interpreter doesn't define a value that we asked for, like a
+    /// config var that's missing on Windows
+    ///
+    /// The `try_*` methods, like [`try_prefix`](struct.PythonConfig.html#method.try_prefix),
+    /// return `None` instead.
+    Undefined {
+        /// What's not defined, like `the config var LIBPL`
+        what: String,
+    },
+    /// The interpreter's implementation doesn't support what we asked
+    /// for, like the config dir of PyPy
+    ///
+    /// See [`PythonConfig::capabilities`](struct.PythonConfig.html#method.capabilities).
+    Unsupported(Capability),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::IO(err) => write!(f, "{}", err),
+            Error::Python3Only => f.write_str("this function is only available for Python 3"),
+            Error::Other(why) => f.write_str(why),
+            Error::Interpreter {
+                status: Some(code),
+                stderr,
+            } => write!(f, "the interpreter exited with status {}: {}", code, stderr),
+            Error::Interpreter {
+                status: None,
+                stderr,
+            } => {
+                write!(f, "the interpreter was terminated: {}", stderr)
+            }
+            Error::InterpreterNotFound { interpreter } => {
+                write!(f, "the Python interpreter '{}' was not found", interpreter)
+            }
+            Error::NotAPython { interpreter } => {
+                write!(f, "'{}' is not a Python interpreter", interpreter)
+            }
+            Error::ParseError { expected, output } => {
+                write!(f, "{}, but the output was '{}'", expected, output)
+            }
+            Error::UnsupportedVersion { version, required } => {
+                write!(
+                    f,
+                    "Python {} is not supported; expected {}",
+                    version, required
+                )
+            }
+            Error::NoMatchingInterpreter { requirement, found } => {
+                write!(f, "no Python interpreter matches '{}'", requirement)?;
+                if found.is_empty() {
+                    return f.write_str("; found no interpreters");
+                }
+                f.write_str("; found")?;
+                for (idx, interpreter) in found.iter().enumerate() {
+                    let sep = if idx == 0 { " " } else { ", " };
+                    write!(
+                        f,
+                        "{}{} ({})",
+                        sep,
+                        interpreter.version,
+                        interpreter.path.display()
+                    )?;
+                }
+                Ok(())
+            }
+            Error::Undefined { what } => {
+                write!(f, "the interpreter doesn't define {}", what)
+            }
+            Error::Unsupported(capability) => {
+                write!(f, "the interpreter doesn't support {}", capability)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IO(err) => Some(err),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for Error {
@@ -85,15 +365,13 @@ impl From<io::Error> for Error {
     }
 }
 
+/// A missing interpreter becomes an error of kind `NotFound`
 impl From<Error> for io::Error {
     fn from(err: Error) -> Self {
         match err {
             Error::IO(err) => err,
-            Error::Python3Only => io::Error::new(
-                io::ErrorKind::Other,
-                "this function is only available for Python 3",
-            ),
-            Error::Other(why) => io::Error::new(io::ErrorKind::Other, why),
+            Error::InterpreterNotFound { .. } => io::Error::new(io::ErrorKind::NotFound, err),
+            err => io::Error::other(err),
         }
     }
 }
@@ -113,7 +391,27 @@ pub type Py3Only<T> = Result<T, Error>;
 
 #[inline]
 fn other_err(what: &'static str) -> Error {
-    Error::Other(what)
+    Error::Other(Cow::Borrowed(what))
+}
+
+#[inline]
+fn parse_err(expected: &'static str, output: &str) -> Error {
+    Error::ParseError {
+        expected,
+        output: output.to_owned(),
+    }
+}
+
+/// Parses the `True` or `False` printed by a Python script
+fn parse_bool(resp: &str) -> PyResult<bool> {
+    match resp {
+        "True" => Ok(true),
+        "False" => Ok(false),
+        _ => Err(parse_err(
+            "expected the script to print 'True' or 'False'",
+            resp,
+        )),
+    }
 }
 
 /// Defines the script with a common prelude of imports
@@ -125,16 +423,175 @@ fn build_script(lines: &[&str]) -> String {
     script.push_str("import sysconfig\n");
     script.push_str("pyver = sysconfig.get_config_var('VERSION')\n");
     script.push_str("getvar = sysconfig.get_config_var\n");
+    // The interpreter's platform, which may differ from the platform
+    // that we're compiled for, like when we run a Windows interpreter
+    // through WSL.
+    script.push_str("import os, sys\n");
+    script.push_str("linux = sys.platform.startswith('linux')\n");
+    script.push_str("macos = sys.platform == 'darwin'\n");
+    script.push_str("windows = os.name == 'nt'\n");
+    script.push_str(query::HELPERS);
+    script.push_str(&format!("print('{}')\n", query::BEGIN));
     script.push_str(&lines.join("\n"));
+    script.push_str(&format!("\nprint('{}')", query::END));
     script
 }
 
+/// Returns what the lines of a script printed, between the markers that
+/// `build_script` prints around them
+///
+/// Anything else on stdout, like a banner printed by `sitecustomize`, or a
+/// warning printed while importing the prelude, is dropped. Commanders
+/// that answer without running the script, like canned responses, don't
+/// print the markers, so we keep their whole response.
+fn unframe(resp: String) -> String {
+    let mut lines = resp.split_inclusive('\n');
+    if !lines.any(|line| line.trim_end() == query::BEGIN) {
+        return resp;
+    }
+    let body: Vec<&str> = lines.collect();
+    let end = body
+        .iter()
+        .rposition(|line| line.trim_end() == query::END)
+        .unwrap_or(body.len());
+    body[..end].concat()
+}
+
+/// Parses the output of `python --version`, like `Python 3.11.7`
+///
+/// Versions that aren't semantic versions, like `3.13.0rc2`, are
+/// converted from a `PythonVersion`.
+fn parse_version_raw(resp: &str) -> PyResult<semver::Version> {
+    semver::Version::parse(version_word(resp)?)
+        .or_else(|_| parse_python_version(resp).map(semver::Version::from))
+        .map_err(|_| parse_err("expected a semantic version", resp))
+}
+
+/// Like `parse_version_raw`, but returns a `PythonVersion`
+fn parse_python_version(resp: &str) -> PyResult<PythonVersion> {
+    version_word(resp)?
+        .parse()
+        .map_err(|_| parse_err("expected a Python version", resp))
+}
+
+/// Returns the version from the output of `python --version`, the word
+/// after `Python`
+fn version_word(resp: &str) -> PyResult<&str> {
+    let mut witer = resp.split_whitespace();
+    witer.next(); // 'Python'
+    witer.next().ok_or_else(|| {
+        parse_err(
+            "expected --version to return a string resembling 'Python X.Y.Z'",
+            resp,
+        )
+    })
+}
+
+/// Normalizes what a commander returns: no leading or trailing
+/// whitespace, and `\n` line endings, even from a Windows interpreter
+fn normalize(resp: String) -> String {
+    let trimmed = resp.trim();
+    if trimmed.contains('\r') {
+        trimmed.replace("\r\n", "\n")
+    } else if trimmed.len() == resp.len() {
+        resp
+    } else {
+        trimmed.to_owned()
+    }
+}
+
+/// Parses a value printed by a script, where Python's `None` means that
+/// the value isn't defined
+fn parse_value(resp: String) -> Option<String> {
+    Some(resp).filter(|value| value != "None")
+}
+
+/// Like `parse_value`, but fails if the value isn't defined
+fn defined(resp: String, what: impl Into<String>) -> PyResult<String> {
+    parse_value(resp).ok_or_else(|| Error::Undefined { what: what.into() })
+}
+
+/// Collects the non-empty lines of a `--lines` response
+fn split_lines(resp: &str) -> Vec<String> {
+    resp.lines()
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Parses the output of the `CONFIG_VAR` query
+fn parse_config_var(resp: &str) -> PyResult<Option<String>> {
+    match resp.split_once('\n') {
+        Some(("1", value)) => Ok(Some(value.to_owned())),
+        None if resp == "1" => Ok(Some(String::new())),
+        None if resp == "0" => Ok(None),
+        _ => Err(parse_err("expected a config var", resp)),
+    }
+}
+
+/// Removes the repeated paths from `paths`, keeping the first of each
+fn dedup_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut unique = Vec::with_capacity(paths.len());
+    for path in paths {
+        if !unique.contains(&path) {
+            unique.push(path);
+        }
+    }
+    unique
+}
+
+/// Creates a `PythonConfig` for the newest interpreter in `found` that
+/// matches `req`
+fn best_of(
+    found: Vec<discover::DiscoveredInterpreter>,
+    req: &semver::VersionReq,
+) -> PyResult<PythonConfig> {
+    match discover::best_match(&found, req) {
+        Some(interpreter) => PythonConfig::interpreter(interpreter.path),
+        None => Err(Error::NoMatchingInterpreter {
+            requirement: req.to_string(),
+            found,
+        }),
+    }
+}
+
+/// The environment variables that select the interpreter used by
+/// [`PythonConfig::from_env`](struct.PythonConfig.html#method.from_env),
+/// from highest to lowest precedence
+///
+/// A build script can ask cargo to re-run when they change:
+///
+/// ```no_run
+/// for var in python_config::INTERPRETER_ENV_VARS {
+///     println!("cargo:rerun-if-env-changed={}", var);
+/// }
+/// ```
+pub const INTERPRETER_ENV_VARS: &[&str] = &[
+    "PYTHON_CONFIG_INTERPRETER",
+    "PYO3_PYTHON",
+    "PYTHON_SYS_EXECUTABLE",
+];
+
 /// Exposes Python configuration information
-pub struct PythonConfig {
+///
+/// By default, we run a Python interpreter to answer each query. To
+/// answer queries some other way, like from canned responses in a test,
+/// implement [`Commander`](trait.Commander.html) and use
+/// [`with_commander`](#method.with_commander).
+pub struct PythonConfig<C: Commander = SysCommand> {
     /// The commander that provides responses to our commands
-    cmdr: SysCommand,
+    cmdr: C,
     /// The version of the Python interpreter we're using
     ver: Version,
+    /// What we learned about the interpreter when we created this
+    /// `PythonConfig`, if we ran it
+    info: Option<InterpreterInfo>,
+    /// The strategy that chose the interpreter, if we resolved it
+    resolution: Option<ResolutionStrategy>,
+    /// Answer `libs` and `ldflags` as if for `--embed`
+    embed: bool,
+    /// The implementation's capabilities, once we've queried them
+    caps: OnceLock<Capabilities>,
 }
 
 impl Default for PythonConfig {
@@ -146,8 +603,46 @@ impl Default for PythonConfig {
 impl PythonConfig {
     /// Create a new `PythonConfig` that uses the system installed Python 3
     /// interpreter to query configuration information.
+    ///
+    /// The interpreter may be overridden by environment variables. See
+    /// [`from_env`](#method.from_env) for details.
     pub fn new() -> Self {
-        PythonConfig::version(Version::Three)
+        PythonConfig::from_env()
+    }
+
+    /// Create a `PythonConfig` that uses the interpreter named by the
+    /// environment, falling back to `python3`
+    ///
+    /// The first of these variables that's set, and not empty, names
+    /// the interpreter:
+    ///
+    /// 1. `PYTHON_CONFIG_INTERPRETER`, specific to this crate
+    /// 2. `PYO3_PYTHON`, respected by the pyo3 build scripts
+    /// 3. `PYTHON_SYS_EXECUTABLE`, respected by the pyo3 and rust-cpython
+    ///    build scripts
+    ///
+    /// The interpreter is assumed to be a Python 3 interpreter. The
+    /// variables are listed in [`INTERPRETER_ENV_VARS`](constant.INTERPRETER_ENV_VARS.html).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// // PYTHON_CONFIG_INTERPRETER=/opt/python/bin/python3 cargo build
+    /// let cfg = PythonConfig::from_env();
+    /// ```
+    pub fn from_env() -> Self {
+        let interpreter = INTERPRETER_ENV_VARS
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|interpreter| !interpreter.is_empty());
+        match interpreter {
+            Some(interpreter) => {
+                Self::with_commander(Version::Three, SysCommand::new(&interpreter))
+            }
+            None => PythonConfig::version(Version::Three),
+        }
     }
 
     /// Create a new `PythonConfig` that uses the system installed Python
@@ -161,29 +656,174 @@ impl PythonConfig {
     /// // Use the system-wide Python3 interpreter
     /// let cfg = PythonConfig::version(Version::Three);
     /// ```
+    ///
+    /// When there's no `python3` on the `PATH`, we fall back to the `py -3`
+    /// launcher, then to the newest versioned interpreter, like `python3.12`,
+    /// then to `python`. The same goes for Python 2. Use
+    /// [`interpreter_path`](#method.interpreter_path) to learn which
+    /// we picked.
+    ///
+    /// For `Version::Custom`, we run the versioned interpreter, like
+    /// `python3.11`, falling back to the `py -3.11` launcher.
     pub fn version(version: Version) -> Self {
-        match version {
-            Version::Three => Self::with_commander(version, SysCommand::new("python3")),
-            Version::Two => Self::with_commander(version, SysCommand::new("python2")),
-        }
+        let major = u64::from(version.major());
+        let command = match version {
+            Version::Custom(_, minor) => Some(discover::versioned_command(major, minor.into())),
+            _ => discover::default_command(major),
+        };
+        let cmdr = match command {
+            Some((program, args)) => {
+                let mut cmdr = SysCommand::new(&program);
+                cmdr.set_args(args);
+                cmdr
+            }
+            None => SysCommand::new(&format!("python{}", major)),
+        };
+        Self::with_commander(version, cmdr)
     }
 
-    fn with_commander(ver: Version, cmdr: SysCommand) -> Self {
-        PythonConfig { cmdr, ver }
+    /// Retry transient failures to spawn the interpreter according to
+    /// `policy`
+    ///
+    /// By default, we never retry. See [`RetryPolicy`](struct.RetryPolicy.html)
+    /// for the failures that we consider transient.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use python_config::{PythonConfig, RetryPolicy};
+    /// use std::time::Duration;
+    ///
+    /// let cfg = PythonConfig::new()
+    ///     .with_retry(RetryPolicy::new(3, Duration::from_millis(100)));
+    /// ```
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.cmdr.set_retry(policy);
+        self
     }
 
-    fn is_py3(&self) -> Result<(), Error> {
-        if self.ver != Version::Three {
-            Err(Error::Python3Only)
-        } else {
-            Ok(())
-        }
+    /// Kill the interpreter, and fail the query, if a query runs for
+    /// longer than `timeout`
+    ///
+    /// By default, we wait as long as it takes. A timed out query returns
+    /// an I/O error of kind `TimedOut`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    /// use std::time::Duration;
+    ///
+    /// let cfg = PythonConfig::new().with_timeout(Duration::from_secs(30));
+    /// ```
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.cmdr.set_timeout(timeout);
+        self
+    }
+
+    /// Set the environment variable `key` to `value` when running the
+    /// interpreter
+    pub fn with_env<K: Into<OsString>, V: Into<OsString>>(mut self, key: K, value: V) -> Self {
+        self.cmdr.set_env(key.into(), Some(value.into()));
+        self
+    }
+
+    /// Remove the environment variable `key` when running the interpreter
+    pub fn without_env<K: Into<OsString>>(mut self, key: K) -> Self {
+        self.cmdr.set_env(key.into(), None);
+        self
+    }
+
+    /// Protect queries from environment variables that change how the
+    /// interpreter starts
+    ///
+    /// This removes `PYTHONSTARTUP`, `PYTHONHOME`, and `PYTHONPATH`, and
+    /// sets `PYTHONIOENCODING` to `utf-8`, so that a user's interactive
+    /// setup can't break a build.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new().with_clean_env();
+    /// ```
+    pub fn with_clean_env(self) -> Self {
+        self.without_env("PYTHONSTARTUP")
+            .without_env("PYTHONHOME")
+            .without_env("PYTHONPATH")
+            .with_env("PYTHONIOENCODING", "utf-8")
+    }
+
+    /// Run the interpreter in the directory `dir`, rather than in the
+    /// current working directory
+    ///
+    /// Python puts the working directory on its import path, so a
+    /// stray `sysconfig.py` in your project could change the results.
+    pub fn with_current_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.cmdr.set_current_dir(dir.into());
+        self
+    }
+
+    /// Remember each query's response, so that asking the same question
+    /// twice only runs the interpreter once
+    ///
+    /// The cache lives as long as this `PythonConfig`. Failed queries are
+    /// not cached. If you need several values at once, also consider
+    /// [`snapshot`](#method.snapshot), which runs the interpreter once.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new().cached();
+    /// let first = cfg.includes().unwrap();
+    /// let second = cfg.includes().unwrap(); // No new process
+    /// assert_eq!(first, second);
+    /// ```
+    pub fn cached(mut self) -> Self {
+        self.cmdr.set_cache(Cache::in_memory());
+        self
+    }
+
+    /// Like [`cached`](#method.cached), but also remembers responses in
+    /// files under `dir`, so that they outlive this process
+    ///
+    /// The cached responses are keyed by the interpreter's path,
+    /// modification time, and size, so reinstalling or upgrading the
+    /// interpreter invalidates them. The key also holds the working
+    /// directory, and the variables that Python reads, like `PYTHONHOME`
+    /// and `VIRTUAL_ENV`. The cache can't see changes that don't touch
+    /// the interpreter, like a new `sitecustomize.py`, or switching the
+    /// version behind a pyenv or asdf shim; delete `dir` to start over.
+    /// Failing to read or write the directory is not an error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    /// use std::env;
+    /// use std::path::PathBuf;
+    ///
+    /// // In a build script
+    /// let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    /// let cfg = PythonConfig::new().cached_on_disk(out_dir.join("python-config"));
+    /// ```
+    pub fn cached_on_disk<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.cmdr.set_cache(Cache::on_disk(dir.into()));
+        self
     }
 
     /// Create a `PythonConfig` that uses the interpreter at the path `interpreter`.
     ///
-    /// This fails if the path cannot be represented as a string, or if a query
-    /// for the Python version fails.
+    /// We run the interpreter right away, to check that it's a Python
+    /// interpreter, and to learn about it; see
+    /// [`interpreter_info`](#method.interpreter_info). This fails with
+    /// [`Error::NotAPython`](enum.Error.html#variant.NotAPython) if the
+    /// program runs, but it's not Python. It also fails if the path cannot
+    /// be represented as a string. Python 2 interpreters are only supported
+    /// with the `python2` feature.
     ///
     /// # Example
     ///
@@ -200,89 +840,182 @@ impl PythonConfig {
                 .to_str()
                 .ok_or_else(|| other_err("unable to coerce interpreter path to string"))?,
         );
-        // Assume Python 3 unless the semver tells us otherwise
-        let mut cfg = PythonConfig {
-            cmdr,
-            ver: Version::Three,
+        PythonConfig::probe_interpreter(cmdr)
+    }
+
+    /// Runs the interpreter behind `cmdr`, to check that it's a supported
+    /// Python interpreter, and to learn about it
+    fn probe_interpreter(cmdr: SysCommand) -> PyResult<Self> {
+        let interpreter = cmdr.program().to_owned();
+        // Assume Python 3 unless the version tells us otherwise
+        let mut cfg = PythonConfig::with_commander(Version::Three, cmdr);
+        let not_a_python = || Error::NotAPython {
+            interpreter: interpreter.clone(),
+        };
+        let info = match cfg.script(query::INTERPRETER_INFO) {
+            Ok(resp) => InterpreterInfo::parse(&resp).ok_or_else(not_a_python)?,
+            Err(Error::Interpreter { .. }) => return Err(not_a_python()),
+            Err(err) => return Err(err),
         };
+        let version = info.version().clone();
+        cfg.info = Some(info);
 
-        if cfg.semantic_version()?.major == 2 {
-            cfg.ver = Version::Two;
+        match version.major {
+            #[cfg(feature = "python2")]
+            2 => Ok(PythonConfig {
+                ver: Version::Two,
+                ..cfg
+            }),
+            3 => Ok(cfg),
+            _ => Err(Error::UnsupportedVersion {
+                version: version.to_string(),
+                required: SUPPORTED_VERSIONS,
+            }),
         }
+    }
 
-        Ok(cfg)
+    /// Create a `PythonConfig` that uses the interpreter of the virtual
+    /// environment at `venv`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::from_venv(".venv").unwrap();
+    /// assert!(cfg.is_virtualenv().unwrap());
+    /// ```
+    pub fn from_venv<P: AsRef<path::Path>>(venv: P) -> PyResult<Self> {
+        let venv = venv.as_ref();
+        if cfg!(windows) {
+            PythonConfig::interpreter(venv.join("Scripts").join("python.exe"))
+        } else {
+            PythonConfig::interpreter(venv.join("bin").join("python"))
+        }
     }
 
-    /// Returns the Python version string
+    /// Create a `PythonConfig` that uses the interpreter of the active
+    /// virtual environment, as named by the `VIRTUAL_ENV` environment
+    /// variable
     ///
-    /// This is the raw return of `python --version`. Consider using
-    /// [`semantic_version`](struct.PythonConfig.html#method.semantic_version)
-    /// for something more useful.
+    /// A virtual environment's activation script sets `VIRTUAL_ENV`, but
+    /// a build may run without the venv's `bin` directory first on the
+    /// `PATH`. This fails if `VIRTUAL_ENV` is not set.
+    pub fn from_active_venv() -> PyResult<Self> {
+        match env::var_os("VIRTUAL_ENV") {
+            Some(ref venv) if !venv.is_empty() => PythonConfig::from_venv(venv),
+            _ => Err(other_err("VIRTUAL_ENV is not set")),
+        }
+    }
+
+    /// Create a `PythonConfig` that uses the interpreter of a conda
+    /// environment, named like `py311` or `base`, or given by its path
+    ///
+    /// We find named environments with `conda info --json`, running the
+    /// conda named by `CONDA_EXE`, or `conda` on the `PATH`. A path to an
+    /// environment doesn't need conda.
     ///
     /// # Example
     ///
     /// ```no_run
     /// use python_config::PythonConfig;
     ///
-    /// let cfg = PythonConfig::new();
-    /// // Prints something like 'Python 3.7.4'
-    /// println!("{}", cfg.version_raw().unwrap());
+    /// let cfg = PythonConfig::from_conda_env("py311").unwrap();
+    /// assert!(cfg.is_conda().unwrap());
     /// ```
-    pub fn version_raw(&self) -> PyResult<String> {
-        self.cmdr.commands(&["--version"]).map_err(From::from)
+    pub fn from_conda_env(name_or_path: &str) -> PyResult<Self> {
+        let prefix = if path::Path::new(name_or_path).is_dir() {
+            PathBuf::from(name_or_path)
+        } else {
+            conda::resolve(&conda::info()?, name_or_path).ok_or_else(|| {
+                Error::Other(format!("no conda environment named {}", name_or_path).into())
+            })?
+        };
+        PythonConfig::interpreter(conda::interpreter(&prefix))
     }
 
-    /// Returns the Python version as a semver
+    /// Create a `PythonConfig` that uses the newest interpreter in the GitHub
+    /// Actions tool cache whose version matches `req`
+    ///
+    /// This finds the interpreters installed by `setup-python`, even when the
+    /// `PATH` ordering would select a different Python. See
+    /// [`discover::hostedtoolcache`](discover/fn.hostedtoolcache.html) for details.
     ///
     /// # Example
     ///
     /// ```no_run
     /// use python_config::PythonConfig;
+    /// use semver::VersionReq;
     ///
-    /// let cfg = PythonConfig::new();
-    /// // Prints semver "3.7.4"
-    /// println!("{}", cfg.semantic_version().unwrap());
+    /// let req = VersionReq::parse("~3.11").unwrap();
+    /// let cfg = PythonConfig::from_toolcache(&req).unwrap();
     /// ```
-    pub fn semantic_version(&self) -> PyResult<semver::Version> {
-        self.version_raw()
-            .and_then(|resp| {
-                let mut witer = resp.split_whitespace();
-                witer.next(); // 'Python'
-                let ver = witer.next().ok_or_else(|| {
-                    other_err("expected --version to return a string resembling 'Python X.Y.Z'")
-                })?;
-                semver::Version::parse(ver).map_err(|_| other_err("unable to parse semver"))
-            })
-            .map_err(From::from)
+    pub fn from_toolcache(req: &semver::VersionReq) -> PyResult<Self> {
+        best_of(discover::hostedtoolcache(), req)
     }
 
-    fn script(&self, lines: &[&str]) -> PyResult<String> {
-        self.cmdr
-            .commands(&["-c", &build_script(lines)])
-            .map_err(From::from)
+    /// Create a `PythonConfig` that uses the newest interpreter on this system
+    /// whose version matches `req`
+    ///
+    /// Rather than using whichever `python3` comes first on the `PATH`, this
+    /// considers every interpreter found by [`discover::all`](discover/fn.all.html).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    /// use semver::VersionReq;
+    ///
+    /// let req = VersionReq::parse(">=3.9").unwrap();
+    /// let cfg = PythonConfig::discover_best(&req).unwrap();
+    /// ```
+    pub fn discover_best(req: &semver::VersionReq) -> PyResult<Self> {
+        best_of(discover::all(), req)
     }
 
-    /// Returns the installation prefix of the Python interpreter as a string.
+    /// Create a `PythonConfig` that uses the first interpreter on this system
+    /// whose version matches `req`
     ///
-    /// The prefix is dependent on the host operating system.
-    /// On macOS, depending on how Python is installed, it will return
-    /// a string resembling
-    /// `/usr/local/opt/python/Frameworks/Python.framework/Versions/3.7`.
+    /// We search the interpreters found by [`discover::all`](discover/fn.all.html),
+    /// in that order, so an interpreter on the `PATH` wins over a newer one
+    /// elsewhere. Use [`discover_best`](#method.discover_best) to prefer the
+    /// newest match instead. If nothing matches, the
+    /// [`Error::NoMatchingInterpreter`](enum.Error.html#variant.NoMatchingInterpreter)
+    /// lists what we found.
     ///
     /// # Example
     ///
     /// ```no_run
+    /// // build.rs
     /// use python_config::PythonConfig;
+    /// use semver::VersionReq;
     ///
-    /// let cfg = PythonConfig::new();
-    /// println!("{}", cfg.prefix().unwrap());
+    /// let req = VersionReq::parse(">=3.9, <3.13").unwrap();
+    /// let cfg = PythonConfig::matching(&req).unwrap_or_else(|err| panic!("{}", err));
     /// ```
-    pub fn prefix(&self) -> PyResult<String> {
-        self.script(&["print(getvar('prefix'))"])
+    pub fn matching(req: &semver::VersionReq) -> PyResult<Self> {
+        let found = discover::all();
+        match found
+            .iter()
+            .find(|interpreter| req.matches(&interpreter.version))
+        {
+            Some(interpreter) => PythonConfig::interpreter(&interpreter.path),
+            None => Err(Error::NoMatchingInterpreter {
+                requirement: req.to_string(),
+                found,
+            }),
+        }
     }
 
-    /// Like [`prefix`](#method.prefix), but returns
-    /// the installation prefix as a `PathBuf`.
+    /// Returns the path to the program that we run to answer queries
+    ///
+    /// Names, like `python3`, are found on the `PATH`. This fails with
+    /// [`Error::InterpreterNotFound`](enum.Error.html#variant.InterpreterNotFound)
+    /// if the program doesn't exist.
+    ///
+    /// If we picked the `py` launcher, this is the launcher. Use
+    /// [`executable`](#method.executable) for the interpreter that the
+    /// launcher runs.
     ///
     /// # Example
     ///
@@ -290,113 +1023,1111 @@ impl PythonConfig {
     /// use python_config::PythonConfig;
     ///
     /// let cfg = PythonConfig::new();
-    /// println!("{}", cfg.prefix_path().unwrap().display());
+    /// println!("{}", cfg.interpreter_path().unwrap().display());
     /// ```
-    pub fn prefix_path(&self) -> PyResult<PathBuf> {
-        self.prefix().map(PathBuf::from)
+    pub fn interpreter_path(&self) -> PyResult<PathBuf> {
+        cache::find_program(self.cmdr.program()).ok_or_else(|| Error::InterpreterNotFound {
+            interpreter: self.cmdr.program().to_owned(),
+        })
     }
+}
 
-    /// Returns the executable path prefix for the Python interpreter as a string
+impl<C: Commander> PythonConfig<C> {
+    /// Create a `PythonConfig` that answers queries for a Python `version`
+    /// interpreter using `cmdr`
     ///
-    /// The path is dependent on the host OS and the installation path
-    /// of the Python interpreter. On macOS, the string may resemble something
-    /// like `/usr/local/opt/python/Frameworks/Python.framework/Versions/3.7`.
-    pub fn exec_prefix(&self) -> PyResult<String> {
-        self.script(&["print(getvar('exec_prefix'))"])
-    }
-
-    /// Like [`exec_prefix`](#method.exec_prefix), but
-    /// returns the executable prefix as a `PathBuf`.
-    pub fn exec_prefix_path(&self) -> PyResult<PathBuf> {
-        self.exec_prefix().map(PathBuf::from)
+    /// The commander receives the same arguments that we'd pass to the
+    /// interpreter. See [`Commander`](trait.Commander.html) for an example.
+    pub fn with_commander(version: Version, cmdr: C) -> Self {
+        PythonConfig {
+            cmdr,
+            ver: version,
+            info: None,
+            resolution: None,
+            embed: false,
+            caps: OnceLock::new(),
+        }
     }
 
-    /// Returns a list of paths that represent the include paths
-    /// for the distribution's headers. This is a space-delimited
-    /// string of paths prefixed with `-I`.
+    /// Returns what we learned about the interpreter when we created this
+    /// `PythonConfig`: its path, resolved path, version, implementation,
+    /// and pointer width
     ///
-    /// The single string may resemble something lke the following
-    /// (on macOS)
+    /// Only [`interpreter`](#method.interpreter), and the constructors
+    /// built on it, like [`from_venv`](#method.from_venv), run the
+    /// interpreter when they create a `PythonConfig`. For the others, like
+    /// [`new`](#method.new), this is `None`.
     ///
-    /// ```text
-    /// -I/usr/local/Cellar/python/3.7.4/Frameworks/Python.framework/Versions/3.7/include/python3.7m
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::interpreter("/usr/bin/python3").unwrap();
+    /// let info = cfg.interpreter_info().unwrap();
+    /// println!("{} ({}-bit)", info.realpath().display(), info.pointer_width());
     /// ```
+    pub fn interpreter_info(&self) -> Option<&InterpreterInfo> {
+        self.info.as_ref()
+    }
+
+    /// Returns the strategy that chose the interpreter, if this
+    /// `PythonConfig` came from [`resolve`](#method.resolve)
+    pub fn resolution(&self) -> Option<ResolutionStrategy> {
+        self.resolution
+    }
+
+    /// Returns `true` if [`libs`](#method.libs) and [`ldflags`](#method.ldflags)
+    /// answer for embedding Python, like `python3-config --embed`
     ///
-    /// Note that the same path may appear more than once.
-    pub fn includes(&self) -> PyResult<String> {
-        self.script(&[
-            "flags = ['-I' + sysconfig.get_path('include'), '-I' + sysconfig.get_path('platinclude')]",
-            "print(' '.join(flags))",
-        ])
+    /// See [`PythonConfigBuilder::embed`](struct.PythonConfigBuilder.html#method.embed).
+    pub fn is_embedding(&self) -> bool {
+        self.embed
     }
 
-    /// Returns a list of paths that represent the include paths
+    fn is_py3(&self) -> Result<(), Error> {
+        if self.ver.major() != 3 {
+            Err(Error::Python3Only)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the Python version string
+    ///
+    /// This is the raw return of `python --version`. Consider using
+    /// [`semantic_version`](struct.PythonConfig.html#method.semantic_version)
+    /// for something more useful.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// // Prints something like 'Python 3.7.4'
+    /// println!("{}", cfg.version_raw().unwrap());
+    /// ```
+    pub fn version_raw(&self) -> PyResult<String> {
+        self.ver.supported()?;
+        self.cmdr.commands(&["--version"]).map(normalize)
+    }
+
+    /// Returns the Python version as a semver
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// // Prints semver "3.7.4"
+    /// println!("{}", cfg.semantic_version().unwrap());
+    /// ```
+    pub fn semantic_version(&self) -> PyResult<semver::Version> {
+        self.version_raw().and_then(|resp| parse_version_raw(&resp))
+    }
+
+    /// Returns the Python version printed by `python --version`, parsed
+    /// leniently
+    ///
+    /// Unlike [`semantic_version`](#method.semantic_version), this keeps
+    /// suffixes like the `+` of a development build, and accepts versions
+    /// without a patch number.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// // Prints something like '3.13.0rc2'
+    /// println!("{}", cfg.python_version().unwrap());
+    /// ```
+    pub fn python_version(&self) -> PyResult<PythonVersion> {
+        self.version_raw()
+            .and_then(|resp| parse_python_version(&resp))
+    }
+
+    /// Returns the Python version as reported by `sys.version_info`
+    ///
+    /// Unlike [`semantic_version`](#method.semantic_version), the return
+    /// captures the release level and serial of pre-release interpreters.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// if cfg.version_info().unwrap().at_least(3, 8) {
+    ///     println!("Python 3.8 or newer");
+    /// }
+    /// ```
+    pub fn version_info(&self) -> PyResult<PyVersion> {
+        self.script(query::VERSION_INFO).and_then(|resp| {
+            pyversion::parse(&resp).ok_or_else(|| parse_err("expected sys.version_info", &resp))
+        })
+    }
+
+    /// Returns the path to the Python interpreter, as reported by
+    /// `sys.executable`
+    ///
+    /// This is the resolved interpreter, which may differ from the
+    /// name or path used to create this `PythonConfig`.
+    pub fn executable(&self) -> PyResult<String> {
+        self.script(query::EXECUTABLE)
+    }
+
+    /// Like [`executable`](#method.executable), but returns
+    /// the interpreter path as a `PathBuf`.
+    pub fn executable_path(&self) -> PyResult<PathBuf> {
+        self.executable().map(PathBuf::from)
+    }
+
+    /// Returns `args`, plus `--embed` in embed mode
+    fn embed_args<'a>(&self, args: &[&'a str]) -> Vec<&'a str> {
+        let mut args = args.to_vec();
+        if self.embed {
+            args.push("--embed");
+        }
+        args
+    }
+
+    fn script(&self, lines: &[&str]) -> PyResult<String> {
+        self.script_args(lines, &[])
+    }
+
+    /// Like `script`, but passes `args` to the script. The script
+    /// finds them in `sys.argv[1:]`.
+    fn script_args(&self, lines: &[&str], args: &[&str]) -> PyResult<String> {
+        self.ver.supported()?;
+        let script = build_script(lines);
+        let mut cmd = vec!["-c", &script];
+        cmd.extend_from_slice(args);
+        self.cmdr.commands(&cmd).map(unframe).map(normalize)
+    }
+
+    /// Returns the value of the `sysconfig` config var called `name`,
+    /// or `None` if the variable is not defined
+    ///
+    /// This is a window into any value returned by
+    /// `sysconfig.get_config_var`, for values that don't have a
+    /// dedicated method. Non-string values, like integers, are
+    /// returned in their string form.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// // Prints something like 'Some("3.11")'
+    /// println!("{:?}", cfg.config_var("LDVERSION").unwrap());
+    /// ```
+    pub fn config_var(&self, name: &str) -> PyResult<Option<String>> {
+        self.script_args(query::CONFIG_VAR, &[name])
+            .and_then(|resp| parse_config_var(&resp))
+    }
+
+    /// Runs the Python code `snippet` with the interpreter, returning what
+    /// it prints
+    ///
+    /// Use it for values that we don't have a method for. The snippet
+    /// runs after the prelude that every query shares, so `sysconfig`,
+    /// `os`, and `sys` are imported, and `getvar` is
+    /// `sysconfig.get_config_var`. Leading and trailing whitespace is
+    /// trimmed from the output. If the snippet raises an exception, the
+    /// error is an [`Error::Interpreter`](enum.Error.html#variant.Interpreter)
+    /// with the traceback.
+    ///
+    /// The snippet's lines must not be indented, except within blocks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// let numpy = cfg.run_python("import numpy\nprint(numpy.get_include())").unwrap();
+    /// println!("cargo:include={}", numpy);
+    /// ```
+    pub fn run_python(&self, snippet: &str) -> PyResult<String> {
+        self.script(&[snippet])
+    }
+
+    /// Like [`run_python`](#method.run_python), but parses what the
+    /// snippet prints as JSON
+    ///
+    /// The snippet should print a single JSON document, like the output
+    /// of `json.dumps`. Requires the `serde` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// let paths: Vec<String> = cfg
+    ///     .run_python_json("import json\nprint(json.dumps(sys.path))")
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn run_python_json<T: serde::de::DeserializeOwned>(&self, snippet: &str) -> PyResult<T> {
+        let resp = self.run_python(snippet)?;
+        serde_json::from_str(&resp).map_err(|_| parse_err("expected a JSON document", &resp))
+    }
+
+    /// Returns the `sysconfig` installation path called `name`, like
+    /// `purelib`, `platlib`, `include`, or `scripts`
+    ///
+    /// This fails if `name` is not a valid path name, or if the scheme
+    /// doesn't define it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// println!("{}", cfg.sysconfig_path("purelib").unwrap().display());
+    /// ```
+    pub fn sysconfig_path(&self, name: &str) -> PyResult<PathBuf> {
+        self.scheme_path(name).map(PathBuf::from)
+    }
+
+    /// Like [`sysconfig_path`](#method.sysconfig_path), but returns `None`
+    /// if the scheme doesn't define the path
+    pub fn try_sysconfig_path(&self, name: &str) -> PyResult<Option<PathBuf>> {
+        self.try_scheme_path(name)
+            .map(|path| path.map(PathBuf::from))
+    }
+
+    fn try_scheme_path(&self, name: &str) -> PyResult<Option<String>> {
+        self.script_args(
+            &["import sys", "print(sysconfig.get_path(sys.argv[1]))"],
+            &[name],
+        )
+        .map(parse_value)
+    }
+
+    fn scheme_path(&self, name: &str) -> PyResult<String> {
+        self.try_scheme_path(name)?.ok_or_else(|| Error::Undefined {
+            what: format!("the sysconfig path '{}'", name),
+        })
+    }
+
+    /// Returns the directory for pure Python packages, the
+    /// `site-packages` directory on most systems
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// println!("{}", cfg.purelib().unwrap());
+    /// ```
+    pub fn purelib(&self) -> PyResult<String> {
+        self.scheme_path("purelib")
+    }
+
+    /// Like [`purelib`](#method.purelib), but returns a `PathBuf`.
+    pub fn purelib_path(&self) -> PyResult<PathBuf> {
+        self.purelib().map(PathBuf::from)
+    }
+
+    /// Returns the directory for platform-specific packages, like those
+    /// with extension modules
+    ///
+    /// This is often the same as [`purelib`](#method.purelib). Some
+    /// distributions keep them apart, like Fedora's `lib` and `lib64`.
+    pub fn platlib(&self) -> PyResult<String> {
+        self.scheme_path("platlib")
+    }
+
+    /// Like [`platlib`](#method.platlib), but returns a `PathBuf`.
+    pub fn platlib_path(&self) -> PyResult<PathBuf> {
+        self.platlib().map(PathBuf::from)
+    }
+
+    /// Returns the directory where packages install their scripts,
+    /// like `bin` on Unix or `Scripts` on Windows
+    pub fn scripts_dir(&self) -> PyResult<String> {
+        self.scheme_path("scripts")
+    }
+
+    /// Like [`scripts_dir`](#method.scripts_dir), but returns a `PathBuf`.
+    pub fn scripts_dir_path(&self) -> PyResult<PathBuf> {
+        self.scripts_dir().map(PathBuf::from)
+    }
+
+    /// Returns the directory where packages install their data files,
+    /// which is usually the installation prefix
+    pub fn data_dir(&self) -> PyResult<String> {
+        self.scheme_path("data")
+    }
+
+    /// Like [`data_dir`](#method.data_dir), but returns a `PathBuf`.
+    pub fn data_dir_path(&self) -> PyResult<PathBuf> {
+        self.data_dir().map(PathBuf::from)
+    }
+
+    /// Returns the directory of the pure Python standard library, like
+    /// `/usr/lib/python3.11`
+    ///
+    /// If you bundle the interpreter with your application, copy this
+    /// directory, and point `PYTHONHOME`, or `PYTHONPATH`, at the copy.
+    pub fn stdlib_dir(&self) -> PyResult<PathBuf> {
+        self.sysconfig_path("stdlib")
+    }
+
+    /// Returns the directory of the platform-specific standard library,
+    /// which holds `lib-dynload`, the standard library's extension modules
+    ///
+    /// This is often the same as [`stdlib_dir`](#method.stdlib_dir). Some
+    /// distributions keep them apart, like Fedora's `lib` and `lib64`.
+    pub fn platstdlib_dir(&self) -> PyResult<PathBuf> {
+        self.sysconfig_path("platstdlib")
+    }
+
+    /// Returns the directories that the interpreter searches for modules,
+    /// from `sys.path`, in order
+    ///
+    /// This is the search path of the interpreter as we run it, so it
+    /// includes `PYTHONPATH`, and the packages of an active virtual
+    /// environment. It doesn't include the current directory, which
+    /// `python -c` adds.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    /// use std::env;
+    ///
+    /// // Give an embedded interpreter the same search path
+    /// let path = PythonConfig::new().sys_path().unwrap();
+    /// let pythonpath = env::join_paths(path).unwrap();
+    /// println!("cargo:rustc-env=EMBEDDED_PYTHONPATH={}", pythonpath.to_string_lossy());
+    /// ```
+    pub fn sys_path(&self) -> PyResult<Vec<PathBuf>> {
+        let resp = self.script(query::SYS_PATH)?;
+        Ok(resp.lines().map(PathBuf::from).collect())
+    }
+
+    /// Returns the installation prefix of the Python interpreter as a string.
+    ///
+    /// The prefix is dependent on the host operating system.
+    /// On macOS, depending on how Python is installed, it will return
+    /// a string resembling
+    /// `/usr/local/opt/python/Frameworks/Python.framework/Versions/3.7`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// println!("{}", cfg.prefix().unwrap());
+    /// ```
+    pub fn prefix(&self) -> PyResult<String> {
+        defined(self.script(query::PREFIX)?, "the config var prefix")
+    }
+
+    /// Like [`prefix`](#method.prefix), but returns `None` if the
+    /// distribution doesn't define the `prefix` config var
+    pub fn try_prefix(&self) -> PyResult<Option<String>> {
+        self.script(query::PREFIX).map(parse_value)
+    }
+
+    /// Like [`prefix`](#method.prefix), but returns
+    /// the installation prefix as a `PathBuf`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// println!("{}", cfg.prefix_path().unwrap().display());
+    /// ```
+    pub fn prefix_path(&self) -> PyResult<PathBuf> {
+        self.prefix().map(PathBuf::from)
+    }
+
+    /// Returns the prefix of the base Python installation
+    ///
+    /// Outside of a virtual environment, this is the same as `sys.prefix`.
+    /// Inside of a virtual environment, this is the installation that
+    /// created the environment, which is where the headers and libraries
+    /// live.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::from_active_venv().unwrap();
+    /// println!("{}", cfg.base_prefix().unwrap());
+    /// ```
+    pub fn base_prefix(&self) -> PyResult<String> {
+        self.script(query::BASE_PREFIX)
+    }
+
+    /// Like [`base_prefix`](#method.base_prefix), but returns
+    /// the base prefix as a `PathBuf`.
+    pub fn base_prefix_path(&self) -> PyResult<PathBuf> {
+        self.base_prefix().map(PathBuf::from)
+    }
+
+    /// Returns `true` if the interpreter is running in a virtual environment,
+    /// created by `venv` or `virtualenv`
+    pub fn is_virtualenv(&self) -> PyResult<bool> {
+        self.script(query::IS_VIRTUALENV)
+            .and_then(|resp| parse_bool(&resp))
+    }
+
+    /// Returns the executable path prefix for the Python interpreter as a string
+    ///
+    /// The path is dependent on the host OS and the installation path
+    /// of the Python interpreter. On macOS, the string may resemble something
+    /// like `/usr/local/opt/python/Frameworks/Python.framework/Versions/3.7`.
+    pub fn exec_prefix(&self) -> PyResult<String> {
+        defined(
+            self.script(query::EXEC_PREFIX)?,
+            "the config var exec_prefix",
+        )
+    }
+
+    /// Like [`exec_prefix`](#method.exec_prefix), but returns `None` if
+    /// the distribution doesn't define the `exec_prefix` config var
+    pub fn try_exec_prefix(&self) -> PyResult<Option<String>> {
+        self.script(query::EXEC_PREFIX).map(parse_value)
+    }
+
+    /// Like [`exec_prefix`](#method.exec_prefix), but
+    /// returns the executable prefix as a `PathBuf`.
+    pub fn exec_prefix_path(&self) -> PyResult<PathBuf> {
+        self.exec_prefix().map(PathBuf::from)
+    }
+
+    /// Returns a list of paths that represent the include paths
+    /// for the distribution's headers. This is a space-delimited
+    /// string of paths prefixed with `-I`.
+    ///
+    /// The single string may resemble something lke the following
+    /// (on macOS)
+    ///
+    /// ```text
+    /// -I/usr/local/Cellar/python/3.7.4/Frameworks/Python.framework/Versions/3.7/include/python3.7m
+    /// ```
+    ///
+    /// Note that the same path may appear more than once.
+    pub fn includes(&self) -> PyResult<String> {
+        self.script(query::INCLUDES)
+    }
+
+    /// Returns a list of paths that represent the include paths
     /// for the distribution's headers. Unlike [`includes`](#method.includes),
     /// this is simply a collection of paths. Note that the same
     /// path may appear more than once.
     pub fn include_paths(&self) -> PyResult<Vec<PathBuf>> {
-        self.script(&[
-            "print(sysconfig.get_path('include'))",
-            "print(sysconfig.get_path('platinclude'))",
-        ])
-        .map(|resp| resp.lines().map(PathBuf::from).collect())
+        self.script(query::INCLUDE_PATHS)
+            .map(|resp| resp.lines().map(PathBuf::from).collect())
+    }
+
+    /// Like [`include_paths`](#method.include_paths), but each path
+    /// appears once
+    ///
+    /// Most distributions use the same directory for `include` and
+    /// `platinclude`. The paths are in the same order as `include_paths`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// for dir in PythonConfig::new().include_paths_dedup().unwrap() {
+    ///     println!("-I{}", dir.display());
+    /// }
+    /// ```
+    pub fn include_paths_dedup(&self) -> PyResult<Vec<PathBuf>> {
+        self.include_paths().map(dedup_paths)
+    }
+
+    /// Like [`include_paths_dedup`](#method.include_paths_dedup), but
+    /// resolves symbolic links first, so that two names for the same
+    /// directory appear once
+    ///
+    /// Paths that don't exist are returned as they are.
+    pub fn include_paths_canonical(&self) -> PyResult<Vec<PathBuf>> {
+        self.include_paths().map(|paths| {
+            dedup_paths(
+                paths
+                    .into_iter()
+                    .map(|path| fs::canonicalize(&path).unwrap_or(path))
+                    .collect(),
+            )
+        })
+    }
+
+    /// Returns the directory that holds `Python.h`, the `include` path of
+    /// [`include_paths`](#method.include_paths)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let include = PythonConfig::new().include_dir().unwrap();
+    /// assert!(include.join("Python.h").exists());
+    /// ```
+    pub fn include_dir(&self) -> PyResult<PathBuf> {
+        self.sysconfig_path("include")
+    }
+
+    /// All the flags useful for C compilation. This includes the include
+    /// paths (see [`includes`](#method.includes)) as well as other compiler
+    /// flags for this target. The return is a string with spaces separating
+    /// the flags.
+    pub fn cflags(&self) -> PyResult<String> {
+        self.script(query::CFLAGS)
+    }
+
+    /// Like [`cflags`](#method.cflags), but returns each flag separately
+    ///
+    /// Include paths that contain spaces stay whole, and flags in the
+    /// config vars are split like a shell would split them, so a quoted
+    /// flag, like `'-DNAME="a b"'`, is one flag. Pass the flags to a
+    /// compiler as they are, without splitting them again.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    /// use std::process::Command;
+    ///
+    /// let flags = PythonConfig::new().cflags_split().unwrap();
+    /// Command::new("cc").args(&flags).arg("-c").arg("extension.c");
+    /// ```
+    pub fn cflags_split(&self) -> PyResult<Vec<String>> {
+        self.script_args(query::CFLAGS, &["--lines"])
+            .map(|resp| split_lines(&resp))
+    }
+
+    /// Returns linker flags required for linking this Python
+    /// distribution. All libraries / frameworks have the appropriate `-l`
+    /// or `-framework` prefixes.
+    ///
+    /// On macOS, the single string may resemble something like
+    ///
+    /// ```text
+    /// -lpython3.7m -ldl -framework CoreFoundation
+    /// ```
+    ///
+    /// Like `python3-config --libs`, the flags only include `-lpython`
+    /// before Python 3.8. Starting with 3.8, extension modules are not
+    /// linked against libpython. If you're embedding Python, use
+    /// [`libs_embed`](#method.libs_embed).
+    ///
+    /// On Windows, extensions always link against the import library. When
+    /// targeting MSVC, the return is the library's file name, like
+    /// `python311.lib`. When targeting MinGW, the return resembles
+    /// `-lpython311`.
+    ///
+    /// PyPy and GraalPy name their libraries differently. For PyPy, the
+    /// library resembles `-lpypy3.10-c`, and for GraalPy, it's
+    /// `-lpython-native`.
+    ///
+    /// If this `PythonConfig` was built in [embed mode](struct.PythonConfigBuilder.html#method.embed),
+    /// this is the same as [`libs_embed`](#method.libs_embed).
+    pub fn libs(&self) -> PyResult<String> {
+        self.script_args(query::LIBS, &self.embed_args(&[]))
+    }
+
+    /// Returns linker flags required for embedding this Python
+    /// distribution in an application, like `python3-config --libs --embed`
+    ///
+    /// Unlike [`libs`](#method.libs), this always includes `-lpython`.
+    /// Before Python 3.8, the return is the same as `libs`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// println!("{}", cfg.libs_embed().unwrap());
+    /// ```
+    pub fn libs_embed(&self) -> PyResult<String> {
+        self.script_args(query::LIBS, &["--embed"])
+    }
+
+    /// Returns linker flags for linking an extension module that targets
+    /// the stable ABI, like `-lpython3 -ldl`
+    ///
+    /// Instead of the versioned library, like `-lpython3.11`, this links
+    /// `python3`, which works with every Python 3 that supports the
+    /// limited API. On Windows, the library is `python3.lib`. The other
+    /// flags are the same as [`libs`](#method.libs).
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    pub fn libs_abi3(&self) -> Py3Only<String> {
+        self.is_py3()?;
+        self.require(Capability::LimitedApi)?;
+        self.script_args(query::LIBS, &["--abi3"])
+    }
+
+    /// Returns linker flags required for creating
+    /// a shared library for this Python distribution. All libraries / frameworks
+    /// have the appropriate `-L`, `-l`, or `-framework` prefixes.
+    ///
+    /// On macOS, the single string may resemble something like
+    ///
+    /// ```text
+    /// -L/usr/local/opt/python/lib/python3.7/config-3.7m-darwin -lpython3.7m -ldl -framework CoreFoundation
+    /// ```
+    ///
+    /// Like [`libs`](#method.libs), the flags only include `-lpython` before
+    /// Python 3.8. If you're embedding Python, use
+    /// [`ldflags_embed`](#method.ldflags_embed).
+    ///
+    /// Framework builds, like the python.org installers and Homebrew, link
+    /// libpython as a framework instead, with flags like
+    /// `-F/Library/Frameworks -framework Python`. See
+    /// [`python_framework`](#method.python_framework). For `-L` and `-l`
+    /// flags, use [`ldflags_for`](#method.ldflags_for).
+    ///
+    /// On Windows, the return adds the `libs` directory of the base
+    /// installation to the search path, using `/LIBPATH:` for MSVC
+    /// or `-L` for MinGW.
+    ///
+    /// For PyPy, the return also adds the directory that holds `libpypy`
+    /// to the search path.
+    ///
+    /// If this `PythonConfig` was built in [embed mode](struct.PythonConfigBuilder.html#method.embed),
+    /// this is the same as [`ldflags_embed`](#method.ldflags_embed), and so
+    /// are [`ldflags_split`](#method.ldflags_split) and
+    /// [`link_args`](#method.link_args).
+    pub fn ldflags(&self) -> PyResult<String> {
+        self.script_args(query::LDFLAGS, &self.embed_args(&[]))
+    }
+
+    /// Like [`ldflags`](#method.ldflags), but returns each flag separately
+    ///
+    /// Paths that contain spaces stay whole, and flags in the config vars
+    /// are split like a shell would split them. A flag that takes an
+    /// argument, like `-framework CoreFoundation`, is two elements, in
+    /// order, just as a shell would pass them to the linker.
+    ///
+    /// To sort the flags by kind, use [`link_args`](#method.link_args).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// // build.rs
+    /// use python_config::PythonConfig;
+    ///
+    /// for flag in PythonConfig::new().ldflags_split().unwrap() {
+    ///     println!("cargo:rustc-link-arg={}", flag);
+    /// }
+    /// ```
+    pub fn ldflags_split(&self) -> PyResult<Vec<String>> {
+        self.script_args(query::LDFLAGS, &self.embed_args(&["--lines"]))
+            .map(|resp| split_lines(&resp))
+    }
+
+    /// Like [`ldflags`](#method.ldflags), but sorts the flags into library
+    /// directories, libraries, frameworks, and other flags
+    ///
+    /// Paths that contain spaces are kept whole.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let link = PythonConfig::new().link_args().unwrap();
+    /// for dir in &link.lib_dirs {
+    ///     println!("cargo:rustc-link-search=native={}", dir.display());
+    /// }
+    /// ```
+    pub fn link_args(&self) -> PyResult<LinkArgs> {
+        self.script_args(query::LDFLAGS, &self.embed_args(&["--lines"]))
+            .map(|resp| link::parse(resp.lines()))
+    }
+
+    /// Like [`link_args`](#method.link_args), but for embedding Python, like
+    /// [`ldflags_embed`](#method.ldflags_embed)
+    pub fn link_args_embed(&self) -> PyResult<LinkArgs> {
+        self.script_args(query::LDFLAGS, &["--lines", "--embed"])
+            .map(|resp| link::parse(resp.lines()))
+    }
+
+    /// Writes the [`link_args`](#method.link_args) to a linker response
+    /// file at `path`, in `format`
+    ///
+    /// Pass the file to the linker as `@path`, which keeps long command
+    /// lines, and paths with spaces, intact. See
+    /// [`LinkArgs::to_response_file`](struct.LinkArgs.html#method.to_response_file).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::{PythonConfig, ResponseFileFormat};
+    /// use std::env;
+    /// use std::path::PathBuf;
+    ///
+    /// // build.rs
+    /// let rsp = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("python.rsp");
+    /// PythonConfig::new()
+    ///     .write_link_args_file(&rsp, ResponseFileFormat::MsvcRsp)
+    ///     .unwrap();
+    /// println!("cargo:rustc-link-arg=@{}", rsp.display());
+    /// ```
+    pub fn write_link_args_file<P: AsRef<path::Path>>(
+        &self,
+        path: P,
+        format: ResponseFileFormat,
+    ) -> PyResult<()> {
+        let file = self.link_args()?.to_response_file(format);
+        fs::write(path, file).map_err(Error::from)
+    }
+
+    /// Returns the symbols that the extension module named `module`, like
+    /// `spam` or `package.spam`, must export, and the linker flags that
+    /// make a loadable module out of it
+    ///
+    /// Python 3 finds the module's `PyInit_<module>` function; Python 2
+    /// finds `init<module>`. On macOS, the flags are
+    /// `-undefined dynamic_lookup`, so that the module finds libpython's
+    /// symbols in the interpreter. When we're built for MSVC, the flags
+    /// export the symbol with `/EXPORT:`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// // build.rs
+    /// let exports = PythonConfig::new().exported_symbols("spam").unwrap();
+    /// for arg in exports.link_args {
+    ///     println!("cargo:rustc-cdylib-link-arg={}", arg);
+    /// }
+    /// ```
+    pub fn exported_symbols(&self, module: &str) -> PyResult<ExportedSymbols> {
+        self.require(Capability::ExtensionModules)?;
+        let resp = self.script_args(query::EXPORTS, &[module])?;
+        let mut lines = resp.lines().map(String::from);
+        match lines.next() {
+            Some(symbol) if !symbol.is_empty() => Ok(ExportedSymbols {
+                symbols: vec![symbol],
+                link_args: lines.collect(),
+            }),
+            _ => Err(parse_err("expected an exported symbol", &resp)),
+        }
+    }
+
+    /// Returns linker flags required for embedding this Python distribution
+    /// in an application, like `python3-config --ldflags --embed`
+    ///
+    /// Unlike [`ldflags`](#method.ldflags), this always links libpython,
+    /// with `-lpython` or, for framework builds, `-framework Python`.
+    /// Before Python 3.8, the return is the same as `ldflags`.
+    pub fn ldflags_embed(&self) -> PyResult<String> {
+        self.script_args(query::LDFLAGS, &["--embed"])
+    }
+
+    /// Like [`ldflags_embed`](#method.ldflags_embed), but also adds
+    /// `LIBDIR` to the runtime library search path with `-Wl,-rpath,<LIBDIR>`
+    ///
+    /// An application linked with these flags finds a shared libpython
+    /// at runtime, without setting `LD_LIBRARY_PATH`. The return is the
+    /// same as `ldflags_embed` when there's nothing to add: on Windows,
+    /// for a statically linked libpython, and for macOS framework builds.
+    /// Conda environments are already on the rpath.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// println!("cc main.c {}", cfg.ldflags_with_rpath().unwrap());
+    /// ```
+    pub fn ldflags_with_rpath(&self) -> PyResult<String> {
+        self.script_args(query::LDFLAGS, &["--embed", "--rpath"])
+    }
+
+    /// Returns linker flags for embedding this Python distribution in an
+    /// application, linking libpython as `mode` describes
+    ///
+    /// [`ldflags_embed`](#method.ldflags_embed) links libpython the way the
+    /// distribution was built; see [`is_shared`](#method.is_shared). Use
+    /// this method to choose. The flags don't link a macOS framework.
+    /// `LinkMode::Static` links `libpythonX.Y.a` in `LIBPL` by its path,
+    /// since `-lpythonX.Y` would link the shared library where both are
+    /// installed, and adds `LINKFORSHARED` so that extension modules can
+    /// find libpython's symbols in the executable. A framework build's
+    /// archive only links to the framework, so there, `LinkMode::Static`
+    /// keeps `-lpythonX.Y`, like `python3-config`. `LinkMode::Shared`
+    /// searches `LIBDIR`, and links `-lpythonX.Y`.
+    ///
+    /// Not every distribution installs both libraries. Windows
+    /// distributions only have a DLL, so the mode doesn't change the flags.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::{LinkMode, PythonConfig};
+    ///
+    /// let cfg = PythonConfig::new();
+    /// let flags = cfg.ldflags_for(LinkMode::Static).unwrap();
+    /// println!("cc main.c {}", flags);
+    /// ```
+    pub fn ldflags_for(&self, mode: LinkMode) -> PyResult<String> {
+        if mode == LinkMode::Static {
+            self.require(Capability::ConfigDir)?;
+        }
+        self.script_args(query::LDFLAGS, &["--embed", mode.script_arg()])
     }
 
-    /// All the flags useful for C compilation. This includes the include
-    /// paths (see [`includes`](#method.includes)) as well as other compiler
-    /// flags for this target. The return is a string with spaces separating
-    /// the flags.
-    pub fn cflags(&self) -> PyResult<String> {
-        self.script(&[
-            "flags = ['-I' + sysconfig.get_path('include'), '-I' + sysconfig.get_path('platinclude')]",
-            linux_line!("flags.extend(getvar('BASECFLAGS').split())"),
-            linux_line!("flags.extend(getvar('CONFIGURE_CFLAGS').split())"),
-            macos_line!("flags.extend(getvar('CFLAGS').split())"),
-            "print(' '.join(flags))",
-        ])
+    /// Returns the framework that holds libpython, if this is a macOS
+    /// framework build
+    ///
+    /// The python.org installers and Homebrew are framework builds. The
+    /// return is `None` for other builds, and on other platforms.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// // build.rs
+    /// if let Some(framework) = PythonConfig::new().python_framework().unwrap() {
+    ///     println!("cargo:rustc-link-search=framework={}", framework.prefix.display());
+    ///     println!("cargo:rustc-link-lib=framework={}", framework.name);
+    /// }
+    /// ```
+    pub fn python_framework(&self) -> PyResult<Option<Framework>> {
+        let resp = self.script(query::FRAMEWORK)?;
+        if resp.is_empty() {
+            return Ok(None);
+        }
+        match resp.split_once('\n') {
+            Some((name, prefix)) => Ok(Some(Framework {
+                name: name.to_owned(),
+                prefix: PathBuf::from(prefix),
+            })),
+            None => Err(parse_err("expected a framework name and prefix", &resp)),
+        }
     }
 
-    /// Returns linker flags required for linking this Python
-    /// distribution. All libraries / frameworks have the appropriate `-l`
-    /// or `-framework` prefixes.
+    /// Returns `true` if the interpreter belongs to a conda environment
     ///
-    /// On macOS, the single string may resemble something like
+    /// The environment's prefix holds a `conda-meta` directory, or it's
+    /// the active environment named by `CONDA_PREFIX`. When linking
+    /// libpython from a conda environment, [`ldflags_embed`](#method.ldflags_embed)
+    /// searches the environment's `lib` directory, and adds it to the rpath.
+    pub fn is_conda(&self) -> PyResult<bool> {
+        self.script(query::IS_CONDA)
+            .and_then(|resp| parse_bool(&resp))
+    }
+
+    /// Returns `true` if this Python distribution has a shared libpython,
+    /// as `Py_ENABLE_SHARED` says
     ///
-    /// ```text
-    /// -lpython3.7m -ldl -framework CoreFoundation
+    /// When this is `false`, libpython is statically linked into the
+    /// interpreter. Conda distributions are the exception: they ship a
+    /// shared library, even though `Py_ENABLE_SHARED` is unset. Windows
+    /// and PyPy distributions are always shared.
+    pub fn is_shared(&self) -> PyResult<bool> {
+        self.script(query::IS_SHARED)
+            .and_then(|resp| parse_bool(&resp))
+    }
+
+    /// Returns `true` if this Python distribution was configured with
+    /// `--enable-optimizations`, meaning it was built with profile guided
+    /// optimizations.
+    ///
+    /// A non-optimized interpreter can be noticeably slower than a
+    /// distribution's release build, which may skew benchmarks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// if !cfg.is_optimized_build().unwrap() {
+    ///     eprintln!("warning: profiling against a non-optimized Python");
+    /// }
     /// ```
-    pub fn libs(&self) -> PyResult<String> {
+    pub fn is_optimized_build(&self) -> PyResult<bool> {
         self.script(&[
-            "import sys",
-            "libs = ['-lpython' + pyver + sys.abiflags]",
-            "libs += getvar('LIBS').split()",
-            "libs += getvar('SYSLIBS').split()",
-            "print(' '.join(libs))",
+            "args = getvar('CONFIG_ARGS') or ''",
+            "nodist = getvar('PY_CFLAGS_NODIST') or ''",
+            "print('--enable-optimizations' in args or '-fprofile-use' in nodist)",
         ])
+        .and_then(|resp| parse_bool(&resp))
     }
 
-    /// Returns linker flags required for creating
-    /// a shared library for this Python distribution. All libraries / frameworks
-    /// have the appropriate `-L`, `-l`, or `-framework` prefixes.
+    /// Returns `true` if this is a free-threaded build of Python, which
+    /// runs without the global interpreter lock
     ///
-    /// On macOS, the single string may resemble something like
+    /// Free-threaded builds, available starting with Python 3.13, have
+    /// their own ABI. The libraries and extension modules carry a `t`,
+    /// like `libpython3.13t` and `.cpython-313t-x86_64-linux-gnu.so`,
+    /// which [`libs`](#method.libs) and
+    /// [`extension_suffix`](#method.extension_suffix) account for.
     ///
-    /// ```text
-    /// -L/usr/local/opt/python/Frameworks/Python.framework/Versions/3.7/lib/python3.7/config-3.7m-darwin -lpython3.7m -ldl -framework CoreFoundation
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// // build.rs
+    /// if PythonConfig::new().gil_disabled().unwrap() {
+    ///     println!("cargo:rustc-cfg=Py_GIL_DISABLED");
+    /// }
     /// ```
-    pub fn ldflags(&self) -> PyResult<String> {
+    pub fn gil_disabled(&self) -> PyResult<bool> {
+        self.script(query::GIL_DISABLED)
+            .and_then(|resp| parse_bool(&resp))
+    }
+
+    /// Returns `true` if this is a debug build of Python, configured with
+    /// `--with-pydebug`
+    ///
+    /// Debug builds have a different ABI, so extension modules must be
+    /// built for them specifically. The libraries carry a `d` on Unix,
+    /// and a `_d` on Windows.
+    pub fn is_debug_build(&self) -> PyResult<bool> {
+        self.script(query::IS_DEBUG_BUILD)
+            .and_then(|resp| parse_bool(&resp))
+    }
+
+    /// Returns the compiler and linker that built this Python
+    /// distribution, like `CC` and `LDSHARED`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let toolchain = PythonConfig::new().build_toolchain().unwrap();
+    /// if let Some(cc) = toolchain.cc {
+    ///     println!("Python was built with {}", cc);
+    /// }
+    /// ```
+    pub fn build_toolchain(&self) -> PyResult<BuildToolchain> {
+        self.script_args(query::BUILD_TOOLCHAIN, toolchain::CONFIG_VARS)
+            .map(|resp| toolchain::parse(&resp))
+    }
+
+    /// Returns the build options that change the ABI, like `Py_DEBUG`
+    /// and `Py_TRACE_REFS`
+    ///
+    /// See [`BuildFlags`](struct.BuildFlags.html) to emit them as
+    /// `cargo:rustc-cfg` values.
+    pub fn build_flags(&self) -> PyResult<BuildFlags> {
+        self.script(query::BUILD_FLAGS)
+            .map(|resp| buildflags::parse(&resp))
+    }
+
+    /// Returns the sanitizers that instrumented this Python distribution,
+    /// as found in the `-fsanitize=` flags of `CONFIGURE_CFLAGS` and `PY_CFLAGS`.
+    ///
+    /// The return is empty for a typical, uninstrumented build.
+    pub fn sanitizers(&self) -> PyResult<Vec<Sanitizer>> {
         self.script(&[
-            "import sys",
-            "libs = ['-lpython' + pyver + sys.abiflags]",
-            linux_line!["libs.insert(0, '-L' + getvar('exec_prefix') + '/lib')"],
-            "libs += getvar('LIBS').split()",
-            "libs += getvar('SYSLIBS').split()",
-            "if not getvar('Py_ENABLED_SHARED'):",
-            tab!("libs.insert(0, '-L' + getvar('LIBPL'))"),
-            "if not getvar('PYTHONFRAMEWORK'):",
-            tab!("libs.extend(getvar('LINKFORSHARED').split())"),
-            "print(' '.join(libs))",
+            "print(getvar('CONFIGURE_CFLAGS') or '')",
+            "print(getvar('PY_CFLAGS') or '')",
         ])
+        .map(|resp| sanitizer::parse(&resp))
+    }
+
+    /// Makes a best guess at how this Python distribution was installed:
+    /// by the OS package manager, Homebrew, a python.org installer, conda,
+    /// pyenv, or from source.
+    ///
+    /// The guess is based on the installation prefix, the interpreter path,
+    /// and the arguments used to configure the build. When running in a
+    /// virtual environment, we classify the base installation.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::{InstallOrigin, PythonConfig};
+    ///
+    /// let cfg = PythonConfig::new();
+    /// if cfg.install_origin().unwrap() == InstallOrigin::Homebrew {
+    ///     println!("Python from Homebrew");
+    /// }
+    /// ```
+    pub fn install_origin(&self) -> PyResult<InstallOrigin> {
+        let resp = self.script(&[
+            "import os, sys",
+            "prefix = getattr(sys, 'base_prefix', getattr(sys, 'real_prefix', sys.prefix))",
+            "print(prefix)",
+            "print(sys.executable)",
+            "print(getvar('CONFIG_ARGS') or '')",
+            "print(os.path.isdir(os.path.join(prefix, 'conda-meta')))",
+        ])?;
+        let lines: Vec<&str> = resp.lines().collect();
+        if let [prefix, executable, config_args, has_conda_meta] = lines[..] {
+            Ok(origin::classify(&origin::Probe {
+                prefix,
+                executable,
+                config_args,
+                has_conda_meta: parse_bool(has_conda_meta)?,
+            }))
+        } else {
+            Err(parse_err(
+                "expected four lines describing the installation",
+                &resp,
+            ))
+        }
+    }
+
+    /// Checks that the distribution's headers (`Python.h`) and `libpython`
+    /// are installed, returning a hint describing what to install if
+    /// either is missing. Returns `None` if everything is in place.
+    ///
+    /// For a report of every check, use [`diagnose`](#method.diagnose).
+    ///
+    /// The hint is specific to how Python was installed (see
+    /// [`install_origin`](#method.install_origin)). For OS packages, we name
+    /// the exact package for the host's distribution, like `python3.11-dev`
+    /// on Debian or `python3.11-devel` on Fedora.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// if let Some(hint) = cfg.missing_headers_hint().unwrap() {
+    ///     eprintln!("{}", hint);
+    /// }
+    /// ```
+    pub fn missing_headers_hint(&self) -> PyResult<Option<String>> {
+        let files = self.dev_files()?;
+        let missing = if !files.has_headers {
+            format!("Python.h not found in {}", files.include)
+        } else if !files.has_libpython {
+            format!("libpython for Python {} not found", files.version)
+        } else {
+            return Ok(None);
+        };
+
+        let os_release = diagnose::os_release();
+        let hint = origin::install_hint(
+            self.install_origin()?,
+            os_release.as_deref(),
+            &files.version,
+        );
+        Ok(Some(format!("{}; {}", missing, hint)))
     }
 
     /// Returns a string that represents the file extension for this distribution's library
@@ -407,8 +2138,200 @@ impl PythonConfig {
     /// On macOS, the string may resemble something like `.cpython-37m-darwin.so`.
     pub fn extension_suffix(&self) -> Py3Only<String> {
         self.is_py3()?;
-        let resp = self.script(&["print(getvar('EXT_SUFFIX'))"])?;
-        Ok(resp)
+        self.require(Capability::ExtensionModules)?;
+        defined(
+            self.script(query::EXTENSION_SUFFIX)?,
+            "the config var EXT_SUFFIX",
+        )
+    }
+
+    /// Like [`extension_suffix`](#method.extension_suffix), but returns
+    /// `None` if the distribution doesn't define `EXT_SUFFIX`, or `SO`
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    pub fn try_extension_suffix(&self) -> Py3Only<Option<String>> {
+        self.is_py3()?;
+        self.script(query::EXTENSION_SUFFIX).map(parse_value)
+    }
+
+    /// Returns the file extension for extension modules that target the
+    /// stable ABI, like `.abi3.so`
+    ///
+    /// On Windows, the return is `.pyd`.
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    pub fn extension_suffix_abi3(&self) -> Py3Only<String> {
+        self.is_py3()?;
+        self.require(Capability::ExtensionModules)?;
+        self.require(Capability::LimitedApi)?;
+        self.script(query::ABI3_EXTENSION_SUFFIX)
+    }
+
+    /// Returns `true` if this interpreter can load an extension module built
+    /// for the limited API of Python `min`, like `(3, 8)`
+    ///
+    /// The interpreter must be CPython, at least version `min`, and not a
+    /// free-threaded build. The limited API was introduced in Python 3.2,
+    /// so an older `min` is treated as `(3, 2)`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// // build.rs
+    /// let cfg = PythonConfig::new();
+    /// if cfg.supports_limited_api((3, 8)).unwrap() {
+    ///     println!("cargo:rustc-cfg=Py_LIMITED_API");
+    /// }
+    /// ```
+    pub fn supports_limited_api(&self, min: (u8, u8)) -> Py3Only<bool> {
+        self.is_py3()?;
+        let (major, minor) = min.max((3, 2));
+        self.script_args(
+            query::SUPPORTS_LIMITED_API,
+            &[&major.to_string(), &minor.to_string()],
+        )
+        .and_then(|resp| parse_bool(&resp))
+    }
+
+    /// Returns the path to the libpython shared library, like
+    /// `libpython3.11.so`, `libpython3.11.dylib`, or `python311.dll`
+    ///
+    /// The path exists on disk. Use it to `dlopen` libpython at runtime.
+    /// Returns an error if the interpreter doesn't have a shared library,
+    /// which is the case when libpython is statically linked into the
+    /// interpreter.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// let libpython = cfg.libpython_path().unwrap();
+    /// println!("cargo:rustc-env=LIBPYTHON={}", libpython.display());
+    /// ```
+    pub fn libpython_path(&self) -> PyResult<PathBuf> {
+        self.require(Capability::Embedding)?;
+        let resp = self.script(query::LIBPYTHON_PATH)?;
+        if resp.is_empty() {
+            Err(other_err(
+                "the interpreter does not have a shared libpython",
+            ))
+        } else {
+            Ok(PathBuf::from(resp))
+        }
+    }
+
+    /// Returns the directory that holds libpython, from the `LIBDIR`
+    /// config var, like `/usr/lib`
+    ///
+    /// Windows distributions don't define `LIBDIR`, so this fails on
+    /// Windows. Also see [`libpython_path`](#method.libpython_path).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // build.rs
+    /// use python_config::PythonConfig;
+    ///
+    /// # if cfg!(windows) { return; }
+    /// let cfg = PythonConfig::new();
+    /// println!("cargo:rustc-link-search=native={}", cfg.lib_dir().unwrap().display());
+    /// // rustc wants the bare name, like `python3.11`, not `libpython3.11.so`
+    /// let ldversion = cfg.config_var("LDVERSION").unwrap().unwrap();
+    /// println!("cargo:rustc-link-lib=python{}", ldversion);
+    /// ```
+    pub fn lib_dir(&self) -> PyResult<PathBuf> {
+        self.required_var("LIBDIR").map(PathBuf::from)
+    }
+
+    /// Returns the directory that holds the static libpython and the
+    /// `Makefile` of the build, from the `LIBPL` config var, like
+    /// `/usr/lib/python3.11/config-3.11-x86_64-linux-gnu`
+    ///
+    /// This is the same directory as [`config_dir_path`](#method.config_dir_path),
+    /// but it's available for every Python version. It fails on Windows.
+    pub fn config_lib_dir(&self) -> PyResult<PathBuf> {
+        self.required_var("LIBPL").map(PathBuf::from)
+    }
+
+    /// Returns the file name of the library that the interpreter links,
+    /// from the `LDLIBRARY` config var, like `libpython3.11.so` for a
+    /// shared build, or `libpython3.11.a` for a static build
+    ///
+    /// This is a file name, so it's not what `cargo:rustc-link-lib` wants;
+    /// see [`lib_dir`](#method.lib_dir). It fails on Windows.
+    pub fn ld_library_name(&self) -> PyResult<String> {
+        self.required_var("LDLIBRARY")
+    }
+
+    /// Returns the versioned file name of the shared libpython, from the
+    /// `INSTSONAME` config var, like `libpython3.11.so.1.0`
+    ///
+    /// For a static build, this names the static library. It fails on
+    /// Windows.
+    pub fn inst_so_name(&self) -> PyResult<String> {
+        self.required_var("INSTSONAME")
+    }
+
+    /// Returns the value of the config var `name`, failing if it's not
+    /// defined
+    fn required_var(&self, name: &str) -> PyResult<String> {
+        self.config_var(name)?.ok_or_else(|| Error::Undefined {
+            what: format!("the config var {}", name),
+        })
+    }
+
+    /// Generates the pkg-config files that Python's build installs, like
+    /// `python-3.11.pc` and `python-3.11-embed.pc`
+    ///
+    /// Some distributions leave these files out. Write them into a
+    /// directory, and point `PKG_CONFIG_PATH` at that directory, to build
+    /// against this interpreter with pkg-config. Python 3.8 and later have
+    /// both files; earlier versions only have `python-3.X.pc`.
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// for file in cfg.pkg_config().unwrap() {
+    ///     let path = file.write_to("pkgconfig").unwrap();
+    ///     println!("wrote {}", path.display());
+    /// }
+    /// ```
+    pub fn pkg_config(&self) -> Py3Only<Vec<PkgConfigFile>> {
+        self.is_py3()?;
+        self.require(Capability::PkgConfig)?;
+        let resp = self.script(query::PKG_CONFIG)?;
+        let values: Vec<&str> = resp.lines().collect();
+        let vars = match values[..] {
+            [prefix, exec_prefix, libdir, includedir, libs, libpython, abiflags, version] => {
+                pkgconfig::Vars {
+                    prefix,
+                    exec_prefix,
+                    libdir,
+                    includedir,
+                    version,
+                    abiflags,
+                    libs,
+                    libpython,
+                }
+            }
+            _ => return Err(parse_err("pkg-config variables", &resp)),
+        };
+        let minor = vars
+            .version
+            .split('.')
+            .nth(1)
+            .and_then(|minor| minor.parse::<u32>().ok());
+        let embed_file = minor.ok_or_else(|| parse_err("VERSION", vars.version))? >= 8;
+        Ok(pkgconfig::render(&vars, embed_file))
     }
 
     /// The ABI flags specified when building this Python distribution
@@ -417,7 +2340,7 @@ impl PythonConfig {
     /// feature parity with the `python3-config` script.
     pub fn abi_flags(&self) -> Py3Only<String> {
         self.is_py3()?;
-        let resp = self.script(&["import sys", "print(sys.abiflags)"])?;
+        let resp = self.script(query::ABI_FLAGS)?;
         Ok(resp)
     }
 
@@ -427,17 +2350,245 @@ impl PythonConfig {
     /// feature parity with the `python3-config` script.
     pub fn config_dir(&self) -> Py3Only<String> {
         self.is_py3()?;
-        let resp = self.script(&["print(getvar('LIBPL'))"])?;
-        Ok(resp)
+        self.require(Capability::ConfigDir)?;
+        defined(self.script(query::CONFIG_DIR)?, "the config var LIBPL")
+    }
+
+    /// Like [`config_dir`](#method.config_dir), but returns `None` if the
+    /// distribution doesn't define `LIBPL`, like on Windows
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    pub fn try_config_dir(&self) -> Py3Only<Option<String>> {
+        self.is_py3()?;
+        self.script(query::CONFIG_DIR).map(parse_value)
+    }
+
+    /// Like [`config_dir`](#method.config_dir), but returns the path to
+    /// the distribution's `python-config` script as a `PathBuf`.
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter! This is for
+    /// feature parity with the `python3-config` script.
+    pub fn config_dir_path(&self) -> Py3Only<PathBuf> {
+        self.config_dir().map(PathBuf::from)
+    }
+
+    /// Describes this interpreter in the terms used by maturin and
+    /// setuptools-rust, so that those tools build for the same interpreter
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    /// use std::process::Command;
+    ///
+    /// let env = PythonConfig::new().build_env().unwrap();
+    /// let status = Command::new("maturin")
+    ///     .arg("build")
+    ///     .envs(env.vars())
+    ///     .status()
+    ///     .unwrap();
+    /// ```
+    pub fn build_env(&self) -> Py3Only<BuildEnv> {
+        self.is_py3()?;
+        let resp = self.script(
+            &[
+                query::ABI_TAG,
+                &[
+                    "print(sys.executable)",
+                    "print(abi_tag)",
+                    "print(getvar('EXT_SUFFIX'))",
+                ],
+            ]
+            .concat(),
+        )?;
+        let lines: Vec<&str> = resp.lines().collect();
+        if let [interpreter, abi_tag, ext_suffix] = lines[..] {
+            Ok(BuildEnv {
+                interpreter: PathBuf::from(interpreter),
+                abi_tag: abi_tag.to_owned(),
+                ext_suffix: ext_suffix.to_owned(),
+            })
+        } else {
+            Err(parse_err(
+                "expected three lines describing the build environment",
+                &resp,
+            ))
+        }
+    }
+
+    /// Returns the `SOABI` config var, which names the ABI in extension
+    /// module file names, like `cpython-311-x86_64-linux-gnu`
+    ///
+    /// Returns `None` if the distribution doesn't define it, like Windows
+    /// distributions before Python 3.13.
+    pub fn soabi(&self) -> PyResult<Option<String>> {
+        self.config_var("SOABI")
+    }
+
+    /// Returns the platform that the distribution was built for, as
+    /// reported by `sysconfig.get_platform()`, like `linux-x86_64`,
+    /// `macosx-11.0-arm64`, or `win-amd64`
+    pub fn platform(&self) -> PyResult<String> {
+        self.script(query::PLATFORM)
+    }
+
+    /// Returns the Python implementation, like CPython or PyPy
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::{Implementation, PythonConfig};
+    ///
+    /// let cfg = PythonConfig::new();
+    /// if cfg.implementation().unwrap() == Implementation::PyPy {
+    ///     println!("running on PyPy");
+    /// }
+    /// ```
+    pub fn implementation(&self) -> PyResult<Implementation> {
+        self.script(query::IMPLEMENTATION)
+            .map(|name| Implementation::from_name(&name))
+    }
+
+    /// Returns the wheel tag for extension modules built for this
+    /// interpreter, like `cp312-cp312-linux_x86_64`
+    ///
+    /// The tag is the interpreter, ABI, and platform tags, separated by
+    /// dashes. The platform tag is `sysconfig.get_platform()` with dashes
+    /// and dots replaced by underscores, like the tag that `bdist_wheel`
+    /// uses. It's not a `manylinux` tag, since those depend on how the
+    /// wheel was built, rather than on the interpreter.
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let tag = PythonConfig::new().wheel_tag().unwrap();
+    /// println!("mypackage-1.0.0-{}.whl", tag);
+    /// ```
+    pub fn wheel_tag(&self) -> Py3Only<String> {
+        self.is_py3()?;
+        self.script(&[query::ABI_TAG, query::WHEEL_TAG].concat())
+    }
+
+    /// Queries all of the values in a [`PythonConfigData`](struct.PythonConfigData.html),
+    /// including every defined config var
+    ///
+    /// Unlike the other methods, which each run the interpreter, a snapshot
+    /// runs the interpreter once. If you need more than a couple of values,
+    /// take a snapshot, then read its fields.
+    ///
+    /// Each value is computed on its own. If computing one raises an
+    /// exception, the error names that field, rather than failing to parse
+    /// the rest. A `libc` that we can't compute is `None`.
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let data = PythonConfig::new().snapshot().unwrap();
+    /// println!("Python {} at {}", data.version, data.prefix);
+    /// println!("LDVERSION = {:?}", data.config_var("LDVERSION"));
+    /// ```
+    pub fn snapshot(&self) -> Py3Only<PythonConfigData> {
+        if cfg!(feature = "probe-script") {
+            return self.probe();
+        }
+        self.is_py3()?;
+        let queries: [(&str, &[&str]); 11] = [
+            ("executable", query::EXECUTABLE),
+            ("prefix", query::PREFIX),
+            ("exec_prefix", query::EXEC_PREFIX),
+            ("includes", query::INCLUDES),
+            ("cflags", query::CFLAGS),
+            ("libs", query::LIBS),
+            ("ldflags", query::LDFLAGS),
+            ("extension_suffix", query::EXTENSION_SUFFIX),
+            ("abi_flags", query::ABI_FLAGS),
+            ("config_dir", query::CONFIG_DIR),
+            ("libc", query::LIBC),
+        ];
+        let mut lines: Vec<String> = query::SNAPSHOT_PRELUDE
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        lines.push("field('version', version)".to_owned());
+        for (name, script) in queries.iter() {
+            lines.push(format!(
+                "field({}, {})",
+                query::literal(name),
+                query::literal(&script.join("\n"))
+            ));
+        }
+        lines.push("emit_config_vars()".to_owned());
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let resp = self.script(&lines)?;
+        PythonConfigData::parse(&resp)
+    }
+
+    /// Like [`snapshot`](#method.snapshot), but runs the probe script
+    /// that's embedded in this crate
+    ///
+    /// The probe is a single Python file that prints every value of the
+    /// snapshot; see [`probe_script`](#method.probe_script). Enable the
+    /// `probe-script` feature to make `snapshot` use the probe.
+    ///
+    /// This is only available when your interpreter is a Python 3 interpreter!
+    pub fn probe(&self) -> Py3Only<PythonConfigData> {
+        self.ver.supported()?;
+        self.is_py3()?;
+        let mut cmd = vec!["-c", query::probe()];
+        if cfg!(target_env = "msvc") {
+            cmd.push("--msvc");
+        }
+        let resp = self.cmdr.commands(&cmd)?;
+        PythonConfigData::parse(&resp)
+    }
+
+    /// Returns the source of the probe script run by [`probe`](#method.probe)
+    ///
+    /// Save it to a file to run it yourself, or to review what we run.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// std::fs::write("probe.py", cfg.probe_script()).unwrap();
+    /// // $ python3 probe.py
+    /// ```
+    pub fn probe_script(&self) -> &'static str {
+        query::probe()
     }
 
-    /// Like [`config_dir`](#method.config_dir), but returns the path to
-    /// the distribution's `python-config` script as a `PathBuf`.
+    /// Re-queries the interpreter and compares the result to the snapshot
+    /// saved at `path` by [`PythonConfigData::save`](struct.PythonConfigData.html#method.save)
     ///
-    /// This is only available when your interpreter is a Python 3 interpreter! This is for
-    /// feature parity with the `python3-config` script.
-    pub fn config_dir_path(&self) -> Py3Only<PathBuf> {
-        self.config_dir().map(PathBuf::from)
+    /// The returned diff is empty if nothing drifted. The saved snapshot is
+    /// on the left of the diff, and the live interpreter is on the right.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new();
+    /// let drift = cfg.verify_against("python-baseline.txt").unwrap();
+    /// for field in &drift {
+    ///     eprintln!("{} changed: '{}' -> '{}'", field.name, field.left, field.right);
+    /// }
+    /// ```
+    pub fn verify_against<P: AsRef<path::Path>>(&self, path: P) -> Py3Only<ConfigDiff> {
+        let saved = PythonConfigData::load(path)?;
+        Ok(saved.diff(&self.snapshot()?))
     }
 }
 
@@ -446,8 +2597,10 @@ mod tests {
     //! The tests only show that, under normal circumstances, there
     //! are no errors returned from the public API.
 
-    use super::PythonConfig;
-    use std::path::PathBuf;
+    use super::{Commander, Error, LinkMode, PyResult, PythonConfig, PythonIntrospection, Version};
+    use crate::testing::{override_vars, rewritten};
+    use std::cell::RefCell;
+    use std::path::{Path, PathBuf};
 
     macro_rules! pycfgtest {
         ($ident:ident) => {
@@ -460,19 +2613,70 @@ mod tests {
 
     pycfgtest!(version_raw);
     pycfgtest!(semantic_version);
+    pycfgtest!(python_version);
+    pycfgtest!(version_info);
+    pycfgtest!(executable);
+    pycfgtest!(executable_path);
     pycfgtest!(prefix);
     pycfgtest!(prefix_path);
     pycfgtest!(exec_prefix);
     pycfgtest!(exec_prefix_path);
+    pycfgtest!(base_prefix);
+    pycfgtest!(purelib);
+    pycfgtest!(purelib_path);
+    pycfgtest!(platlib);
+    pycfgtest!(platlib_path);
+    pycfgtest!(scripts_dir);
+    pycfgtest!(scripts_dir_path);
+    pycfgtest!(data_dir);
+    pycfgtest!(data_dir_path);
+    pycfgtest!(base_prefix_path);
+    pycfgtest!(is_virtualenv);
     pycfgtest!(includes);
     pycfgtest!(include_paths);
     pycfgtest!(cflags);
+    pycfgtest!(cflags_split);
+    pycfgtest!(lib_dir);
+    pycfgtest!(config_lib_dir);
+    pycfgtest!(ld_library_name);
+    pycfgtest!(inst_so_name);
+    pycfgtest!(ldflags_split);
     pycfgtest!(libs);
     pycfgtest!(ldflags);
+    pycfgtest!(libs_embed);
+    pycfgtest!(link_args);
+    pycfgtest!(link_args_embed);
+    pycfgtest!(ldflags_embed);
     pycfgtest!(extension_suffix);
     pycfgtest!(abi_flags);
     pycfgtest!(config_dir);
     pycfgtest!(config_dir_path);
+    pycfgtest!(snapshot);
+    pycfgtest!(build_env);
+    pycfgtest!(is_optimized_build);
+    pycfgtest!(sanitizers);
+    pycfgtest!(install_origin);
+    pycfgtest!(missing_headers_hint);
+    pycfgtest!(interpreter_path);
+    pycfgtest!(soabi);
+    pycfgtest!(platform);
+    pycfgtest!(implementation);
+    pycfgtest!(wheel_tag);
+    pycfgtest!(gil_disabled);
+    pycfgtest!(is_debug_build);
+    pycfgtest!(libs_abi3);
+    pycfgtest!(extension_suffix_abi3);
+    pycfgtest!(pkg_config);
+    pycfgtest!(libpython_path);
+    pycfgtest!(is_shared);
+    pycfgtest!(python_framework);
+    pycfgtest!(is_conda);
+    pycfgtest!(ldflags_with_rpath);
+    pycfgtest!(include_paths_dedup);
+    pycfgtest!(include_paths_canonical);
+    pycfgtest!(include_dir);
+    pycfgtest!(build_toolchain);
+    pycfgtest!(build_flags);
 
     // Shows that includes and include_paths return the same things
     // just in different types.
@@ -491,4 +2695,849 @@ mod tests {
         let actual = cfg.include_paths().unwrap();
         assert_eq!(actual, paths);
     }
+
+    #[test]
+    fn include_paths_unique() {
+        let cfg = PythonConfig::new();
+        let paths = cfg.include_paths_dedup().unwrap();
+        assert_eq!(paths[0], cfg.include_dir().unwrap());
+        assert!(paths.len() <= cfg.include_paths().unwrap().len());
+        assert!(!paths[1..].contains(&paths[0]));
+        let canonical = cfg.include_paths_canonical().unwrap();
+        assert!(canonical.len() <= paths.len());
+        assert!(canonical.iter().all(|path| path.is_absolute()));
+    }
+
+    #[test]
+    fn dedup_paths() {
+        let paths = ["/a", "/b", "/a", "/c", "/b"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(
+            super::dedup_paths(paths),
+            [
+                PathBuf::from("/a"),
+                PathBuf::from("/b"),
+                PathBuf::from("/c")
+            ]
+        );
+    }
+
+    // Shows that a live interpreter and its snapshot are
+    // interchangeable through the introspection trait.
+    #[test]
+    fn introspection_snapshot_same() {
+        let cfg = PythonConfig::new();
+        let data = cfg.snapshot().unwrap();
+        let sources: [&dyn PythonIntrospection; 2] = [&cfg, &data];
+        for source in &sources {
+            assert_eq!(source.snapshot().unwrap(), data);
+            assert_eq!(source.prefix_path().unwrap(), cfg.prefix_path().unwrap());
+        }
+    }
+
+    #[test]
+    fn snapshot_same() {
+        let cfg = PythonConfig::new();
+        let data = cfg.snapshot().unwrap();
+        assert_eq!(data.version, cfg.version_info().unwrap());
+        assert_eq!(data.executable, cfg.executable().unwrap());
+        assert_eq!(data.includes, cfg.includes().unwrap());
+        assert_eq!(data.cflags, cfg.cflags().unwrap());
+        assert_eq!(data.ldflags, cfg.ldflags().unwrap());
+        assert_eq!(data.config_dir, cfg.config_dir().unwrap());
+        assert_eq!(
+            data.config_var("VERSION"),
+            cfg.config_var("VERSION").unwrap().as_deref()
+        );
+        assert_eq!(data.lib_dir(), Some(cfg.lib_dir().unwrap()));
+        assert_eq!(data.config_lib_dir(), Some(cfg.config_lib_dir().unwrap()));
+        assert_eq!(
+            data.ld_library_name(),
+            Some(cfg.ld_library_name().unwrap().as_str())
+        );
+        assert_eq!(
+            data.inst_so_name(),
+            Some(cfg.inst_so_name().unwrap().as_str())
+        );
+        assert_eq!(
+            cfg.config_lib_dir().unwrap(),
+            cfg.config_dir_path().unwrap()
+        );
+    }
+
+    #[test]
+    fn embed_links_libpython() {
+        let cfg = PythonConfig::new();
+        let libpython = format!("-lpython{}", cfg.config_var("LDVERSION").unwrap().unwrap());
+        let has_libpython = |flags: String| flags.split_whitespace().any(|f| f == libpython);
+        assert!(has_libpython(cfg.libs_embed().unwrap()));
+        assert!(has_libpython(cfg.ldflags_embed().unwrap()));
+        if cfg.version_info().unwrap().at_least(3, 8) {
+            assert!(!has_libpython(cfg.libs().unwrap()));
+            assert!(!has_libpython(cfg.ldflags().unwrap()));
+        }
+    }
+
+    #[test]
+    fn venv() {
+        let venv =
+            std::env::temp_dir().join(format!("python-config-rs-venv-{}", std::process::id()));
+        let status = std::process::Command::new("python3")
+            .args(["-m", "venv", "--without-pip"])
+            .arg(&venv)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let base = PythonConfig::new();
+        let cfg = PythonConfig::from_venv(&venv).unwrap();
+        let result = std::panic::catch_unwind(|| {
+            assert!(cfg.is_virtualenv().unwrap());
+            assert_eq!(cfg.base_prefix().unwrap(), base.base_prefix().unwrap());
+            assert_eq!(cfg.include_paths().unwrap(), base.include_paths().unwrap());
+            assert_eq!(cfg.ldflags().unwrap(), base.ldflags().unwrap());
+        });
+        let _ = std::fs::remove_dir_all(&venv);
+        result.unwrap();
+    }
+
+    // Shows that link_args has the same flags as ldflags, just sorted
+    #[test]
+    fn link_args_same() {
+        let cfg = PythonConfig::new();
+        let link = cfg.link_args().unwrap();
+        let ldflags = cfg.ldflags().unwrap();
+        let count = link.lib_dirs.len()
+            + link.libs.len()
+            + 2 * link.frameworks.len()
+            + link.other_flags.len();
+        assert_eq!(count, ldflags.split_whitespace().count());
+        for lib in &link.libs {
+            assert!(ldflags.contains(&format!("-l{}", lib)));
+        }
+    }
+
+    #[test]
+    fn link_args_file() {
+        let cfg = PythonConfig::new();
+        let path =
+            std::env::temp_dir().join(format!("python-config-rs-link-{}.rsp", std::process::id()));
+        cfg.write_link_args_file(&path, crate::ResponseFileFormat::GccAtFile)
+            .unwrap();
+        let file = std::fs::read_to_string(&path);
+        let _ = std::fs::remove_file(&path);
+        // Nothing in a typical Linux LDFLAGS needs quotes. The file sorts
+        // the flags by kind.
+        let file = file.unwrap();
+        let mut args: Vec<&str> = file.lines().collect();
+        args.sort_unstable();
+        let ldflags = cfg.ldflags().unwrap();
+        let mut expected: Vec<&str> = ldflags.split_whitespace().collect();
+        expected.sort_unstable();
+        assert_eq!(args, expected);
+    }
+
+    #[test]
+    fn exported_symbols() {
+        let cfg = PythonConfig::new();
+        let exports = cfg.exported_symbols("package.spam").unwrap();
+        assert_eq!(exports.symbols, ["PyInit_spam"]);
+        assert!(exports.link_args.is_empty());
+        assert_eq!(
+            cfg.exported_symbols("spąm").unwrap().symbols,
+            ["PyInitU_spm_jpa"]
+        );
+    }
+
+    #[test]
+    fn split_flags() {
+        let cfg = PythonConfig::new();
+        let cflags = cfg.cflags_split().unwrap();
+        assert!(cflags[0].starts_with("-I"));
+        assert_eq!(cflags.join(" "), cfg.cflags().unwrap());
+        assert_eq!(
+            cfg.ldflags_split().unwrap().join(" "),
+            cfg.ldflags().unwrap()
+        );
+        assert_eq!(
+            super::split_lines("-I/opt/my python/include\n\n-DNAME=\"a b\"\n"),
+            ["-I/opt/my python/include", "-DNAME=\"a b\""]
+        );
+    }
+
+    #[test]
+    fn cargo_metadata_links_libpython() {
+        let cfg = PythonConfig::new();
+        let lib = format!(
+            "cargo:rustc-link-lib=python{}",
+            cfg.config_var("LDVERSION").unwrap().unwrap()
+        );
+        let lines = crate::build::cargo_metadata(&cfg).unwrap();
+        assert!(lines.contains(&lib));
+        assert!(lines.iter().any(|line| line.starts_with("cargo:include=")));
+    }
+
+    #[test]
+    fn invocation_options() {
+        let cfg = PythonConfig::new()
+            .with_clean_env()
+            .with_env("PYTHONPATH", "/this/path/does/not/exist")
+            .with_current_dir(std::env::temp_dir())
+            .with_timeout(std::time::Duration::from_secs(60));
+        assert_eq!(cfg.prefix().unwrap(), PythonConfig::new().prefix().unwrap());
+    }
+
+    #[test]
+    fn cached() {
+        let dir =
+            std::env::temp_dir().join(format!("python-config-rs-cached-{}", std::process::id()));
+        let cfg = PythonConfig::new();
+        for cached in &[
+            PythonConfig::new().cached(),
+            PythonConfig::new().cached_on_disk(&dir),
+            PythonConfig::new().cached_on_disk(&dir),
+        ] {
+            assert_eq!(cached.includes().unwrap(), cfg.includes().unwrap());
+            assert_eq!(cached.includes().unwrap(), cfg.includes().unwrap());
+            assert_eq!(cached.libs_embed().unwrap(), cfg.libs_embed().unwrap());
+        }
+        assert!(std::fs::read_dir(&dir).unwrap().count() > 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn discover_best() {
+        let req = semver::VersionReq::parse(">=3").unwrap();
+        let cfg = PythonConfig::discover_best(&req).unwrap();
+        assert!(req.matches(&cfg.semantic_version().unwrap()));
+        let req = semver::VersionReq::parse(">=99").unwrap();
+        assert!(PythonConfig::discover_best(&req).is_err());
+    }
+
+    #[test]
+    fn wheel_tag_parts() {
+        let cfg = PythonConfig::new();
+        let tag = cfg.wheel_tag().unwrap();
+        let build_env = cfg.build_env().unwrap();
+        let parts: Vec<&str> = tag.split('-').collect();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[1], build_env.abi_tag);
+        assert_eq!(parts[2], cfg.platform().unwrap().replace(['-', '.'], "_"));
+        if cfg.implementation().unwrap() == super::Implementation::CPython {
+            assert!(parts[0].starts_with("cp"));
+            assert!(cfg.soabi().unwrap().unwrap().starts_with("cpython-"));
+        }
+    }
+
+    // Shows that the build flavor is reflected in the ABI flags, and in
+    // the library that we link
+    #[test]
+    fn build_flavor() {
+        let cfg = PythonConfig::new();
+        let abi_flags = cfg.abi_flags().unwrap();
+        assert_eq!(cfg.gil_disabled().unwrap(), abi_flags.contains('t'));
+        assert_eq!(cfg.is_debug_build().unwrap(), abi_flags.contains('d'));
+        let libpython = format!(
+            "-lpython{}{}",
+            cfg.config_var("VERSION").unwrap().unwrap(),
+            abi_flags
+        );
+        assert!(cfg.libs_embed().unwrap().split(' ').any(|f| f == libpython));
+
+        let flags = cfg.build_flags().unwrap();
+        assert_eq!(flags.py_debug, cfg.is_debug_build().unwrap());
+        assert_eq!(flags.py_gil_disabled, cfg.gil_disabled().unwrap());
+    }
+
+    #[test]
+    fn stable_abi() {
+        let cfg = PythonConfig::new();
+        let libs = cfg.libs_abi3().unwrap();
+        assert!(libs.split(' ').any(|f| f == "-lpython3"));
+        assert!(cfg.extension_suffix_abi3().unwrap().starts_with(".abi3."));
+
+        let version = cfg.version_info().unwrap();
+        let current = (version.major as u8, version.minor as u8);
+        let supported = !cfg.gil_disabled().unwrap();
+        assert_eq!(cfg.supports_limited_api(current).unwrap(), supported);
+        assert_eq!(cfg.supports_limited_api((0, 0)).unwrap(), supported);
+        assert!(!cfg.supports_limited_api((3, 99)).unwrap());
+    }
+
+    #[test]
+    fn link_modes() {
+        let cfg = PythonConfig::new();
+        let libdir = format!("-L{}", cfg.config_var("LIBDIR").unwrap().unwrap());
+        let libpl = format!("-L{}", cfg.config_var("LIBPL").unwrap().unwrap());
+
+        let shared = cfg.ldflags_for(LinkMode::Shared).unwrap();
+        let shared: Vec<&str> = shared.split(' ').collect();
+        assert!(shared.contains(&libdir.as_str()));
+        assert!(!shared.contains(&libpl.as_str()));
+
+        let static_ = cfg.ldflags_for(LinkMode::Static).unwrap();
+        let static_: Vec<&str> = static_.split(' ').collect();
+        assert!(static_.contains(&libpl.as_str()));
+
+        let libpython = cfg.libs_embed().unwrap();
+        let libpython = libpython.split(' ').next().unwrap();
+        assert!(shared.contains(&libpython));
+        let archive = cfg
+            .config_lib_dir()
+            .unwrap()
+            .join(cfg.config_var("LIBRARY").unwrap().unwrap());
+        if archive.is_file() {
+            assert!(static_.contains(&archive.to_str().unwrap()));
+            assert!(!static_.contains(&libpython));
+        } else {
+            assert!(static_.contains(&libpython));
+        }
+    }
+
+    #[test]
+    fn rpath() {
+        let cfg = PythonConfig::new();
+        let embed = cfg.ldflags_embed().unwrap();
+        let with_rpath = cfg.ldflags_with_rpath().unwrap();
+        assert!(with_rpath.starts_with(&embed));
+        if !cfg!(windows) && cfg.is_shared().unwrap() && !cfg.is_conda().unwrap() {
+            let rpath = format!("-Wl,-rpath,{}", cfg.config_var("LIBDIR").unwrap().unwrap());
+            assert!(with_rpath.split(' ').any(|flag| flag == rpath));
+        }
+
+        // A static libpython doesn't need an rpath
+        let static_build = rewritten(|script| {
+            override_vars(
+                script,
+                "{'Py_ENABLE_SHARED': 0, 'LIBDIR': '/opt/static/lib'}",
+            )
+        });
+        assert_eq!(
+            static_build.ldflags_with_rpath().unwrap(),
+            static_build.ldflags_embed().unwrap()
+        );
+    }
+
+    #[test]
+    fn libpython_path_exists() {
+        let cfg = PythonConfig::new();
+        let path = cfg.libpython_path().unwrap();
+        assert!(path.is_file());
+        let name = path.file_name().unwrap().to_str().unwrap();
+        let version = cfg.config_var("VERSION").unwrap().unwrap();
+        assert!(name.contains(&version), "{} is not for {}", name, version);
+    }
+
+    #[test]
+    fn pkg_config_files() {
+        let cfg = PythonConfig::new();
+        let libdir = cfg.config_var("LIBDIR").unwrap().unwrap();
+        let fields = |contents: &str| -> Vec<String> {
+            contents
+                .lines()
+                .filter(|line| {
+                    ["Description:", "Version:", "Libs", "Cflags:"]
+                        .iter()
+                        .any(|field| line.starts_with(field))
+                })
+                .map(|line| line.trim_end().to_owned())
+                .collect()
+        };
+        // Distributions that ship the files should agree with what we generate
+        for file in cfg.pkg_config().unwrap() {
+            let installed = Path::new(&libdir).join("pkgconfig").join(&file.name);
+            if let Ok(contents) = std::fs::read_to_string(installed) {
+                assert_eq!(fields(&file.contents), fields(&contents));
+            }
+        }
+    }
+
+    #[test]
+    fn probe_same_as_queries() {
+        // Ask each query on its own, so that this holds with the
+        // probe-script feature, too
+        let cfg = PythonConfig::new();
+        let probed = cfg.probe().unwrap();
+        assert_eq!(probed.version, cfg.version_info().unwrap());
+        assert_eq!(probed.executable, cfg.executable().unwrap());
+        assert_eq!(probed.prefix, cfg.prefix().unwrap());
+        assert_eq!(probed.exec_prefix, cfg.exec_prefix().unwrap());
+        assert_eq!(probed.includes, cfg.includes().unwrap());
+        assert_eq!(probed.cflags, cfg.cflags().unwrap());
+        assert_eq!(probed.libs, cfg.libs().unwrap());
+        assert_eq!(probed.ldflags, cfg.ldflags().unwrap());
+        assert_eq!(probed.extension_suffix, cfg.extension_suffix().unwrap());
+        assert_eq!(probed.abi_flags, cfg.abi_flags().unwrap());
+        assert_eq!(probed.config_dir, cfg.config_dir().unwrap());
+        assert_eq!(probed.libc, cfg.libc().unwrap());
+        for name in ["LDVERSION", "LIBDIR", "EXT_SUFFIX", "Py_ENABLE_SHARED"] {
+            assert_eq!(
+                probed.config_var(name),
+                cfg.config_var(name).unwrap().as_deref()
+            );
+        }
+    }
+
+    #[test]
+    fn matching() {
+        let req = semver::VersionReq::parse(">=3, <99").unwrap();
+        let cfg = PythonConfig::matching(&req).unwrap();
+        assert!(req.matches(&cfg.semantic_version().unwrap()));
+
+        let req = semver::VersionReq::parse(">=99").unwrap();
+        match PythonConfig::matching(&req) {
+            Err(err @ Error::NoMatchingInterpreter { .. }) => {
+                let version = PythonConfig::new().semantic_version().unwrap();
+                assert!(err.to_string().contains(&version.to_string()));
+            }
+            Err(err) => panic!("unexpected error {}", err),
+            Ok(_) => panic!("no interpreter should match {}", req),
+        }
+    }
+
+    #[test]
+    fn custom_version() {
+        let version = PythonConfig::new().version_info().unwrap();
+        let cfg = PythonConfig::version(Version::Custom(3, version.minor as u8));
+        assert_eq!(cfg.version_info().unwrap(), version);
+        assert!(cfg.abi_flags().is_ok());
+    }
+
+    #[test]
+    fn build_toolchain_vars() {
+        let cfg = PythonConfig::new();
+        let toolchain = cfg.build_toolchain().unwrap();
+        assert_eq!(toolchain.cc, cfg.config_var("CC").unwrap());
+        assert_eq!(toolchain.ldshared, cfg.config_var("LDSHARED").unwrap());
+        assert_eq!(toolchain.opt, cfg.config_var("OPT").unwrap());
+    }
+
+    #[test]
+    fn run_python() {
+        let cfg = PythonConfig::new();
+        assert_eq!(
+            cfg.run_python("print(getvar('VERSION'))").unwrap(),
+            cfg.config_var("VERSION").unwrap().unwrap()
+        );
+        assert_eq!(
+            cfg.run_python("for x in range(3):\n\tprint(x)").unwrap(),
+            "0\n1\n2"
+        );
+        match cfg.run_python("raise ValueError('nope')") {
+            Err(Error::Interpreter { stderr, .. }) => assert!(stderr.contains("ValueError: nope")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn run_python_json() {
+        let cfg = PythonConfig::new();
+        let version: Vec<u64> = cfg
+            .run_python_json("import json\nprint(json.dumps(list(sys.version_info[:2])))")
+            .unwrap();
+        let info = cfg.version_info().unwrap();
+        assert_eq!(version, [info.major, info.minor]);
+        assert!(cfg.run_python_json::<Vec<u64>>("print('nope')").is_err());
+    }
+
+    #[test]
+    fn packages() {
+        let dir =
+            std::env::temp_dir().join(format!("python-config-rs-packages-{}", std::process::id()));
+        let has_include_dir = dir.join("has_include_dir");
+        let has_get_include = dir.join("has_get_include");
+        std::fs::create_dir_all(has_include_dir.join("include")).unwrap();
+        std::fs::create_dir_all(&has_get_include).unwrap();
+        std::fs::write(
+            has_include_dir.join("__init__.py"),
+            "__version__ = '1.2.3'\n",
+        )
+        .unwrap();
+        std::fs::write(
+            has_get_include.join("__init__.py"),
+            "def get_include():\n    return '/opt/headers'\n",
+        )
+        .unwrap();
+
+        let cfg = PythonConfig::new().with_env("PYTHONPATH", &dir);
+        let result = std::panic::catch_unwind(|| {
+            let include = cfg.package_include_dir("has_include_dir").unwrap();
+            assert_eq!(
+                std::fs::canonicalize(include).unwrap(),
+                std::fs::canonicalize(has_include_dir.join("include")).unwrap()
+            );
+            assert_eq!(cfg.package_version("has_include_dir").unwrap(), "1.2.3");
+            assert_eq!(
+                cfg.package_include_dir("has_get_include").unwrap(),
+                PathBuf::from("/opt/headers")
+            );
+            assert!(cfg.package_version("has_get_include").is_err());
+            assert!(cfg.package_include_dir("not_a_real_package").is_err());
+
+            assert!(cfg.module_available("has_include_dir").unwrap());
+            assert_eq!(
+                std::fs::canonicalize(cfg.module_path("has_include_dir").unwrap().unwrap())
+                    .unwrap(),
+                std::fs::canonicalize(has_include_dir.join("__init__.py")).unwrap()
+            );
+            assert!(!cfg.module_available("not_a_real_package").unwrap());
+            assert!(!cfg.module_available("has_include_dir.missing").unwrap());
+            assert!(!cfg.module_available("").unwrap());
+            assert_eq!(cfg.module_path("not_a_real_package").unwrap(), None);
+            assert!(cfg.module_available("sys").unwrap());
+            assert_eq!(cfg.module_path("sys").unwrap(), None);
+            assert!(cfg.module_path("json").unwrap().is_some());
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+        result.unwrap();
+    }
+
+    #[test]
+    fn config_var() {
+        let cfg = PythonConfig::new();
+        let version = cfg.config_var("VERSION").unwrap().unwrap();
+        let semver = cfg.semantic_version().unwrap();
+        assert_eq!(version, format!("{}.{}", semver.major, semver.minor));
+        assert_eq!(cfg.config_var("NOT_A_REAL_CONFIG_VAR").unwrap(), None);
+    }
+
+    #[test]
+    fn sysconfig_path() {
+        let cfg = PythonConfig::new();
+        let include = cfg.sysconfig_path("include").unwrap();
+        assert_eq!(include, cfg.include_paths().unwrap()[0]);
+        assert!(cfg.sysconfig_path("not-a-real-path").is_err());
+        assert_eq!(
+            cfg.purelib_path().unwrap(),
+            cfg.sysconfig_path("purelib").unwrap()
+        );
+        assert!(cfg.purelib().unwrap().ends_with("-packages"));
+
+        let stdlib = cfg.stdlib_dir().unwrap();
+        assert!(stdlib.join("os.py").is_file());
+        assert!(cfg.platstdlib_dir().unwrap().is_dir());
+        let sys_path = cfg.sys_path().unwrap();
+        assert!(sys_path.contains(&stdlib));
+        assert!(sys_path.iter().all(|entry| !entry.as_os_str().is_empty()));
+    }
+
+    /// Records the arguments of each command, and replies with `resp`
+    struct Recorder {
+        resp: &'static str,
+        seen: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl Commander for Recorder {
+        fn commands(&self, args: &[&str]) -> PyResult<String> {
+            self.seen
+                .borrow_mut()
+                .push(args.iter().map(|arg| arg.to_string()).collect());
+            Ok(self.resp.to_owned())
+        }
+    }
+
+    #[test]
+    fn custom_commander() {
+        let recorder = Recorder {
+            resp: "Python 3.11.7",
+            seen: RefCell::new(Vec::new()),
+        };
+        let cfg = PythonConfig::with_commander(Version::Three, &recorder);
+        assert_eq!(cfg.semantic_version().unwrap().minor, 11);
+        assert_eq!(cfg.libs_embed().unwrap(), "Python 3.11.7");
+        let seen = recorder.seen.borrow();
+        assert_eq!(seen[0], ["--version"]);
+        assert_eq!(seen[1][0], "-c");
+        assert_eq!(seen[1][2], "--embed");
+
+        #[cfg(feature = "python2")]
+        {
+            let boxed: Box<dyn Commander> = Box::new(Recorder {
+                resp: "",
+                seen: RefCell::new(Vec::new()),
+            });
+            let cfg = PythonConfig::with_commander(Version::Two, boxed);
+            assert!(matches!(cfg.abi_flags(), Err(Error::Python3Only)));
+        }
+    }
+
+    #[test]
+    fn unsupported_custom_version() {
+        let recorder = Recorder {
+            resp: "Python 2.7.18",
+            seen: RefCell::new(Vec::new()),
+        };
+        let cfg = PythonConfig::with_commander(Version::Custom(4, 0), &recorder);
+        assert!(matches!(
+            cfg.version_raw(),
+            Err(Error::UnsupportedVersion { .. })
+        ));
+        assert!(matches!(
+            cfg.prefix(),
+            Err(Error::UnsupportedVersion { .. })
+        ));
+
+        let cfg = PythonConfig::with_commander(Version::Custom(2, 7), &recorder);
+        if cfg!(feature = "python2") {
+            assert!(matches!(cfg.abi_flags(), Err(Error::Python3Only)));
+        } else {
+            match cfg.prefix() {
+                Err(Error::UnsupportedVersion { version, .. }) => assert_eq!(version, "2.7"),
+                other => panic!("unexpected result {:?}", other),
+            }
+        }
+        assert!(recorder.seen.borrow().is_empty());
+    }
+
+    #[test]
+    fn normalized_results() {
+        let recorder = Recorder {
+            resp: "  /opt/python\r\n",
+            seen: RefCell::new(Vec::new()),
+        };
+        let cfg = PythonConfig::with_commander(Version::Three, &recorder);
+        assert_eq!(cfg.prefix().unwrap(), "/opt/python");
+        assert_eq!(cfg.prefix_path().unwrap(), PathBuf::from("/opt/python"));
+        assert_eq!(super::normalize("1\r\nx\r\n".to_owned()), "1\nx");
+
+        let recorder = Recorder {
+            resp: "None\n",
+            seen: RefCell::new(Vec::new()),
+        };
+        let cfg = PythonConfig::with_commander(Version::Three, &recorder);
+        assert!(matches!(cfg.prefix(), Err(Error::Undefined { .. })));
+        assert_eq!(cfg.try_prefix().unwrap(), None);
+        assert_eq!(cfg.try_exec_prefix().unwrap(), None);
+        assert_eq!(cfg.try_config_dir().unwrap(), None);
+        assert_eq!(cfg.try_extension_suffix().unwrap(), None);
+        assert_eq!(cfg.try_sysconfig_path("include").unwrap(), None);
+        match cfg.sysconfig_path("include") {
+            Err(err @ Error::Undefined { .. }) => assert_eq!(
+                err.to_string(),
+                "the interpreter doesn't define the sysconfig path 'include'"
+            ),
+            other => panic!("{:?}", other),
+        }
+
+        let cfg = PythonConfig::new();
+        assert_eq!(cfg.try_prefix().unwrap().unwrap(), cfg.prefix().unwrap());
+        assert_eq!(
+            cfg.try_sysconfig_path("purelib").unwrap().unwrap(),
+            cfg.purelib_path().unwrap()
+        );
+    }
+
+    #[test]
+    fn interpreter_info() {
+        let cfg = PythonConfig::new();
+        assert!(cfg.interpreter_info().is_none());
+        let executable = cfg.executable_path().unwrap();
+        let cfg = PythonConfig::interpreter(&executable).unwrap();
+        let info = cfg.interpreter_info().unwrap();
+        assert_eq!(info.executable(), executable);
+        assert_eq!(info.realpath(), std::fs::canonicalize(&executable).unwrap());
+        assert_eq!(*info.version(), cfg.version_info().unwrap());
+        assert_eq!(*info.implementation(), cfg.implementation().unwrap());
+        assert!([32, 64].contains(&info.pointer_width()));
+    }
+
+    #[test]
+    fn not_a_python() {
+        for program in &["true", "echo"] {
+            match PythonConfig::interpreter(program) {
+                Err(Error::NotAPython { interpreter }) => assert_eq!(interpreter, *program),
+                Err(err) => panic!("unexpected error {}", err),
+                Ok(_) => panic!("{} is not a Python interpreter", program),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_version_raw() {
+        let semver = |resp| super::parse_version_raw(resp).unwrap().to_string();
+        assert_eq!(semver("Python 3.11.7"), "3.11.7");
+        assert_eq!(semver("Python 3.13.0rc2"), "3.13.0-rc.2");
+        assert_eq!(semver("Python 3.8.10+"), "3.8.10");
+        assert!(super::parse_version_raw("Python").is_err());
+
+        let cfg = PythonConfig::new();
+        let version = cfg.python_version().unwrap();
+        let info = cfg.version_info().unwrap();
+        assert_eq!(
+            (version.major, version.minor, version.patch),
+            (info.major, info.minor, info.micro)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = PythonConfig::interpreter("this-python-does-not-exist")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "the Python interpreter 'this-python-does-not-exist' was not found"
+        );
+        assert_eq!(
+            std::io::Error::from(err).kind(),
+            std::io::ErrorKind::NotFound
+        );
+
+        let err = Error::from(std::io::Error::other("broken pipe"));
+        assert!(std::error::Error::source(&err).is_some());
+
+        let err = super::parse_bool("maybe").unwrap_err();
+        assert!(matches!(err, Error::ParseError { ref output, .. } if output == "maybe"));
+        assert!(std::error::Error::source(&err).is_none());
+    }
+
+    #[test]
+    fn stray_output() {
+        let cfg = PythonConfig::new();
+        // Prints a warning to stdout before the query, like a noisy
+        // `sitecustomize`, and breaks the `version` field of snapshots
+        let noisy = rewritten(|script| {
+            script
+                .replacen(
+                    "import os, sys\n",
+                    "import os, sys\nprint('DeprecationWarning: noise')\n",
+                    1,
+                )
+                .replacen(
+                    "def version():\n",
+                    "def version():\n\traise OSError('no version')\n",
+                    1,
+                )
+        });
+        assert_eq!(noisy.prefix().unwrap(), cfg.prefix().unwrap());
+        assert_eq!(noisy.architecture().unwrap(), cfg.architecture().unwrap());
+        // The probe doesn't run the snapshot prelude
+        if !cfg!(feature = "probe-script") {
+            match noisy.snapshot() {
+                Err(Error::Other(msg)) => assert_eq!(
+                    msg,
+                    "the interpreter failed to compute version: OSError: no version"
+                ),
+                other => panic!("{:?}", other),
+            }
+        }
+
+        assert_eq!(
+            super::unframe("banner\n__PCRS__ begin\n1\n2\n__PCRS__ end\natexit\n".to_owned()),
+            "1\n2\n"
+        );
+        assert_eq!(super::unframe("1\n2".to_owned()), "1\n2");
+    }
+
+    #[test]
+    fn alternative_implementations() {
+        let version = PythonConfig::new().version_info().unwrap();
+        // We load the config vars before we pretend, since `sysconfig`
+        // needs the real `sys.implementation` to find them.
+        let pretend = |name: &'static str| {
+            rewritten(move |script| {
+                script.replacen(
+                    "import sysconfig\n",
+                    &format!(
+                        "import sysconfig\nimport sys, types\nsysconfig.get_config_vars()\nsys.implementation = types.SimpleNamespace(**dict(vars(sys.implementation), name='{}'))\n",
+                        name
+                    ),
+                    1,
+                )
+            })
+        };
+
+        let pypy = pretend("pypy");
+        assert_eq!(pypy.implementation().unwrap(), super::Implementation::PyPy);
+        let libpypy = format!("-lpypy{}.{}-c", version.major, version.minor);
+        assert!(pypy.libs_embed().unwrap().split(' ').any(|f| f == libpypy));
+        let libdir = pypy.config_var("LIBDIR").unwrap().unwrap();
+        let ldflags = pypy.ldflags_embed().unwrap();
+        assert!(ldflags.split(' ').any(|f| f == libpypy));
+        assert!(ldflags.contains(&format!("-L{}", libdir)));
+
+        let graalpy = pretend("graalpy");
+        assert!(graalpy
+            .libs_embed()
+            .unwrap()
+            .split(' ')
+            .any(|f| f == "-lpython-native"));
+    }
+
+    #[test]
+    fn interpreter_platform() {
+        let version = PythonConfig::new().version_info().unwrap();
+        // Runs the scripts as if the interpreter ran on Windows
+        let windows = rewritten(|script| {
+            script
+                .replacen("\nlinux = ", "\nlinux = False and ", 1)
+                .replacen("\nwindows = ", "\nwindows = True or ", 1)
+        });
+        let libname = format!("python{}.{}", version.major, version.minor);
+        let libs = windows.libs().unwrap();
+        if cfg!(target_env = "msvc") {
+            assert_eq!(libs, format!("{}.lib", libname));
+        } else {
+            assert_eq!(libs, format!("-l{}", libname));
+        }
+        assert!(windows.is_shared().unwrap());
+    }
+
+    #[test]
+    fn framework_builds() {
+        let cfg = PythonConfig::new();
+        assert_eq!(cfg.python_framework().unwrap(), None);
+
+        // Runs the scripts as if the interpreter were a macOS framework build
+        let framework = rewritten(|script| {
+            let vars =
+                "{'PYTHONFRAMEWORK': 'Python', 'PYTHONFRAMEWORKPREFIX': '/Library/Frameworks'}";
+            override_vars(script, vars)
+                .replacen("\nlinux = ", "\nlinux = False and ", 1)
+                .replacen("\nmacos = ", "\nmacos = True or ", 1)
+        });
+        let found = framework.python_framework().unwrap().unwrap();
+        assert_eq!(found.name, "Python");
+        assert_eq!(found.prefix, PathBuf::from("/Library/Frameworks"));
+        assert_eq!(
+            found.path(),
+            PathBuf::from("/Library/Frameworks/Python.framework")
+        );
+
+        let ldflags = framework.ldflags_embed().unwrap();
+        assert!(ldflags.starts_with("-F/Library/Frameworks -framework Python "));
+        assert!(!ldflags.contains("-lpython"));
+        let link = framework.link_args_embed().unwrap();
+        assert_eq!(link.frameworks[0], "Python");
+
+        // Upstream links a framework's archive with -l, too
+        let libpython = cfg.libs_embed().unwrap();
+        let libpython = libpython.split(' ').next().unwrap();
+        let ldflags = framework.ldflags_for(LinkMode::Static).unwrap();
+        assert!(ldflags.split(' ').any(|flag| flag == libpython));
+        assert!(!ldflags.contains("-framework Python"));
+    }
+
+    #[test]
+    fn conda() {
+        let cfg = PythonConfig::new();
+        let conda = match PythonConfig::from_conda_env("base") {
+            Ok(conda) => conda,
+            // Without conda, we can only check that we're not conda
+            Err(_) => return assert!(!cfg.is_conda().unwrap()),
+        };
+        assert!(conda.is_conda().unwrap());
+        let prefix = std::fs::canonicalize(conda.prefix().unwrap()).unwrap();
+        let libdir = prefix.join("lib");
+        let ldflags = conda.ldflags_embed().unwrap();
+        let ldflags: Vec<&str> = ldflags.split(' ').collect();
+        assert!(ldflags.contains(&format!("-L{}", libdir.display()).as_str()));
+        assert!(ldflags.contains(&format!("-Wl,-rpath,{}", libdir.display()).as_str()));
+
+        let by_path = PythonConfig::from_conda_env(prefix.to_str().unwrap()).unwrap();
+        assert_eq!(by_path.prefix().unwrap(), conda.prefix().unwrap());
+    }
 }
@@ -0,0 +1,295 @@
+//! The C library of a Linux interpreter, and the wheels that it can install
+
+use crate::{parse_err, query, Commander, Error, PyResult, PythonConfig};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// The C library that a Linux interpreter runs on, with its version
+///
+/// The libc decides which binary wheels the interpreter can install:
+/// manylinux wheels need glibc, and musllinux wheels need musl. See
+/// [`PythonConfig::libc`](struct.PythonConfig.html#method.libc).
+///
+/// The text form, like `glibc 2.35` or `musl 1.2`, parses back into a
+/// `Libc`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Libc {
+    /// GNU libc, like `2.35`
+    Glibc {
+        /// The major version, which is always `2`
+        major: u32,
+        /// The minor version, like `35`
+        minor: u32,
+    },
+    /// musl, like `1.2`
+    Musl {
+        /// The major version, like `1`
+        major: u32,
+        /// The minor version, like `2`
+        minor: u32,
+    },
+}
+
+/// The architectures that have a legacy manylinux alias, and the glibc
+/// minor version of each alias
+const LEGACY_MANYLINUX: &[(&str, u32, &[&str])] = &[
+    (
+        "manylinux2014",
+        17,
+        &[
+            "x86_64", "i686", "aarch64", "armv7l", "ppc64", "ppc64le", "s390x",
+        ],
+    ),
+    ("manylinux2010", 12, &["x86_64", "i686"]),
+    ("manylinux1", 5, &["x86_64", "i686"]),
+];
+
+impl Libc {
+    /// Returns the platform tags of the binary wheels that this libc can
+    /// run on `arch`, like `manylinux_2_35_x86_64`, newest first
+    ///
+    /// glibc supports every `manylinux_2_Y` tag up to its own version,
+    /// along with the legacy aliases, like `manylinux2014`. The oldest tag
+    /// is `manylinux_2_17`, or `manylinux_2_5` on `x86_64` and `i686`. musl
+    /// supports every `musllinux_1_Y` tag up to its own version.
+    pub fn platform_tags(self, arch: &str) -> Vec<String> {
+        let mut tags = Vec::new();
+        match self {
+            Libc::Glibc { major, minor } => {
+                let oldest = if arch == "x86_64" || arch == "i686" {
+                    5
+                } else {
+                    17
+                };
+                for glibc_minor in (oldest..=minor).rev() {
+                    tags.push(format!("manylinux_{}_{}_{}", major, glibc_minor, arch));
+                    for (alias, alias_minor, arches) in LEGACY_MANYLINUX {
+                        if major == 2 && glibc_minor == *alias_minor && arches.contains(&arch) {
+                            tags.push(format!("{}_{}", alias, arch));
+                        }
+                    }
+                }
+            }
+            Libc::Musl { major, minor } => {
+                for musl_minor in (0..=minor).rev() {
+                    tags.push(format!("musllinux_{}_{}_{}", major, musl_minor, arch));
+                }
+            }
+        }
+        tags
+    }
+}
+
+impl fmt::Display for Libc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Libc::Glibc { major, minor } => write!(f, "glibc {}.{}", major, minor),
+            Libc::Musl { major, minor } => write!(f, "musl {}.{}", major, minor),
+        }
+    }
+}
+
+/// Parses the text form, like `glibc 2.35`, or `musl 1.2.4`. A patch
+/// version is ignored.
+impl FromStr for Libc {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || parse_err("expected a libc resembling 'glibc 2.35'", s);
+        let (name, version) = s.trim().split_once(' ').ok_or_else(err)?;
+        let mut parts = version.split('.').map(str::parse::<u32>);
+        let (major, minor) = match (parts.next(), parts.next()) {
+            (Some(Ok(major)), Some(Ok(minor))) => (major, minor),
+            _ => return Err(err()),
+        };
+        match name {
+            "glibc" => Ok(Libc::Glibc { major, minor }),
+            "musl" => Ok(Libc::Musl { major, minor }),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// Which binary wheels a Linux interpreter can install
+///
+/// See [`PythonConfig::manylinux_compatibility`](struct.PythonConfig.html#method.manylinux_compatibility).
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManylinuxCompatibility {
+    /// The interpreter's C library
+    pub libc: Libc,
+    /// The architecture in platform tags, like `x86_64`, or `i686` for a
+    /// 32-bit interpreter on a 64-bit system
+    pub arch: String,
+    /// The platform tags that the interpreter can install, newest first,
+    /// like `manylinux_2_35_x86_64`; see [`Libc::platform_tags`](enum.Libc.html#method.platform_tags)
+    pub tags: Vec<String>,
+}
+
+/// Returns the architecture in platform tags for a Linux interpreter
+/// whose `sysconfig.get_platform()` is `platform`, like `linux-x86_64`
+fn tag_arch(platform: &str, pointer_width: u32) -> Option<String> {
+    let arch = platform
+        .strip_prefix("linux-")?
+        .replace(['-', '.', ' '], "_");
+    let arch = match (arch.as_str(), pointer_width) {
+        ("x86_64", 32) => "i686".to_owned(),
+        ("aarch64", 32) | ("armv8l", _) => "armv7l".to_owned(),
+        _ => arch,
+    };
+    Some(arch)
+}
+
+impl<C: Commander> PythonConfig<C> {
+    /// Returns the C library that the interpreter runs on, like glibc
+    /// 2.35, or `None` if it's not a Linux interpreter, or we can't tell
+    ///
+    /// glibc reports its version to the interpreter. For musl, we run the
+    /// dynamic loader, like `/lib/ld-musl-x86_64.so.1`, which prints its
+    /// version.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::{Libc, PythonConfig};
+    ///
+    /// if let Some(Libc::Musl { .. }) = PythonConfig::new().libc().unwrap() {
+    ///     println!("cargo:rustc-cfg=musl_python");
+    /// }
+    /// ```
+    pub fn libc(&self) -> PyResult<Option<Libc>> {
+        let resp = self.script(query::LIBC)?;
+        if resp.is_empty() {
+            Ok(None)
+        } else {
+            resp.parse().map(Some)
+        }
+    }
+
+    /// Returns the manylinux, or musllinux, platform tags of the binary
+    /// wheels that the interpreter can install, or `None` if it's not a
+    /// Linux interpreter, or we can't tell its libc
+    ///
+    /// Use it to decide which tag to build a wheel for, or to check that
+    /// a wheel will install.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// if let Some(compat) = PythonConfig::new().manylinux_compatibility().unwrap() {
+    ///     // Like 'manylinux_2_35_x86_64'
+    ///     println!("{}", compat.tags[0]);
+    /// }
+    /// ```
+    pub fn manylinux_compatibility(&self) -> PyResult<Option<ManylinuxCompatibility>> {
+        let resp = self.script(
+            &[
+                query::LIBC,
+                &[
+                    "import struct",
+                    "print(sysconfig.get_platform())",
+                    "print(struct.calcsize('P') * 8)",
+                ],
+            ]
+            .concat(),
+        )?;
+        let lines: Vec<&str> = resp.lines().collect();
+        let (libc, platform, pointer_width) = match lines[..] {
+            [libc, platform, pointer_width] => (libc, platform, pointer_width),
+            // No libc, so the first line is blank, and trimmed
+            [_, _] => return Ok(None),
+            _ => {
+                return Err(parse_err(
+                    "expected the libc, the platform, and the pointer width",
+                    &resp,
+                ))
+            }
+        };
+        let pointer_width = pointer_width
+            .parse()
+            .map_err(|_| parse_err("expected the pointer width", &resp))?;
+        let (libc, arch) = match (libc, tag_arch(platform, pointer_width)) {
+            ("", _) | (_, None) => return Ok(None),
+            (libc, Some(arch)) => (libc.parse::<Libc>()?, arch),
+        };
+        Ok(Some(ManylinuxCompatibility {
+            libc,
+            tags: libc.platform_tags(&arch),
+            arch,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tag_arch, Libc};
+    use crate::PythonConfig;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "glibc 2.35".parse::<Libc>().unwrap(),
+            Libc::Glibc {
+                major: 2,
+                minor: 35
+            }
+        );
+        let musl: Libc = "musl 1.2.4".parse().unwrap();
+        assert_eq!(musl, Libc::Musl { major: 1, minor: 2 });
+        assert_eq!(musl.to_string(), "musl 1.2");
+        assert!("uclibc 1.0".parse::<Libc>().is_err());
+        assert!("glibc".parse::<Libc>().is_err());
+    }
+
+    #[test]
+    fn platform_tags() {
+        let glibc = Libc::Glibc {
+            major: 2,
+            minor: 17,
+        };
+        let tags = glibc.platform_tags("x86_64");
+        assert_eq!(
+            &tags[..3],
+            [
+                "manylinux_2_17_x86_64",
+                "manylinux2014_x86_64",
+                "manylinux_2_16_x86_64"
+            ]
+        );
+        assert!(tags.contains(&"manylinux2010_x86_64".to_owned()));
+        assert_eq!(tags.last().unwrap(), "manylinux1_x86_64");
+        assert_eq!(
+            glibc.platform_tags("aarch64"),
+            ["manylinux_2_17_aarch64", "manylinux2014_aarch64"]
+        );
+        assert_eq!(
+            Libc::Musl { major: 1, minor: 1 }.platform_tags("aarch64"),
+            ["musllinux_1_1_aarch64", "musllinux_1_0_aarch64"]
+        );
+    }
+
+    #[test]
+    fn arch() {
+        assert_eq!(tag_arch("linux-x86_64", 64).unwrap(), "x86_64");
+        assert_eq!(tag_arch("linux-x86_64", 32).unwrap(), "i686");
+        assert_eq!(tag_arch("linux-armv8l", 32).unwrap(), "armv7l");
+        assert_eq!(tag_arch("macosx-11.0-arm64", 64), None);
+    }
+
+    #[test]
+    fn live() {
+        let cfg = PythonConfig::new();
+        let libc = cfg.libc().unwrap();
+        let compat = cfg.manylinux_compatibility().unwrap();
+        if cfg!(target_os = "linux") {
+            let compat = compat.unwrap();
+            assert_eq!(Some(compat.libc), libc);
+            assert!(compat.tags[0].contains(&compat.arch));
+        } else {
+            assert!(compat.is_none());
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! Structured linker arguments
+
+use std::path::PathBuf;
+
+/// The linker arguments for a Python distribution, sorted by kind
+///
+/// Unlike the flag strings returned by
+/// [`PythonConfig::ldflags`](struct.PythonConfig.html#method.ldflags),
+/// each path is kept whole, even if it contains spaces. Within each
+/// field, the arguments are in the order that the distribution
+/// reported them.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkArgs {
+    /// Library search directories, from `-L` (or `/LIBPATH:` for MSVC)
+    pub lib_dirs: Vec<PathBuf>,
+    /// Libraries to link, without the `-l` prefix (or the `.lib`
+    /// suffix for MSVC), like `python3.11` or `dl`
+    pub libs: Vec<String>,
+    /// macOS frameworks to link, without the `-framework` flag,
+    /// like `CoreFoundation`
+    pub frameworks: Vec<String>,
+    /// Every other flag, like `-Wl,-rpath,...` or `-Xlinker`
+    pub other_flags: Vec<String>,
+}
+
+impl LinkArgs {
+    /// Renders the arguments as a linker response file in `format`, one
+    /// argument per line
+    ///
+    /// The arguments are in the order of the fields: library directories,
+    /// libraries, frameworks, then the other flags. Other flags are passed
+    /// as they are, so they should suit the linker that reads the file.
+    /// MSVC has no frameworks, so `MsvcRsp` leaves them out.
+    ///
+    /// See [`PythonConfig::write_link_args_file`](struct.PythonConfig.html#method.write_link_args_file).
+    pub fn to_response_file(&self, format: ResponseFileFormat) -> String {
+        let mut args = Vec::new();
+        match format {
+            ResponseFileFormat::GccAtFile => {
+                args.extend(
+                    self.lib_dirs
+                        .iter()
+                        .map(|dir| format!("-L{}", dir.display())),
+                );
+                args.extend(self.libs.iter().map(|lib| format!("-l{}", lib)));
+                for framework in &self.frameworks {
+                    args.push("-framework".to_owned());
+                    args.push(framework.clone());
+                }
+            }
+            ResponseFileFormat::MsvcRsp => {
+                args.extend(
+                    self.lib_dirs
+                        .iter()
+                        .map(|dir| format!("/LIBPATH:{}", dir.display())),
+                );
+                args.extend(self.libs.iter().map(|lib| format!("{}.lib", lib)));
+            }
+        }
+        args.extend(self.other_flags.iter().cloned());
+
+        let mut file = String::new();
+        for arg in args {
+            file.push_str(&format.quote(&arg));
+            file.push('\n');
+        }
+        file
+    }
+}
+
+/// The format of a linker response file, which holds arguments for the
+/// linker
+///
+/// See [`LinkArgs::to_response_file`](struct.LinkArgs.html#method.to_response_file).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResponseFileFormat {
+    /// A response file for MSVC's `link.exe`, passed as `@file.rsp`.
+    /// Libraries are named like `python311.lib`, and directories are
+    /// searched with `/LIBPATH:`.
+    MsvcRsp,
+    /// A response file for GCC, Clang, and their linkers, passed as
+    /// `@file`. Libraries and directories use `-l` and `-L`.
+    GccAtFile,
+}
+
+impl ResponseFileFormat {
+    /// Quotes `arg`, if it needs quotes, the way that the tool that reads
+    /// this format splits arguments
+    fn quote(self, arg: &str) -> String {
+        let special: &[char] = match self {
+            ResponseFileFormat::MsvcRsp => &[' ', '\t', '"'],
+            ResponseFileFormat::GccAtFile => &[' ', '\t', '"', '\'', '\\'],
+        };
+        if !arg.is_empty() && !arg.contains(special) {
+            return arg.to_owned();
+        }
+        let mut quoted = String::from("\"");
+        match self {
+            // Backslashes are literal, unless they precede a quote
+            ResponseFileFormat::MsvcRsp => {
+                let mut backslashes = 0;
+                for c in arg.chars() {
+                    if c == '\\' {
+                        backslashes += 1;
+                        continue;
+                    }
+                    let escapes = if c == '"' {
+                        backslashes * 2 + 1
+                    } else {
+                        backslashes
+                    };
+                    quoted.extend(std::iter::repeat_n('\\', escapes));
+                    quoted.push(c);
+                    backslashes = 0;
+                }
+                quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+            }
+            // A backslash escapes any character
+            ResponseFileFormat::GccAtFile => {
+                for c in arg.chars() {
+                    if c == '"' || c == '\\' {
+                        quoted.push('\\');
+                    }
+                    quoted.push(c);
+                }
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+}
+
+/// The symbols that an extension module must export, and the linker
+/// flags that export them
+///
+/// See [`PythonConfig::exported_symbols`](struct.PythonConfig.html#method.exported_symbols).
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExportedSymbols {
+    /// The symbols, like `PyInit_spam`
+    pub symbols: Vec<String>,
+    /// The linker flags for a loadable extension module, like
+    /// `-undefined dynamic_lookup` on macOS, or `/EXPORT:PyInit_spam` for
+    /// MSVC. Empty when the linker's defaults work, like on Linux.
+    pub link_args: Vec<String>,
+}
+
+/// A macOS framework that holds libpython
+///
+/// See [`PythonConfig::python_framework`](struct.PythonConfig.html#method.python_framework).
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Framework {
+    /// The framework's name, from `PYTHONFRAMEWORK`, like `Python`
+    pub name: String,
+    /// The directory that holds the framework, from `PYTHONFRAMEWORKPREFIX`,
+    /// like `/Library/Frameworks`. This is the directory that you pass to
+    /// the linker with `-F`.
+    pub prefix: PathBuf,
+}
+
+impl Framework {
+    /// Returns the path to the framework bundle, like
+    /// `/Library/Frameworks/Python.framework`
+    pub fn path(&self) -> PathBuf {
+        self.prefix.join(format!("{}.framework", self.name))
+    }
+}
+
+/// How to link libpython into an application
+///
+/// See [`PythonConfig::ldflags_for`](struct.PythonConfig.html#method.ldflags_for).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LinkMode {
+    /// Link the static library, `libpythonX.Y.a`, found in `LIBPL`
+    Static,
+    /// Link the shared library, found in `LIBDIR`
+    Shared,
+}
+
+impl LinkMode {
+    /// The flag that selects this mode in the `LDFLAGS` script
+    pub(crate) fn script_arg(self) -> &'static str {
+        match self {
+            LinkMode::Static => "--static",
+            LinkMode::Shared => "--shared",
+        }
+    }
+}
+
+/// Sorts the individual linker `args` into a `LinkArgs`
+pub(crate) fn parse<'a, I>(args: I) -> LinkArgs
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut link = LinkArgs::default();
+    let mut args = args.into_iter().filter(|arg| !arg.is_empty());
+    while let Some(arg) = args.next() {
+        if let Some(dir) = arg
+            .strip_prefix("-L")
+            .or_else(|| arg.strip_prefix("/LIBPATH:"))
+        {
+            link.lib_dirs.push(PathBuf::from(dir));
+        } else if let Some(lib) = arg.strip_prefix("-l") {
+            link.libs.push(lib.to_owned());
+        } else if arg == "-framework" {
+            match args.next() {
+                Some(framework) => link.frameworks.push(framework.to_owned()),
+                None => link.other_flags.push(arg.to_owned()),
+            }
+        } else if let Some(lib) = arg.strip_suffix(".lib").filter(|_| !arg.starts_with('-')) {
+            link.libs.push(lib.to_owned());
+        } else {
+            link.other_flags.push(arg.to_owned());
+        }
+    }
+    link
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, LinkArgs, ResponseFileFormat};
+    use std::path::PathBuf;
+
+    #[test]
+    fn sorted_by_kind() {
+        let args = [
+            "-L/Library/Application Support/Python/lib",
+            "-lpython3.11",
+            "-ldl",
+            "-framework",
+            "CoreFoundation",
+            "-Wl,-rpath,/opt/python/lib",
+            "",
+            "-Xlinker",
+            "-export-dynamic",
+        ];
+        assert_eq!(
+            parse(args.iter().cloned()),
+            LinkArgs {
+                lib_dirs: vec![PathBuf::from("/Library/Application Support/Python/lib")],
+                libs: vec!["python3.11".to_owned(), "dl".to_owned()],
+                frameworks: vec!["CoreFoundation".to_owned()],
+                other_flags: vec![
+                    "-Wl,-rpath,/opt/python/lib".to_owned(),
+                    "-Xlinker".to_owned(),
+                    "-export-dynamic".to_owned()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn msvc() {
+        let link = parse(vec![
+            r"/LIBPATH:C:\Program Files\Python311\libs",
+            "python311.lib",
+        ]);
+        assert_eq!(
+            link.lib_dirs,
+            [PathBuf::from(r"C:\Program Files\Python311\libs")]
+        );
+        assert_eq!(link.libs, ["python311"]);
+    }
+
+    #[test]
+    fn response_files() {
+        let link = LinkArgs {
+            lib_dirs: vec![PathBuf::from(r"C:\Program Files\Python311\libs")],
+            libs: vec!["python311".to_owned()],
+            frameworks: vec!["CoreFoundation".to_owned()],
+            other_flags: vec![r#"-DNAME="a b""#.to_owned()],
+        };
+        assert_eq!(
+            link.to_response_file(ResponseFileFormat::MsvcRsp),
+            concat!(
+                r#""/LIBPATH:C:\Program Files\Python311\libs""#,
+                "\npython311.lib\n",
+                r#""-DNAME=\"a b\"""#,
+                "\n"
+            )
+        );
+        assert_eq!(
+            link.to_response_file(ResponseFileFormat::GccAtFile),
+            concat!(
+                r#""-LC:\\Program Files\\Python311\\libs""#,
+                "\n-lpython311\n-framework\nCoreFoundation\n",
+                r#""-DNAME=\"a b\"""#,
+                "\n"
+            )
+        );
+        assert_eq!(
+            ResponseFileFormat::MsvcRsp.quote(r"C:\a b\"),
+            r#""C:\a b\\""#
+        );
+        assert_eq!(ResponseFileFormat::GccAtFile.quote(""), r#""""#);
+    }
+}
@@ -0,0 +1,180 @@
+//! Several Python minor versions, side by side
+
+use crate::{
+    best_of, compare, discover, parse_err, Error, InterpreterReport, PyResult, PythonConfig,
+};
+
+use std::path::PathBuf;
+
+/// One of the versions in a [`PythonMatrix`](struct.PythonMatrix.html)
+pub struct MatrixEntry {
+    /// The version that was asked for, like `3.11`
+    pub version: String,
+    /// The newest interpreter of that version, or the reason that we
+    /// couldn't find one
+    pub config: PyResult<PythonConfig>,
+}
+
+/// The interpreters of several Python minor versions, like 3.9 through
+/// 3.12
+///
+/// Tools that build an extension module, or a wheel, for each Python
+/// that they support, need the configuration of each. A matrix finds the
+/// newest interpreter of each version with
+/// [`discover::all`](discover/fn.all.html). A version that isn't
+/// installed doesn't fail the whole matrix. Its entry holds the error.
+///
+/// # Example
+///
+/// ```no_run
+/// use python_config::PythonMatrix;
+///
+/// let matrix = PythonMatrix::from_versions(&["3.9", "3.10", "3.11", "3.12"]).unwrap();
+/// for (version, cfg) in matrix.configs() {
+///     println!("{}: {}", version, cfg.extension_suffix().unwrap());
+/// }
+/// for (version, err) in matrix.missing() {
+///     eprintln!("skipping Python {}: {}", version, err);
+/// }
+/// ```
+pub struct PythonMatrix {
+    entries: Vec<MatrixEntry>,
+}
+
+/// Parses a minor version, like `3.11`, into the requirement that its
+/// interpreters match
+fn minor_requirement(version: &str) -> PyResult<semver::VersionReq> {
+    let err = || parse_err("expected a Python minor version, like '3.11'", version);
+    let (major, minor) = version.trim().split_once('.').ok_or_else(err)?;
+    let major: u64 = major.parse().map_err(|_| err())?;
+    let minor: u64 = minor.parse().map_err(|_| err())?;
+    semver::VersionReq::parse(&format!("~{}.{}", major, minor)).map_err(|_| err())
+}
+
+impl PythonMatrix {
+    /// Finds the newest interpreter of each of `versions`, like `"3.11"`
+    ///
+    /// The entries are in the order of `versions`. This fails if a version
+    /// isn't a minor version, like `3.11`, but not if we can't find its
+    /// interpreter.
+    pub fn from_versions<S: AsRef<str>>(versions: &[S]) -> PyResult<Self> {
+        let reqs = versions
+            .iter()
+            .map(|version| minor_requirement(version.as_ref()))
+            .collect::<PyResult<Vec<_>>>()?;
+        let found = discover::all();
+        let entries = versions
+            .iter()
+            .zip(reqs)
+            .map(|(version, req)| MatrixEntry {
+                version: version.as_ref().trim().to_owned(),
+                config: best_of(found.clone(), &req),
+            })
+            .collect();
+        Ok(PythonMatrix { entries })
+    }
+
+    /// Iterates over every entry, in the order that the versions were given
+    pub fn iter(&self) -> std::slice::Iter<'_, MatrixEntry> {
+        self.entries.iter()
+    }
+
+    /// Iterates over the versions that have an interpreter, with its
+    /// `PythonConfig`
+    pub fn configs(&self) -> impl Iterator<Item = (&str, &PythonConfig)> {
+        self.entries
+            .iter()
+            .filter_map(|entry| Some((entry.version.as_str(), entry.config.as_ref().ok()?)))
+    }
+
+    /// Iterates over the versions that don't have an interpreter, with the
+    /// reason
+    pub fn missing(&self) -> impl Iterator<Item = (&str, &Error)> {
+        self.entries
+            .iter()
+            .filter_map(|entry| Some((entry.version.as_str(), entry.config.as_ref().err()?)))
+    }
+
+    /// Returns the `PythonConfig` of `version`, like `"3.11"`, if we found
+    /// its interpreter
+    pub fn get(&self, version: &str) -> Option<&PythonConfig> {
+        self.configs()
+            .find(|(candidate, _)| *candidate == version.trim())
+            .map(|(_, cfg)| cfg)
+    }
+
+    /// Queries every interpreter at the same time, returning a report for
+    /// each entry, in order, like [`compare`](fn.compare.html)
+    ///
+    /// The report of a version without an interpreter names the program
+    /// that we'd expect, like `python3.13`, and holds the reason that we
+    /// didn't find it.
+    pub fn report(&self) -> Vec<InterpreterReport> {
+        let paths: Vec<PathBuf> = self
+            .configs()
+            .filter_map(|(_, cfg)| cfg.interpreter_info())
+            .map(|info| info.executable().to_owned())
+            .collect();
+        let mut reports = compare(&paths).into_iter();
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry.config {
+                Ok(ref cfg) => cfg.interpreter_info().and_then(|_| reports.next()),
+                Err(ref err) => Some(InterpreterReport {
+                    interpreter: PathBuf::from(format!("python{}", entry.version)),
+                    result: Err(Error::Other(err.to_string().into())),
+                }),
+            })
+            .collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a PythonMatrix {
+    type Item = &'a MatrixEntry;
+    type IntoIter = std::slice::Iter<'a, MatrixEntry>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{minor_requirement, PythonMatrix};
+    use crate::{Error, PythonConfig};
+
+    #[test]
+    fn requirements() {
+        let req = minor_requirement("3.11").unwrap();
+        assert!(req.matches(&semver::Version::new(3, 11, 7)));
+        assert!(!req.matches(&semver::Version::new(3, 12, 0)));
+        assert!(minor_requirement("3").is_err());
+        assert!(minor_requirement("3.x").is_err());
+        assert!(PythonMatrix::from_versions(&["3.11", "three"]).is_err());
+    }
+
+    #[test]
+    fn matrix() {
+        let version = PythonConfig::new().version_info().unwrap();
+        let ours = format!("{}.{}", version.major, version.minor);
+        let matrix = PythonMatrix::from_versions(&[ours.as_str(), "3.99"]).unwrap();
+        assert_eq!(matrix.iter().count(), 2);
+
+        let cfg = matrix.get(&ours).unwrap();
+        assert_eq!(cfg.version_info().unwrap().minor, version.minor);
+        let missing: Vec<&str> = matrix.missing().map(|(version, _)| version).collect();
+        assert_eq!(missing, ["3.99"]);
+        assert!(matches!(
+            matrix.missing().next(),
+            Some((_, Error::NoMatchingInterpreter { .. }))
+        ));
+
+        let reports = matrix.report();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(
+            reports[0].result.as_ref().unwrap().version.minor,
+            version.minor
+        );
+        assert!(reports[1].result.is_err());
+        assert_eq!(reports[1].interpreter.to_str(), Some("python3.99"));
+    }
+}
@@ -0,0 +1,261 @@
+//! Queries that don't block the thread, for the `tokio` runtime
+
+use crate::{
+    parse_config_var, parse_version_raw, query, Capabilities, Capability, Error, Py3Only, PyResult,
+    PythonConfig, SysCommand,
+};
+
+/// Like a [`PythonConfig`](struct.PythonConfig.html), but the queries
+/// are `async`
+///
+/// Each query runs the interpreter with `tokio::process`, so a runtime
+/// can query several interpreters at once, without dedicating a thread
+/// to each. The queries honor the retry policy, timeout, environment,
+/// working directory, and cache of the `PythonConfig` that we wrap.
+///
+/// Requires the `tokio` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// use python_config::{AsyncPythonConfig, PythonConfig};
+///
+/// async fn prefixes() -> Vec<String> {
+///     let queries: Vec<_> = ["python3.11", "python3.12"]
+///         .iter()
+///         .map(|name| {
+///             let cfg = AsyncPythonConfig::from(PythonConfig::interpreter(name).unwrap());
+///             tokio::spawn(async move { cfg.prefix().await })
+///         })
+///         .collect();
+///     let mut prefixes = Vec::new();
+///     for query in queries {
+///         prefixes.push(query.await.unwrap().unwrap());
+///     }
+///     prefixes
+/// }
+/// ```
+pub struct AsyncPythonConfig {
+    cfg: PythonConfig<SysCommand>,
+}
+
+impl From<PythonConfig<SysCommand>> for AsyncPythonConfig {
+    fn from(cfg: PythonConfig<SysCommand>) -> Self {
+        AsyncPythonConfig { cfg }
+    }
+}
+
+impl Default for AsyncPythonConfig {
+    fn default() -> Self {
+        AsyncPythonConfig::new()
+    }
+}
+
+impl AsyncPythonConfig {
+    /// Like [`PythonConfig::new`](struct.PythonConfig.html#method.new)
+    pub fn new() -> Self {
+        PythonConfig::new().into()
+    }
+
+    /// Returns the `PythonConfig` that we wrap, for the blocking queries
+    pub fn blocking(&self) -> &PythonConfig<SysCommand> {
+        &self.cfg
+    }
+
+    async fn script(&self, lines: &[&str]) -> PyResult<String> {
+        self.script_args(lines, &[]).await
+    }
+
+    async fn script_args(&self, lines: &[&str], args: &[&str]) -> PyResult<String> {
+        self.cfg.ver.supported()?;
+        let script = crate::build_script(lines);
+        let mut cmd = vec!["-c", &script];
+        cmd.extend_from_slice(args);
+        self.cfg
+            .cmdr
+            .commands_async(&cmd)
+            .await
+            .map(crate::unframe)
+            .map(crate::normalize)
+    }
+
+    /// Like [`PythonConfig::version_raw`](struct.PythonConfig.html#method.version_raw)
+    pub async fn version_raw(&self) -> PyResult<String> {
+        self.cfg.ver.supported()?;
+        self.cfg
+            .cmdr
+            .commands_async(&["--version"])
+            .await
+            .map(crate::normalize)
+    }
+
+    /// Like [`PythonConfig::semantic_version`](struct.PythonConfig.html#method.semantic_version)
+    pub async fn semantic_version(&self) -> PyResult<semver::Version> {
+        parse_version_raw(&self.version_raw().await?)
+    }
+
+    /// Like [`PythonConfig::capabilities`](struct.PythonConfig.html#method.capabilities)
+    ///
+    /// The answer is shared with the `PythonConfig` that we wrap.
+    pub async fn capabilities(&self) -> PyResult<Capabilities> {
+        if let Some(caps) = self.cfg.caps.get() {
+            return Ok(*caps);
+        }
+        let caps = Capabilities::parse(&self.script(query::CAPABILITIES).await?);
+        Ok(*self.cfg.caps.get_or_init(|| caps))
+    }
+
+    /// Fails with `Error::Unsupported` if the interpreter doesn't support
+    /// `capability`
+    async fn require(&self, capability: Capability) -> PyResult<()> {
+        if self.capabilities().await?.contains(capability) {
+            Ok(())
+        } else {
+            Err(Error::Unsupported(capability))
+        }
+    }
+
+    /// Like [`PythonConfig::executable`](struct.PythonConfig.html#method.executable)
+    pub async fn executable(&self) -> PyResult<String> {
+        self.script(query::EXECUTABLE).await
+    }
+
+    /// Like [`PythonConfig::config_var`](struct.PythonConfig.html#method.config_var)
+    pub async fn config_var(&self, name: &str) -> PyResult<Option<String>> {
+        parse_config_var(&self.script_args(query::CONFIG_VAR, &[name]).await?)
+    }
+
+    /// Like [`PythonConfig::prefix`](struct.PythonConfig.html#method.prefix)
+    pub async fn prefix(&self) -> PyResult<String> {
+        crate::defined(self.script(query::PREFIX).await?, "the config var prefix")
+    }
+
+    /// Like [`PythonConfig::exec_prefix`](struct.PythonConfig.html#method.exec_prefix)
+    pub async fn exec_prefix(&self) -> PyResult<String> {
+        crate::defined(
+            self.script(query::EXEC_PREFIX).await?,
+            "the config var exec_prefix",
+        )
+    }
+
+    /// Like [`PythonConfig::includes`](struct.PythonConfig.html#method.includes)
+    pub async fn includes(&self) -> PyResult<String> {
+        self.script(query::INCLUDES).await
+    }
+
+    /// Like [`PythonConfig::cflags`](struct.PythonConfig.html#method.cflags)
+    pub async fn cflags(&self) -> PyResult<String> {
+        self.script(query::CFLAGS).await
+    }
+
+    /// Like [`PythonConfig::libs`](struct.PythonConfig.html#method.libs)
+    pub async fn libs(&self) -> PyResult<String> {
+        self.script_args(query::LIBS, &self.cfg.embed_args(&[]))
+            .await
+    }
+
+    /// Like [`PythonConfig::libs_embed`](struct.PythonConfig.html#method.libs_embed)
+    pub async fn libs_embed(&self) -> PyResult<String> {
+        self.script_args(query::LIBS, &["--embed"]).await
+    }
+
+    /// Like [`PythonConfig::ldflags`](struct.PythonConfig.html#method.ldflags)
+    pub async fn ldflags(&self) -> PyResult<String> {
+        self.script_args(query::LDFLAGS, &self.cfg.embed_args(&[]))
+            .await
+    }
+
+    /// Like [`PythonConfig::ldflags_embed`](struct.PythonConfig.html#method.ldflags_embed)
+    pub async fn ldflags_embed(&self) -> PyResult<String> {
+        self.script_args(query::LDFLAGS, &["--embed"]).await
+    }
+
+    /// Like [`PythonConfig::extension_suffix`](struct.PythonConfig.html#method.extension_suffix)
+    pub async fn extension_suffix(&self) -> Py3Only<String> {
+        self.cfg.is_py3()?;
+        self.require(Capability::ExtensionModules).await?;
+        crate::defined(
+            self.script(query::EXTENSION_SUFFIX).await?,
+            "the config var EXT_SUFFIX",
+        )
+    }
+
+    /// Like [`PythonConfig::abi_flags`](struct.PythonConfig.html#method.abi_flags)
+    pub async fn abi_flags(&self) -> Py3Only<String> {
+        self.cfg.is_py3()?;
+        self.script(query::ABI_FLAGS).await
+    }
+
+    /// Like [`PythonConfig::config_dir`](struct.PythonConfig.html#method.config_dir)
+    pub async fn config_dir(&self) -> Py3Only<String> {
+        self.cfg.is_py3()?;
+        self.require(Capability::ConfigDir).await?;
+        crate::defined(
+            self.script(query::CONFIG_DIR).await?,
+            "the config var LIBPL",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncPythonConfig;
+    use crate::{Capabilities, Capability, Error, PythonConfig, SysCommand, Version};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn same_as_blocking() {
+        let cfg = AsyncPythonConfig::new();
+        let blocking = PythonConfig::new();
+        block_on(async {
+            assert_eq!(cfg.prefix().await.unwrap(), blocking.prefix().unwrap());
+            assert_eq!(cfg.ldflags().await.unwrap(), blocking.ldflags().unwrap());
+            assert_eq!(
+                cfg.semantic_version().await.unwrap(),
+                blocking.semantic_version().unwrap()
+            );
+            assert_eq!(
+                cfg.config_var("VERSION").await.unwrap(),
+                blocking.config_var("VERSION").unwrap()
+            );
+            assert_eq!(
+                cfg.capabilities().await.unwrap(),
+                blocking.capabilities().unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn requires_capabilities() {
+        let blocking = PythonConfig::new();
+        blocking.caps.set(Capabilities::empty()).unwrap();
+        let cfg = AsyncPythonConfig::from(blocking);
+        block_on(async {
+            assert!(matches!(
+                cfg.extension_suffix().await,
+                Err(Error::Unsupported(Capability::ExtensionModules))
+            ));
+            assert!(matches!(
+                cfg.config_dir().await,
+                Err(Error::Unsupported(Capability::ConfigDir))
+            ));
+        });
+    }
+
+    #[test]
+    fn interpreter_not_found() {
+        let cmdr = SysCommand::new("this-program-does-not-exist-python");
+        let cfg = AsyncPythonConfig::from(PythonConfig::with_commander(Version::Three, cmdr));
+        match block_on(cfg.prefix()) {
+            Err(crate::Error::InterpreterNotFound { .. }) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Classifies how a Python distribution was installed
+
+use std::path::Path;
+
+/// Describes how a Python distribution was installed
+///
+/// The classification is a best guess based on the installation
+/// prefix, the interpreter's path, and the arguments used to
+/// configure the build.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InstallOrigin {
+    /// Installed by the operating system's package manager (apt, dnf, ...),
+    /// or shipped with the operating system
+    OsPackage,
+    /// Installed by Homebrew
+    Homebrew,
+    /// Installed with an installer from python.org
+    PythonOrg,
+    /// Part of a conda environment
+    Conda,
+    /// Built and installed by pyenv
+    Pyenv,
+    /// Built from source and installed by hand
+    Source,
+    /// We could not tell how this distribution was installed
+    Unknown,
+}
+
+/// The facts we need to classify an installation
+pub(crate) struct Probe<'a> {
+    /// The base installation prefix (never a virtual environment)
+    pub prefix: &'a str,
+    /// The path to the interpreter
+    pub executable: &'a str,
+    /// The `CONFIG_ARGS` config var; empty if not available
+    pub config_args: &'a str,
+    /// Whether the prefix contains a `conda-meta` directory
+    pub has_conda_meta: bool,
+}
+
+fn contains_any(haystack: &str, needles: &[&str]) -> bool {
+    needles.iter().any(|needle| haystack.contains(needle))
+}
+
+pub(crate) fn classify(probe: &Probe) -> InstallOrigin {
+    // Normalize Windows separators so that the patterns below
+    // work on any path.
+    let prefix = probe.prefix.replace('\\', "/");
+    let executable = probe.executable.replace('\\', "/");
+    let paths = [prefix.as_str(), executable.as_str()];
+    let any_path = |needles: &[&str]| paths.iter().any(|path| contains_any(path, needles));
+
+    if probe.has_conda_meta || any_path(&["/conda/", "/anaconda", "/miniconda", "/miniforge"]) {
+        InstallOrigin::Conda
+    } else if any_path(&["/.pyenv/versions/", "/pyenv/versions/"]) {
+        InstallOrigin::Pyenv
+    } else if any_path(&[
+        "/Cellar/",
+        "/opt/homebrew/",
+        "/home/linuxbrew/",
+        "/.linuxbrew/",
+    ]) {
+        InstallOrigin::Homebrew
+    } else if prefix.starts_with("/Library/Frameworks/Python.framework")
+        || any_path(&["/Programs/Python/Python", "Program Files/Python"])
+    {
+        InstallOrigin::PythonOrg
+    } else if prefix == "/usr"
+        || prefix.starts_with("/System/Library/Frameworks/")
+        || prefix.starts_with("/Library/Developer/CommandLineTools/")
+        || prefix.starts_with("/Applications/Xcode.app/")
+        || executable.contains("/WindowsApps/")
+    {
+        InstallOrigin::OsPackage
+    } else if !probe.config_args.is_empty() || Path::new(&prefix).starts_with("/usr/local") {
+        InstallOrigin::Source
+    } else {
+        InstallOrigin::Unknown
+    }
+}
+
+/// Returns the `ID` and `ID_LIKE` values of an `os-release` file,
+/// most specific first.
+fn os_release_ids(os_release: &str) -> Vec<&str> {
+    let mut ids = Vec::new();
+    for line in os_release.lines() {
+        let line = line.trim();
+        let value = if let Some(value) = line.strip_prefix("ID=") {
+            value
+        } else if let Some(value) = line.strip_prefix("ID_LIKE=") {
+            value
+        } else {
+            continue;
+        };
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        if line.starts_with("ID=") {
+            ids.insert(0, value);
+        } else {
+            ids.extend(value.split_whitespace());
+        }
+    }
+    ids
+}
+
+/// Describes what to install to get the headers and libraries for
+/// a Python distribution that came from `origin`.
+///
+/// `os_release` is the content of the host's `os-release` file, if any,
+/// and `version` is the full `X.Y.Z` version of the interpreter.
+pub(crate) fn install_hint(
+    origin: InstallOrigin,
+    os_release: Option<&str>,
+    version: &str,
+) -> String {
+    let minor = version.splitn(3, '.').take(2).collect::<Vec<_>>().join(".");
+    match origin {
+        InstallOrigin::OsPackage => {
+            let ids = os_release.map(os_release_ids).unwrap_or_default();
+            let package = ids.iter().find_map(|id| match *id {
+                "debian" | "ubuntu" => Some((format!("python{}-dev", minor), "apt install")),
+                "fedora" | "rhel" | "centos" => {
+                    Some((format!("python{}-devel", minor), "dnf install"))
+                }
+                "suse" | "opensuse" => Some(("python3-devel".to_owned(), "zypper install")),
+                "alpine" => Some(("python3-dev".to_owned(), "apk add")),
+                "arch" => Some(("python".to_owned(), "pacman -S")),
+                _ => None,
+            });
+            match package {
+                Some((package, installer)) => {
+                    format!(
+                        "install the {} package (sudo {} {})",
+                        package, installer, package
+                    )
+                }
+                None if cfg!(target_os = "macos") => {
+                    "install the Xcode command line tools (xcode-select --install)".to_owned()
+                }
+                None => format!(
+                    "install the development package for Python {} from your OS vendor",
+                    minor
+                ),
+            }
+        }
+        InstallOrigin::Homebrew => format!(
+            "reinstall Python with Homebrew (brew reinstall python@{})",
+            minor
+        ),
+        InstallOrigin::PythonOrg => format!(
+            "reinstall Python {} with the installer from python.org",
+            version
+        ),
+        InstallOrigin::Conda => format!(
+            "install Python into the conda environment (conda install python={})",
+            minor
+        ),
+        InstallOrigin::Pyenv => format!(
+            "rebuild Python with pyenv (pyenv install --force {})",
+            version
+        ),
+        InstallOrigin::Source => format!(
+            "re-run 'make install' in your Python {} source tree",
+            version
+        ),
+        InstallOrigin::Unknown => {
+            format!("install the headers and libraries for Python {}", version)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify, install_hint, InstallOrigin, Probe};
+
+    fn origin(prefix: &str, executable: &str, config_args: &str) -> InstallOrigin {
+        classify(&Probe {
+            prefix,
+            executable,
+            config_args,
+            has_conda_meta: false,
+        })
+    }
+
+    #[test]
+    fn unix_layouts() {
+        let args = "'--prefix=/usr' '--enable-shared'";
+        assert_eq!(
+            origin("/usr", "/usr/bin/python3", args),
+            InstallOrigin::OsPackage
+        );
+        assert_eq!(
+            origin(
+                "/root/.pyenv/versions/3.11.7",
+                "/root/.pyenv/versions/3.11.7/bin/python3",
+                args
+            ),
+            InstallOrigin::Pyenv
+        );
+        assert_eq!(
+            origin(
+                "/opt/homebrew/opt/python@3.12/Frameworks/Python.framework/Versions/3.12",
+                "/opt/homebrew/bin/python3",
+                args
+            ),
+            InstallOrigin::Homebrew
+        );
+        assert_eq!(
+            origin(
+                "/Library/Frameworks/Python.framework/Versions/3.12",
+                "/usr/local/bin/python3",
+                args
+            ),
+            InstallOrigin::PythonOrg
+        );
+        assert_eq!(
+            origin("/usr/local", "/usr/local/bin/python3", args),
+            InstallOrigin::Source
+        );
+    }
+
+    #[test]
+    fn conda_meta() {
+        let probe = Probe {
+            prefix: "/opt/envs/science",
+            executable: "/opt/envs/science/bin/python",
+            config_args: "",
+            has_conda_meta: true,
+        };
+        assert_eq!(classify(&probe), InstallOrigin::Conda);
+    }
+
+    #[test]
+    fn windows_layouts() {
+        assert_eq!(
+            origin(
+                r"C:\Users\me\AppData\Local\Programs\Python\Python312",
+                r"C:\Users\me\AppData\Local\Programs\Python\Python312\python.exe",
+                ""
+            ),
+            InstallOrigin::PythonOrg
+        );
+        assert_eq!(
+            origin(r"D:\tools\python", r"D:\tools\python\python.exe", ""),
+            InstallOrigin::Unknown
+        );
+    }
+
+    #[test]
+    fn distro_hints() {
+        let debian = "PRETTY_NAME=\"Debian GNU/Linux 12 (bookworm)\"\nID=debian\n";
+        assert_eq!(
+            install_hint(InstallOrigin::OsPackage, Some(debian), "3.11.2"),
+            "install the python3.11-dev package (sudo apt install python3.11-dev)"
+        );
+        let rocky = "ID=\"rocky\"\nID_LIKE=\"rhel centos fedora\"\n";
+        assert_eq!(
+            install_hint(InstallOrigin::OsPackage, Some(rocky), "3.9.18"),
+            "install the python3.9-devel package (sudo dnf install python3.9-devel)"
+        );
+        assert_eq!(
+            install_hint(InstallOrigin::Homebrew, None, "3.12.1"),
+            "reinstall Python with Homebrew (brew reinstall python@3.12)"
+        );
+    }
+}
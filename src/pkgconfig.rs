@@ -0,0 +1,137 @@
+//! pkg-config files that describe a Python distribution
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A pkg-config file, like the `python-3.11.pc` that Python installs
+///
+/// Returned by [`PythonConfig::pkg_config`](struct.PythonConfig.html#method.pkg_config).
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PkgConfigFile {
+    /// The file name, like `python-3.11.pc` or `python-3.11-embed.pc`
+    pub name: String,
+    /// The contents of the file
+    pub contents: String,
+}
+
+impl PkgConfigFile {
+    /// Writes the file into `dir`, returning the path of the file
+    ///
+    /// Point `PKG_CONFIG_PATH` at `dir` to make the file visible to
+    /// pkg-config.
+    pub fn write_to<P: AsRef<Path>>(&self, dir: P) -> io::Result<PathBuf> {
+        let path = dir.as_ref().join(&self.name);
+        fs::write(&path, &self.contents)?;
+        Ok(path)
+    }
+}
+
+/// The values that go into the pkg-config files, as defined by the
+/// config vars of the same names
+pub(crate) struct Vars<'a> {
+    pub prefix: &'a str,
+    pub exec_prefix: &'a str,
+    pub libdir: &'a str,
+    pub includedir: &'a str,
+    /// The `VERSION`, like `3.11`
+    pub version: &'a str,
+    pub abiflags: &'a str,
+    pub libs: &'a str,
+    pub libpython: &'a str,
+}
+
+/// Renders the files that Python's build installs, following
+/// `Misc/python.pc.in` and `Misc/python-embed.pc.in`
+///
+/// Python 3.8 added the `-embed` file, and stopped linking extensions
+/// against libpython. Earlier versions only have the one file, which
+/// links libpython.
+pub(crate) fn render(vars: &Vars, embed_file: bool) -> Vec<PkgConfigFile> {
+    let libpython = format!("-L${{libdir}} -lpython{}{}", vars.version, vars.abiflags);
+    let file = |name: String, description: &str, libs: &str| PkgConfigFile {
+        name,
+        contents: format!(
+            "# See: man pkg-config\n\
+             prefix={}\n\
+             exec_prefix={}\n\
+             libdir={}\n\
+             includedir={}\n\
+             \n\
+             Name: Python\n\
+             Description: {}\n\
+             Requires:\n\
+             Version: {}\n\
+             Libs.private: {}\n\
+             Libs: {}\n\
+             Cflags: -I${{includedir}}/python{}{}\n",
+            vars.prefix,
+            vars.exec_prefix,
+            vars.libdir,
+            vars.includedir,
+            description,
+            vars.version,
+            vars.libs,
+            libs,
+            vars.version,
+            vars.abiflags
+        ),
+    };
+    if embed_file {
+        vec![
+            file(
+                format!("python-{}.pc", vars.version),
+                "Build a C extension for Python",
+                vars.libpython,
+            ),
+            file(
+                format!("python-{}-embed.pc", vars.version),
+                "Embed Python into an application",
+                &libpython,
+            ),
+        ]
+    } else {
+        vec![file(
+            format!("python-{}.pc", vars.version),
+            "Python library",
+            &libpython,
+        )]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, Vars};
+
+    const VARS: Vars = Vars {
+        prefix: "/usr",
+        exec_prefix: "/usr",
+        libdir: "/usr/lib/x86_64-linux-gnu",
+        includedir: "/usr/include",
+        version: "3.11",
+        abiflags: "",
+        libs: "-ldl",
+        libpython: "",
+    };
+
+    #[test]
+    fn files() {
+        let files = render(&VARS, true);
+        let names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, ["python-3.11.pc", "python-3.11-embed.pc"]);
+        assert!(files[0].contents.contains("\nLibs: \n"));
+        assert!(files[1]
+            .contents
+            .contains("\nLibs: -L${libdir} -lpython3.11\n"));
+        assert!(files[1]
+            .contents
+            .ends_with("\nCflags: -I${includedir}/python3.11\n"));
+
+        let files = render(&VARS, false);
+        assert_eq!(files.len(), 1);
+        assert!(files[0]
+            .contents
+            .contains("\nLibs: -L${libdir} -lpython3.11\n"));
+    }
+}
@@ -0,0 +1,174 @@
+"""Prints the configuration of the running interpreter
+
+This is the probe behind `PythonConfig::probe`. Each line resembles
+`__PCRS__ name=value`, with backslashes and newlines in the value escaped
+as `\\` and `\n`. The `__PCRS__` sentinel tells our lines from warnings,
+or anything else that the interpreter prints. Config vars are named
+`config_var.NAME`. Without the sentinel, the output is the text form of a
+`PythonConfigData` snapshot.
+
+If computing a value raises, we print `__PCRS__ !name=error` instead, and
+carry on with the other values.
+
+The values match the `PythonConfig` methods of the same names. Pass
+`--msvc` to describe libraries the way the MSVC toolchain expects them.
+
+We paste the functions of `helpers.py` over the `# __PCRS__ helpers`
+line. Save `PythonConfig::probe_script`, which has them, and run it
+yourself to see what the crate sees:
+
+    python3 probe.py
+"""
+
+import os
+import sys
+import sysconfig
+
+getvar = sysconfig.get_config_var
+pyver = getvar('VERSION')
+impl = sys.implementation.name
+abiflags = getattr(sys, 'abiflags', '')
+
+linux = sys.platform.startswith('linux')
+macos = sys.platform == 'darwin'
+windows = os.name == 'nt'
+msvc = '--msvc' in sys.argv[1:]
+
+# __PCRS__ helpers
+
+
+def escape(value):
+    return value.replace('\\', '\\\\').replace('\n', '\\n')
+
+
+def emit(name, value):
+    print('__PCRS__ ' + name + '=' + escape(value))
+
+
+def field(name, compute):
+    """Emits the value returned by `compute`, or the error that it raised"""
+    try:
+        value = str(compute()).strip()
+    except Exception as err:
+        print('__PCRS__ !' + name + '=' + escape('%s: %s' % (type(err).__name__, err)))
+    else:
+        emit(name, value)
+
+
+def version():
+    v = sys.version_info
+    pre = {'alpha': 'a', 'beta': 'b', 'candidate': 'rc'}.get(v[3])
+    return '%d.%d.%d' % v[:3] + (pre + str(v[4]) if pre else '')
+
+
+def includes():
+    return [
+        '-I' + sysconfig.get_path('include'),
+        '-I' + sysconfig.get_path('platinclude'),
+    ]
+
+
+def cflags():
+    flags = includes()
+    if linux:
+        flags.extend(getvar('BASECFLAGS').split())
+        flags.extend(getvar('CONFIGURE_CFLAGS').split())
+    elif macos:
+        flags.extend(getvar('CFLAGS').split())
+    return flags
+
+
+def libpython():
+    """Extensions only link libpython before Python 3.8"""
+    if sys.version_info < (3, 8):
+        return ['-l' + library_name()]
+    return [getvar('LIBPYTHON')] if getvar('LIBPYTHON') else []
+
+
+def libs():
+    if windows:
+        if msvc:
+            return [library_name() + '.lib']
+        return ['-l' + library_name()]
+    flags = libpython()
+    flags += (getvar('LIBS') or '').split()
+    flags += (getvar('SYSLIBS') or '').split()
+    return flags
+
+
+def is_framework():
+    """macOS framework builds link libpython as a framework"""
+    return bool(macos and impl == 'cpython' and getvar('PYTHONFRAMEWORK')
+                and getvar('PYTHONFRAMEWORKPREFIX'))
+
+
+def ldflags():
+    if windows:
+        libdir = os.path.join(sys.base_exec_prefix, 'libs')
+        if msvc:
+            return ['/LIBPATH:' + libdir, library_name() + '.lib']
+        return ['-L' + libdir, '-l' + library_name()]
+    linked = sys.version_info < (3, 8)
+    framework = linked and is_framework()
+    if framework:
+        flags = ['-F' + getvar('PYTHONFRAMEWORKPREFIX'), '-framework',
+                 getvar('PYTHONFRAMEWORK')]
+    else:
+        flags = libpython()
+    if linux:
+        flags.insert(0, '-L' + sys.base_exec_prefix + '/lib')
+    libdir = getvar('LIBDIR')
+    if impl == 'pypy' and libdir and '-L' + libdir not in flags:
+        flags.insert(0, '-L' + libdir)
+    flags += (getvar('LIBS') or '').split()
+    flags += (getvar('SYSLIBS') or '').split()
+    if not getvar('Py_ENABLE_SHARED') and not framework and getvar('LIBPL'):
+        flags.insert(0, '-L' + getvar('LIBPL'))
+    if not getvar('PYTHONFRAMEWORK'):
+        flags.extend((getvar('LINKFORSHARED') or '').split())
+    if linked and is_conda():
+        # Conda only installs a shared libpython
+        libdir = os.path.join(os.path.realpath(sys.prefix), 'lib')
+        if '-L' + libdir not in flags:
+            flags.insert(0, '-L' + libdir)
+        flags.append('-Wl,-rpath,' + libdir)
+    return flags
+
+
+def libc():
+    """glibc reports its version. musl's dynamic loader prints its version"""
+    if not linux:
+        return ''
+    try:
+        name, ver = os.confstr('CS_GNU_LIBC_VERSION').split()
+        return 'glibc ' + ver
+    except (AttributeError, OSError, ValueError):
+        pass
+    import glob
+    import subprocess
+    for loader in glob.glob('/lib/ld-musl-*.so.1'):
+        proc = subprocess.Popen([loader], stdout=subprocess.PIPE,
+                                stderr=subprocess.PIPE,
+                                universal_newlines=True)
+        for line in proc.communicate()[1].splitlines():
+            if line.startswith('Version '):
+                return 'musl ' + line.split()[1]
+    return ''
+
+
+field('version', version)
+field('executable', lambda: sys.executable)
+field('prefix', lambda: getvar('prefix'))
+field('exec_prefix', lambda: getvar('exec_prefix'))
+field('includes', lambda: ' '.join(includes()))
+field('cflags', lambda: ' '.join(cflags()))
+field('libs', lambda: ' '.join(libs()))
+field('ldflags', lambda: ' '.join(ldflags()))
+# PyPy 2 only defines the older `SO`
+field('extension_suffix', lambda: getvar('EXT_SUFFIX') or getvar('SO'))
+field('abi_flags', lambda: abiflags)
+field('config_dir', lambda: getvar('LIBPL'))
+field('libc', libc)
+for name, value in sorted(sysconfig.get_config_vars().items()):
+    if value is not None:
+        emit('config_var.' + name, str(value))
@@ -0,0 +1,416 @@
+//! Python's `sys.version_info`, as a Rust type
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// The release level of a Python version
+///
+/// Levels are ordered from earliest to latest, so that
+/// `Alpha < Beta < Candidate < Final`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReleaseLevel {
+    /// An alpha release, like `3.13.0a1`
+    Alpha,
+    /// A beta release, like `3.13.0b1`
+    Beta,
+    /// A release candidate, like `3.13.0rc2`
+    Candidate,
+    /// A final release, like `3.13.0`
+    Final,
+}
+
+impl ReleaseLevel {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "alpha" => Some(ReleaseLevel::Alpha),
+            "beta" => Some(ReleaseLevel::Beta),
+            "candidate" => Some(ReleaseLevel::Candidate),
+            "final" => Some(ReleaseLevel::Final),
+            _ => None,
+        }
+    }
+
+    /// The short suffix used in version strings, like `rc` for
+    /// a release candidate. Empty for a final release.
+    fn suffix(self) -> &'static str {
+        match self {
+            ReleaseLevel::Alpha => "a",
+            ReleaseLevel::Beta => "b",
+            ReleaseLevel::Candidate => "rc",
+            ReleaseLevel::Final => "",
+        }
+    }
+}
+
+/// A Python version, equivalent to `sys.version_info`
+///
+/// Unlike a `semver::Version`, a `PyVersion` knows about Python's
+/// release levels and serial numbers. Versions are ordered the same
+/// way that Python orders `sys.version_info`, which makes them
+/// useful for feature gating:
+///
+/// ```
+/// use python_config::{PyVersion, ReleaseLevel};
+///
+/// let ver = PyVersion {
+///     major: 3,
+///     minor: 13,
+///     micro: 0,
+///     releaselevel: ReleaseLevel::Candidate,
+///     serial: 2,
+/// };
+/// assert!(ver.at_least(3, 8));
+/// assert_eq!(ver.to_string(), "3.13.0rc2");
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PyVersion {
+    /// The major version, like `3`
+    pub major: u64,
+    /// The minor version, like `11`
+    pub minor: u64,
+    /// The micro (patch) version, like `7`
+    pub micro: u64,
+    /// The release level
+    pub releaselevel: ReleaseLevel,
+    /// The serial number of a pre-release, like the `2` in `3.13.0rc2`
+    pub serial: u64,
+}
+
+impl PyVersion {
+    /// Returns `true` if this version is at least `major.minor`
+    pub fn at_least(&self, major: u64, minor: u64) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+}
+
+impl fmt::Display for PyVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.micro)?;
+        if self.releaselevel != ReleaseLevel::Final {
+            write!(f, "{}{}", self.releaselevel.suffix(), self.serial)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a version as formatted by `Display`, like `3.11.7` or `3.13.0rc2`
+impl FromStr for PyVersion {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || crate::Error::ParseError {
+            expected: "expected a Python version",
+            output: s.to_owned(),
+        };
+        let mut parts = s.splitn(3, '.');
+        let major = parts.next().and_then(|p| p.parse().ok()).ok_or_else(err)?;
+        let minor = parts.next().and_then(|p| p.parse().ok()).ok_or_else(err)?;
+        let rest = parts.next().ok_or_else(err)?;
+        let split = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let micro = rest[..split].parse().map_err(|_| err())?;
+        let (releaselevel, serial) = match &rest[split..] {
+            "" => (ReleaseLevel::Final, 0),
+            pre => {
+                let level = [
+                    ReleaseLevel::Candidate,
+                    ReleaseLevel::Alpha,
+                    ReleaseLevel::Beta,
+                ]
+                .iter()
+                .find(|level| pre.starts_with(level.suffix()))
+                .ok_or_else(err)?;
+                let serial = pre[level.suffix().len()..].parse().map_err(|_| err())?;
+                (*level, serial)
+            }
+        };
+        Ok(PyVersion {
+            major,
+            minor,
+            micro,
+            releaselevel,
+            serial,
+        })
+    }
+}
+
+/// Pre-releases are represented as semver pre-release identifiers,
+/// so `3.13.0rc2` becomes `3.13.0-rc.2`.
+impl From<PyVersion> for semver::Version {
+    fn from(ver: PyVersion) -> Self {
+        let pre = if ver.releaselevel == ReleaseLevel::Final {
+            Vec::new()
+        } else {
+            vec![
+                semver::Identifier::AlphaNumeric(ver.releaselevel.suffix().to_owned()),
+                semver::Identifier::Numeric(ver.serial),
+            ]
+        };
+        semver::Version {
+            major: ver.major,
+            minor: ver.minor,
+            patch: ver.micro,
+            pre,
+            build: Vec::new(),
+        }
+    }
+}
+
+/// A Python version as printed by `python --version`, like `3.11.7`,
+/// `3.13.0rc2`, or `3.8.10+`
+///
+/// Parsing is lenient: a missing patch version is `0`, and anything
+/// after the numbers is kept in the `suffix`. Versions are ordered by
+/// their numbers, then pre-releases (`a`, `b`, and `rc` suffixes) come
+/// before the final release, and other suffixes, like the `+` of a
+/// development build, come after.
+///
+/// ```
+/// use python_config::PythonVersion;
+///
+/// let rc: PythonVersion = "3.13.0rc2".parse().unwrap();
+/// let dev: PythonVersion = "3.13.0+".parse().unwrap();
+/// assert!(rc < "3.13.0".parse().unwrap());
+/// assert!(dev > "3.13.0".parse().unwrap());
+/// assert_eq!("3.12".parse::<PythonVersion>().unwrap().patch, 0);
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PythonVersion {
+    /// The major version, like `3`
+    pub major: u64,
+    /// The minor version, like `11`
+    pub minor: u64,
+    /// The patch version, like `7`, or `0` if the version didn't have one
+    pub patch: u64,
+    /// Everything after the numbers, like `rc2` or `+`. Empty for a
+    /// final release.
+    pub suffix: String,
+}
+
+impl PythonVersion {
+    /// Returns the release level and serial of a pre-release suffix,
+    /// like `rc2`, or `None` for any other suffix
+    fn pre_release(&self) -> Option<(ReleaseLevel, u64)> {
+        [
+            ReleaseLevel::Candidate,
+            ReleaseLevel::Alpha,
+            ReleaseLevel::Beta,
+        ]
+        .iter()
+        .filter_map(|&level| {
+            let serial = self.suffix.strip_prefix(level.suffix())?.parse().ok()?;
+            Some((level, serial))
+        })
+        .next()
+    }
+
+    /// Orders the suffix: pre-releases, then final releases, then
+    /// everything else
+    fn suffix_key(&self) -> (ReleaseLevel, u64, &str) {
+        match self.pre_release() {
+            Some((level, serial)) => (level, serial, &self.suffix),
+            None => (ReleaseLevel::Final, 0, &self.suffix),
+        }
+    }
+}
+
+impl Ord for PythonVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch, self.suffix_key()).cmp(&(
+            other.major,
+            other.minor,
+            other.patch,
+            other.suffix_key(),
+        ))
+    }
+}
+
+impl PartialOrd for PythonVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for PythonVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}{}",
+            self.major, self.minor, self.patch, self.suffix
+        )
+    }
+}
+
+/// Parses a version like `3.11.7`, `3.12`, `3.13.0rc2`, or `3.8.10+`
+impl FromStr for PythonVersion {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || crate::Error::ParseError {
+            expected: "expected a Python version",
+            output: s.to_owned(),
+        };
+        // Splits the leading number from the rest of `text`
+        let number = |text: &str| -> Option<(u64, usize)> {
+            let end = text
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(text.len());
+            text[..end].parse().ok().map(|number| (number, end))
+        };
+        let (major, end) = number(s).ok_or_else(err)?;
+        let rest = s[end..].strip_prefix('.').ok_or_else(err)?;
+        let (minor, end) = number(rest).ok_or_else(err)?;
+        let mut rest = &rest[end..];
+        let mut patch = 0;
+        if let Some((number, end)) = rest.strip_prefix('.').and_then(number) {
+            patch = number;
+            rest = &rest[1 + end..];
+        }
+        Ok(PythonVersion {
+            major,
+            minor,
+            patch,
+            suffix: rest.to_owned(),
+        })
+    }
+}
+
+/// Pre-releases are represented as semver pre-release identifiers, so
+/// `3.13.0rc2` becomes `3.13.0-rc.2`. Other suffixes become build
+/// metadata, if they can, so `3.8.10+` becomes `3.8.10`.
+impl From<PythonVersion> for semver::Version {
+    fn from(ver: PythonVersion) -> Self {
+        let mut pre = Vec::new();
+        let mut build = Vec::new();
+        if let Some((level, serial)) = ver.pre_release() {
+            pre.push(semver::Identifier::AlphaNumeric(level.suffix().to_owned()));
+            pre.push(semver::Identifier::Numeric(serial));
+        } else {
+            let metadata = ver.suffix.trim_start_matches(['+', '-']);
+            if !metadata.is_empty()
+                && metadata.split('.').all(|id| {
+                    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                })
+            {
+                build = metadata
+                    .split('.')
+                    .map(|id| semver::Identifier::AlphaNumeric(id.to_owned()))
+                    .collect();
+            }
+        }
+        semver::Version {
+            major: ver.major,
+            minor: ver.minor,
+            patch: ver.patch,
+            pre,
+            build,
+        }
+    }
+}
+
+/// Parses the space-separated fields of `sys.version_info`,
+/// like `3 11 7 final 0`.
+pub(crate) fn parse(resp: &str) -> Option<PyVersion> {
+    let mut fields = resp.split_whitespace();
+    let major = fields.next()?.parse().ok()?;
+    let minor = fields.next()?.parse().ok()?;
+    let micro = fields.next()?.parse().ok()?;
+    let releaselevel = ReleaseLevel::from_name(fields.next()?)?;
+    let serial = fields.next()?.parse().ok()?;
+    Some(PyVersion {
+        major,
+        minor,
+        micro,
+        releaselevel,
+        serial,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, PyVersion, PythonVersion, ReleaseLevel};
+
+    #[test]
+    fn parse_final() {
+        let ver = parse("3 11 7 final 0").unwrap();
+        assert_eq!((ver.major, ver.minor, ver.micro), (3, 11, 7));
+        assert_eq!(ver.releaselevel, ReleaseLevel::Final);
+        assert_eq!(semver::Version::from(ver).to_string(), "3.11.7");
+    }
+
+    #[test]
+    fn parse_candidate() {
+        let ver = parse("3 13 0 candidate 2").unwrap();
+        assert_eq!(ver.to_string(), "3.13.0rc2");
+        assert!(ver < parse("3 13 0 final 0").unwrap());
+        assert_eq!(semver::Version::from(ver).to_string(), "3.13.0-rc.2");
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for ver in &["3.11.7", "3.13.0rc2", "3.14.0a1", "2.7.18"] {
+            assert_eq!(ver.parse::<PyVersion>().unwrap().to_string(), *ver);
+        }
+        assert!("3.11".parse::<PyVersion>().is_err());
+        assert!("3.11.0x1".parse::<PyVersion>().is_err());
+    }
+
+    #[test]
+    fn parse_garbage() {
+        assert!(parse("3 11").is_none());
+        assert!(parse("3 11 7 gamma 0").is_none());
+    }
+
+    #[test]
+    fn lenient_versions() {
+        let parse = |text: &str| text.parse::<PythonVersion>().unwrap();
+        assert_eq!(
+            parse("3.8.10+"),
+            PythonVersion {
+                major: 3,
+                minor: 8,
+                patch: 10,
+                suffix: "+".to_owned()
+            }
+        );
+        assert_eq!(parse("3.12").to_string(), "3.12.0");
+        assert_eq!(parse("3.12rc1").suffix, "rc1");
+        assert_eq!(parse("3.13.0rc2").to_string(), "3.13.0rc2");
+        assert!("3".parse::<PythonVersion>().is_err());
+        assert!("three.twelve".parse::<PythonVersion>().is_err());
+
+        let mut versions = [
+            parse("3.13.0+"),
+            parse("3.13.0"),
+            parse("3.13.0rc2"),
+            parse("3.12.4"),
+            parse("3.13.0a1"),
+            parse("3.13.0rc10"),
+        ];
+        versions.sort();
+        let sorted: Vec<String> = versions.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            sorted,
+            [
+                "3.12.4",
+                "3.13.0a1",
+                "3.13.0rc2",
+                "3.13.0rc10",
+                "3.13.0",
+                "3.13.0+"
+            ]
+        );
+    }
+
+    #[test]
+    fn lenient_semver() {
+        let semver = |text: &str| semver::Version::from(text.parse::<PythonVersion>().unwrap());
+        assert_eq!(semver("3.13.0rc2").to_string(), "3.13.0-rc.2");
+        assert_eq!(semver("3.8.10+").to_string(), "3.8.10");
+        assert_eq!(semver("3.12.1+chromium.1").to_string(), "3.12.1+chromium.1");
+        assert_eq!(semver("3.12").to_string(), "3.12.0");
+    }
+}
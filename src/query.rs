@@ -0,0 +1,600 @@
+//! The scripts behind the `PythonConfig` queries
+//!
+//! Each script prints its response. Keeping the scripts here, rather
+//! than inline in each method, lets a snapshot run several of them in a
+//! single interpreter invocation.
+//!
+//! What an interpreter prints isn't only ours: `sitecustomize`, `.pth`
+//! files, and warnings can print, too. So every line that we rely on
+//! starts with `SENTINEL`. Single queries print between the `BEGIN` and
+//! `END` lines, and snapshots print `__PCRS__ name=value` lines.
+
+use std::sync::OnceLock;
+
+/// Starts the lines that our scripts print
+pub const SENTINEL: &str = "__PCRS__";
+
+/// Printed before the lines of a query run
+pub const BEGIN: &str = "__PCRS__ begin";
+
+/// Printed after the lines of a query run
+pub const END: &str = "__PCRS__ end";
+
+/// Functions that every query script can call, run after the prelude
+pub const HELPERS: &str = include_str!("helpers.py");
+
+pub const VERSION_INFO: &[&str] = &[
+    "import sys",
+    "print(' '.join(str(v) for v in sys.version_info[:5]))",
+];
+
+pub const EXECUTABLE: &[&str] = &["import sys", "print(sys.executable)"];
+
+pub const PREFIX: &[&str] = &["print(getvar('prefix'))"];
+
+pub const EXEC_PREFIX: &[&str] = &["print(getvar('exec_prefix'))"];
+
+// Python 2 virtualenvs record the base installation in `real_prefix`.
+pub const BASE_PREFIX: &[&str] = &[
+    "import sys",
+    "print(getattr(sys, 'real_prefix', None) or getattr(sys, 'base_prefix', sys.prefix))",
+];
+
+pub const IS_VIRTUALENV: &[&str] = &[
+    "import sys",
+    "base = getattr(sys, 'real_prefix', None) or getattr(sys, 'base_prefix', sys.prefix)",
+    "print(base != sys.prefix)",
+];
+
+// Distinguishes an empty value from an undefined one.
+pub const CONFIG_VAR: &[&str] = &[
+    "import sys",
+    "value = getvar(sys.argv[1])",
+    "if value is None:",
+    tab!("print('0')"),
+    "else:",
+    tab!("print('1')"),
+    tab!("print(value)"),
+];
+
+// `python -c` puts the current directory first on `sys.path`, as an
+// empty string. It's not part of the interpreter's search path.
+pub const SYS_PATH: &[&str] = &[
+    "import sys",
+    "for entry in sys.path:",
+    tab!("if entry:"),
+    tab!(tab!("print(entry)")),
+];
+
+pub const INCLUDES: &[&str] = &[
+    "flags = ['-I' + sysconfig.get_path('include'), '-I' + sysconfig.get_path('platinclude')]",
+    "print(' '.join(flags))",
+];
+
+pub const INCLUDE_PATHS: &[&str] = &[
+    "print(sysconfig.get_path('include'))",
+    "print(sysconfig.get_path('platinclude'))",
+];
+
+// Called with `--lines`, we print one flag per line, and split the config
+// vars like a shell would, so that include paths with spaces and quoted
+// flags stay whole.
+pub const CFLAGS: &[&str] = &[
+    "import shlex",
+    "lines = '--lines' in sys.argv[1:]",
+    "split = shlex.split if lines else str.split",
+    "flags = ['-I' + sysconfig.get_path('include'), '-I' + sysconfig.get_path('platinclude')]",
+    "if linux:",
+    tab!("flags.extend(split(getvar('BASECFLAGS')))"),
+    tab!("flags.extend(split(getvar('CONFIGURE_CFLAGS')))"),
+    "elif macos:",
+    tab!("flags.extend(split(getvar('CFLAGS')))"),
+    "print(('\\n' if lines else ' ').join(flags))",
+];
+
+// Starting with 3.8, libpython is only linked when the script is
+// called with `--embed`. Called with `--abi3`, we link the stable ABI
+// library, `python3`, instead. See `library_name` in `helpers.py`.
+//
+// Windows doesn't define most of the config vars, and extensions always
+// link against the library. We emit MinGW-style flags, or library names
+// for MSVC.
+pub const LIBS: &[&str] = &[
+    "import sys",
+    "abi3 = '--abi3' in sys.argv[1:]",
+    "libname = library_name(abi3)",
+    "if abi3 or '--embed' in sys.argv[1:] or sys.version_info < (3, 8):",
+    tab!("libs = ['-l' + libname]"),
+    "else:",
+    tab!("libs = [getvar('LIBPYTHON')] if getvar('LIBPYTHON') else []"),
+    "libs += (getvar('LIBS') or '').split()",
+    "libs += (getvar('SYSLIBS') or '').split()",
+    "if windows:",
+    tab!("libs = ['-l' + libname]"),
+    msvc_line!(tab!("libs = [libs[0][2:] + '.lib']")),
+    "print(' '.join(libs))",
+];
+
+// The import libraries live in `<prefix>\libs` on Windows. PyPy keeps its
+// library in `LIBDIR`, which isn't always `<prefix>/lib`.
+//
+// Called with `--lines`, we print one argument per line, so that
+// arguments containing spaces stay whole. The config vars are split
+// like a shell would split them, so quoted arguments stay whole, too.
+//
+// `--static` and `--shared` link libpython in that mode, rather than the
+// way the distribution was built. A static libpython lives in `LIBPL`, and
+// needs `LINKFORSHARED` so that extension modules can find its symbols in
+// the executable. We name the archive by its path, since many
+// distributions keep the shared library next to it, and `-l` prefers the
+// shared library. A framework build's archive only links to the
+// framework, so we keep `-l` there, like upstream. A shared libpython
+// lives in `LIBDIR`.
+//
+// Otherwise, macOS framework builds link libpython as a framework, found
+// in `PYTHONFRAMEWORKPREFIX`.
+//
+// Conda doesn't install a static libpython, even though it doesn't define
+// `Py_ENABLE_SHARED`. When we link libpython from a conda environment, we
+// search the environment's `lib` directory, and add it to the rpath, so
+// that the application finds libpython without activating the environment.
+//
+// `--rpath` adds `LIBDIR` to the rpath, when we link a shared libpython
+// on a platform that has an rpath. Framework builds and conda already
+// record where to find libpython.
+pub const LDFLAGS: &[&str] = &[
+    "import shlex, sys",
+    "split = shlex.split if '--lines' in sys.argv[1:] else str.split",
+    "impl = sys.implementation.name if hasattr(sys, 'implementation') else 'cpython'",
+    "mode = 'static' if '--static' in sys.argv[1:] else 'shared' if '--shared' in sys.argv[1:] else None",
+    "libname = library_name()",
+    "framework = macos and not mode and impl == 'cpython' and getvar('PYTHONFRAMEWORK') and getvar('PYTHONFRAMEWORKPREFIX')",
+    "linked = bool(mode or '--embed' in sys.argv[1:] or sys.version_info < (3, 8))",
+    "if linked:",
+    tab!("libs = ['-l' + libname]"),
+    tab!("if framework:"),
+    tab!(tab!("libs = ['-F' + getvar('PYTHONFRAMEWORKPREFIX'), '-framework', getvar('PYTHONFRAMEWORK')]")),
+    "else:",
+    tab!("libs = [getvar('LIBPYTHON')] if getvar('LIBPYTHON') else []"),
+    tab!("framework = False"),
+    "if linux and mode != 'static':",
+    tab!("libs.insert(0, '-L' + getattr(sys, 'base_exec_prefix', getvar('exec_prefix')) + '/lib')"),
+    "if (impl == 'pypy' or mode == 'shared') and getvar('LIBDIR') and '-L' + getvar('LIBDIR') not in libs:",
+    tab!("libs.insert(0, '-L' + getvar('LIBDIR'))"),
+    "libs += split(getvar('LIBS') or '')",
+    "libs += split(getvar('SYSLIBS') or '')",
+    "static = mode == 'static' or (mode is None and not getvar('Py_ENABLE_SHARED'))",
+    "if static and not framework and getvar('LIBPL'):",
+    tab!("libs.insert(0, '-L' + getvar('LIBPL'))"),
+    "archive = os.path.join(getvar('LIBPL') or '', getvar('LIBRARY') or '')",
+    "if mode == 'static' and not getvar('PYTHONFRAMEWORK') and getvar('LIBRARY') and os.path.isfile(archive):",
+    tab!("libs = [archive if lib == '-l' + libname else lib for lib in libs]"),
+    "if not getvar('PYTHONFRAMEWORK') and mode != 'shared':",
+    tab!("libs.extend(split(getvar('LINKFORSHARED') or ''))"),
+    "conda = is_conda()",
+    "if conda and linked and mode != 'static' and not windows:",
+    tab!("libdir = os.path.join(os.path.realpath(sys.prefix), 'lib')"),
+    tab!("if '-L' + libdir not in libs:"),
+    tab!(tab!("libs.insert(0, '-L' + libdir)")),
+    tab!("libs.append('-Wl,-rpath,' + libdir)"),
+    "if '--rpath' in sys.argv[1:] and linked and not static and not framework and not conda and not windows and getvar('LIBDIR'):",
+    tab!("rpath = '-Wl,-rpath,' + getvar('LIBDIR')"),
+    tab!("if rpath not in libs:"),
+    tab!(tab!("libs.append(rpath)")),
+    "if windows:",
+    tab!("libdir = os.path.join(getattr(sys, 'base_exec_prefix', sys.exec_prefix), 'libs')"),
+    tab!("libs = ['-L' + libdir, '-l' + libname]"),
+    msvc_line!(tab!("libs = ['/LIBPATH:' + libdir, libs[1][2:] + '.lib']")),
+    "print(('\\n' if '--lines' in sys.argv[1:] else ' ').join(libs))",
+];
+
+// Whether the development files exist: `Python.h`, libpython, and the
+// `pythonX.Y-config` script that OS packagers put in the dev package. One
+// value per line.
+pub const DEV_FILES: &[&str] = &[
+    "include = sysconfig.get_path('include')",
+    "print(include)",
+    "print(os.path.exists(os.path.join(include, 'Python.h')))",
+    "libdir = getvar('LIBDIR') or ''",
+    "dirs = [libdir, os.path.join(libdir, getvar('MULTIARCH') or ''), getvar('LIBPL') or '']",
+    "names = [name for name in (getvar('LDLIBRARY'), getvar('LIBRARY')) if name]",
+    "print(not names or any(os.path.exists(os.path.join(d, n)) for d in dirs for n in names))",
+    "script = os.path.join(getvar('BINDIR'), 'python' + pyver + '-config') if getvar('BINDIR') else ''",
+    "print(script)",
+    "print(bool(script) and os.path.exists(script))",
+    "print('.'.join(str(v) for v in sys.version_info[:3]))",
+];
+
+// Non-framework builds define `PYTHONFRAMEWORK` as an empty string.
+pub const FRAMEWORK: &[&str] = &[
+    "if getvar('PYTHONFRAMEWORK'):",
+    tab!("print(getvar('PYTHONFRAMEWORK'))"),
+    tab!("print(getvar('PYTHONFRAMEWORKPREFIX'))"),
+];
+
+// Prints the first candidate that exists, or nothing.
+//
+// Framework builds record the library relative to `PYTHONFRAMEWORKPREFIX`.
+// Debian keeps the library in a `MULTIARCH` subdirectory of `LIBDIR`, and
+// PyPy keeps its library next to the executable. Conda doesn't define
+// `Py_ENABLE_SHARED`, so `LDLIBRARY` names the static library, but conda
+// ships the shared library anyway. Windows builds have no config vars; the
+// DLL lives next to `python.exe` in the base installation.
+pub const LIBPYTHON_PATH: &[&str] = &[
+    "import os, sys",
+    "impl = sys.implementation.name if hasattr(sys, 'implementation') else 'cpython'",
+    "libdir = getvar('LIBDIR') or ''",
+    "ldlibrary = getvar('LDLIBRARY') or ''",
+    "candidates = []",
+    "if getvar('PYTHONFRAMEWORKPREFIX') and ldlibrary:",
+    tab!("candidates.append(os.path.join(getvar('PYTHONFRAMEWORKPREFIX'), ldlibrary))"),
+    "if impl == 'pypy':",
+    tab!("stem = 'libpypy%d.%d-c' % sys.version_info[:2] if sys.version_info >= (3, 9) else 'libpypy3-c'"),
+    tab!("names = [stem + '.so', stem + '.dylib']"),
+    "else:",
+    tab!("stem = 'libpython' + (getvar('LDVERSION') or pyver + getattr(sys, 'abiflags', ''))"),
+    tab!("names = [ldlibrary, getvar('INSTSONAME') or '', stem + '.so', stem + '.dylib']"),
+    "dirs = [libdir, os.path.join(libdir, getvar('MULTIARCH') or ''), os.path.dirname(os.path.realpath(sys.executable))]",
+    "if windows:",
+    tab!("base = getattr(sys, 'base_prefix', sys.prefix)"),
+    tab!("dirs = [os.path.dirname(sys.executable), base, os.path.join(base, 'DLLs')]"),
+    tab!("names = ['python%d%d%s%s.dll' % (sys.version_info[0], sys.version_info[1], 't' if getvar('Py_GIL_DISABLED') else '', '_d' if hasattr(sys, 'gettotalrefcount') else '')]"),
+    "candidates += [os.path.join(d, n) for d in dirs for n in names if d and n and not n.endswith('.a')]",
+    "print(next((c for c in candidates if os.path.isfile(c)), ''))",
+];
+
+// See `is_conda` in `helpers.py`.
+pub const IS_CONDA: &[&str] = &["print(is_conda())"];
+
+// Windows builds don't define `Py_ENABLE_SHARED`, but always have a DLL.
+// So does PyPy.
+pub const IS_SHARED: &[&str] = &[
+    "import sys",
+    "pypy = getattr(sys, 'pypy_version_info', None) is not None",
+    "print(windows or pypy or bool(getvar('Py_ENABLE_SHARED')))",
+];
+
+// Free-threaded builds define `Py_GIL_DISABLED` and add `t` to the ABI
+// flags, starting with 3.13.
+pub const GIL_DISABLED: &[&str] = &[
+    "import sys",
+    "print(bool(getvar('Py_GIL_DISABLED')) or 't' in getattr(sys, 'abiflags', ''))",
+];
+
+// Windows doesn't define `Py_DEBUG`, but only debug builds have
+// `sys.gettotalrefcount`.
+pub const IS_DEBUG_BUILD: &[&str] = &[
+    "import sys",
+    "debug = getvar('Py_DEBUG')",
+    "print(bool(debug) if debug is not None else hasattr(sys, 'gettotalrefcount'))",
+];
+
+// Prints the name of each ABI-affecting option that's set. Windows
+// doesn't define the config vars, so we look for the functions that the
+// options add to `sys`, like PyO3 does. Python 3.8 made `Py_DEBUG` imply
+// `Py_REF_DEBUG`, and Windows builds use pymalloc.
+pub const BUILD_FLAGS: &[&str] = &[
+    "import sys",
+    "debug = getvar('Py_DEBUG')",
+    "debug = bool(debug) if debug is not None else hasattr(sys, 'gettotalrefcount')",
+    "trace_refs = getvar('Py_TRACE_REFS')",
+    "pymalloc = getvar('WITH_PYMALLOC')",
+    "flags = [",
+    tab!("('Py_DEBUG', debug),"),
+    tab!("('Py_REF_DEBUG', getvar('Py_REF_DEBUG') or (debug and sys.version_info >= (3, 8)) or hasattr(sys, 'gettotalrefcount')),"),
+    tab!("('Py_TRACE_REFS', bool(trace_refs) if trace_refs is not None else hasattr(sys, 'getobjects')),"),
+    tab!("('COUNT_ALLOCS', getvar('COUNT_ALLOCS')),"),
+    tab!("('WITH_PYMALLOC', bool(pymalloc) if pymalloc is not None else windows),"),
+    tab!("('Py_GIL_DISABLED', getvar('Py_GIL_DISABLED') or 't' in getattr(sys, 'abiflags', '')),"),
+    "]",
+    "for name, value in flags:",
+    tab!("if value:"),
+    tab!(tab!("print(name)")),
+];
+
+// Windows extensions are always `.pyd` files.
+pub const ABI3_EXTENSION_SUFFIX: &[&str] = &[
+    "import sys",
+    "if sys.platform == 'win32':",
+    tab!("print('.pyd')"),
+    "else:",
+    tab!("print('.abi3' + (getvar('SHLIB_SUFFIX') or '.so'))"),
+];
+
+// Free-threaded builds add a `t` ABI flag after the version in
+// `EXT_SUFFIX`, like `.cpython-313t-darwin.so`, or `.cp313t-win_amd64.pyd`.
+// Prints nothing if this interpreter has no free-threaded build.
+pub const FREE_THREADED_EXTENSION_SUFFIX: &[&str] = &[
+    "import sys",
+    "suffix = getvar('EXT_SUFFIX') or ''",
+    "nodot = getvar('py_version_nodot') or ''",
+    "flags = getattr(sys, 'abiflags', '')",
+    "if getvar('Py_GIL_DISABLED'):",
+    tab!("print(suffix)"),
+    "elif sys.implementation.name == 'cpython' and sys.version_info >= (3, 13) and nodot + flags + '-' in suffix:",
+    tab!("print(suffix.replace(nodot + flags + '-', nodot + 't' + flags + '-', 1))"),
+    "else:",
+    tab!("print('')"),
+];
+
+// Called with the major and minor version that an extension targets.
+// Free-threaded builds don't support the limited API.
+pub const SUPPORTS_LIMITED_API: &[&str] = &[
+    "import sys",
+    "target = (int(sys.argv[1]), int(sys.argv[2]))",
+    "print(sys.implementation.name == 'cpython' and not getvar('Py_GIL_DISABLED') and sys.version_info[:2] >= target)",
+];
+
+// One value per line. `VERSION` comes last so that the output never ends
+// with an empty line.
+pub const PKG_CONFIG: &[&str] = &[
+    "import sys",
+    "for name in ('prefix', 'exec_prefix', 'LIBDIR', 'INCLUDEDIR', 'LIBS', 'LIBPYTHON'):",
+    tab!("print(getvar(name) or '')"),
+    "print(getattr(sys, 'abiflags', ''))",
+    "print(pyver)",
+];
+
+// The symbol that initializes the extension module named by `sys.argv[1]`,
+// then the flags that link a loadable module, one per line. Per PEP 489,
+// names that aren't ASCII use `PyInitU_` and punycode. The module is the
+// last part of a dotted name.
+//
+// macOS extension modules don't link libpython, so they look its symbols
+// up at load time. MSVC only exports what it's asked to export; GCC and
+// Clang export every public symbol.
+pub const EXPORTS: &[&str] = &[
+    "import sys",
+    "module = sys.argv[1].split('.')[-1]",
+    "if sys.version_info < (3,):",
+    tab!("symbol = 'init' + module"),
+    "elif all(ord(c) < 128 for c in module):",
+    tab!("symbol = 'PyInit_' + module"),
+    "else:",
+    tab!("symbol = 'PyInitU_' + module.encode('punycode').decode('ascii').replace('-', '_')"),
+    "print(symbol)",
+    "if macos:",
+    tab!("print('-undefined')"),
+    tab!("print('dynamic_lookup')"),
+    msvc_line!("if windows:"),
+    msvc_line!(tab!("print('/EXPORT:' + symbol)")),
+];
+
+// PyPy 2 only defines the older `SO`.
+pub const EXTENSION_SUFFIX: &[&str] = &["print(getvar('EXT_SUFFIX') or getvar('SO'))"];
+
+// Windows builds don't have ABI flags.
+pub const ABI_FLAGS: &[&str] = &["import sys", "print(getattr(sys, 'abiflags', ''))"];
+
+pub const CONFIG_DIR: &[&str] = &["print(getvar('LIBPL'))"];
+
+// Python 2 doesn't have `sys.implementation`.
+pub const IMPLEMENTATION: &[&str] = &[
+    "import platform, sys",
+    "if hasattr(sys, 'implementation'):",
+    tab!("print(sys.implementation.name)"),
+    "else:",
+    tab!("print(platform.python_implementation().lower())"),
+];
+
+// `platform.machine()` describes the machine, rather than the interpreter,
+// so we also print the pointer width. macOS universal2 builds end their
+// platform with `-universal2`.
+pub const ARCHITECTURE: &[&str] = &[
+    "import platform, struct",
+    "print(platform.machine())",
+    "print(struct.calcsize('P') * 8)",
+    "print(sysconfig.get_platform())",
+];
+
+pub const POINTER_WIDTH: &[&str] = &["import struct", "print(struct.calcsize('P') * 8)"];
+
+// Prints the names of the capabilities that the implementation supports,
+// on one line. See `Capability::name`.
+pub const CAPABILITIES: &[&str] = &[
+    "import platform, sys",
+    "if hasattr(sys, 'implementation'):",
+    tab!("name = sys.implementation.name"),
+    "else:",
+    tab!("name = platform.python_implementation().lower()"),
+    "caps = []",
+    "if getvar('EXT_SUFFIX') or getvar('SO'):",
+    tab!("caps.append('extension_modules')"),
+    "if name in ('cpython', 'pypy', 'graalpy'):",
+    tab!("caps.append('embedding')"),
+    "if name == 'cpython' and sys.version_info[0] == 3 and not getvar('Py_GIL_DISABLED'):",
+    tab!("caps.append('limited_api')"),
+    "if getvar('LIBPL'):",
+    tab!("caps.append('config_dir')"),
+    "if name == 'cpython' and not windows:",
+    tab!("caps.append('pkg_config')"),
+    "print(' '.join(caps))",
+];
+
+// Run when we create a `PythonConfig` for an interpreter path, to check
+// that it's really a Python interpreter. One value per line.
+pub const INTERPRETER_INFO: &[&str] = &[
+    "import platform, struct",
+    "print(sys.executable)",
+    "print(os.path.realpath(sys.executable))",
+    "print(' '.join(str(v) for v in sys.version_info[:5]))",
+    "if hasattr(sys, 'implementation'):",
+    tab!("print(sys.implementation.name)"),
+    "else:",
+    tab!("print(platform.python_implementation().lower())"),
+    "print(struct.calcsize('P') * 8)",
+];
+
+// Called with the names of config vars. Prints `NAME=value` for each
+// one that's defined.
+pub const BUILD_TOOLCHAIN: &[&str] = &[
+    "import sys",
+    "for name in sys.argv[1:]:",
+    tab!("value = getvar(name)"),
+    tab!("if value is not None:"),
+    tab!(tab!("print(name + '=' + str(value))")),
+];
+
+// Called with the name of a package. Prints nothing if we can't find the
+// headers. Older NumPy releases keep them in `core/include`.
+pub const PACKAGE_INCLUDE_DIR: &[&str] = &[
+    "import importlib",
+    "module = importlib.import_module(sys.argv[1])",
+    "if callable(getattr(module, 'get_include', None)):",
+    tab!("print(module.get_include())"),
+    "else:",
+    tab!("root = os.path.dirname(getattr(module, '__file__', None) or '')"),
+    tab!("candidates = [os.path.join(root, 'include'), os.path.join(root, 'core', 'include')]"),
+    tab!("print(next((c for c in candidates if root and os.path.isdir(c)), ''))"),
+];
+
+// Called with the name of a package. The distribution's name may differ
+// from the package's name, so we fall back to `__version__`.
+pub const PACKAGE_VERSION: &[&str] = &[
+    "try:",
+    tab!("from importlib.metadata import version"),
+    tab!("print(version(sys.argv[1]))"),
+    "except Exception:",
+    tab!("import importlib"),
+    tab!("print(importlib.import_module(sys.argv[1]).__version__)"),
+];
+
+// Called with the name of a module. Finds the module without importing
+// it, though finding `a.b` imports `a`. Prints `0` if there's no module.
+// Otherwise, prints `1`, then the module's file, or the first directory
+// of a namespace package. Built-in and frozen modules don't have either.
+pub const MODULE: &[&str] = &[
+    "import importlib.util",
+    "try:",
+    tab!("spec = importlib.util.find_spec(sys.argv[1])"),
+    "except Exception:",
+    tab!("spec = None"),
+    "if spec is None:",
+    tab!("print('0')"),
+    "else:",
+    tab!("print('1')"),
+    tab!("if spec.has_location and spec.origin:"),
+    tab!(tab!("print(spec.origin)")),
+    tab!("elif spec.submodule_search_locations:"),
+    tab!(tab!("print(list(spec.submodule_search_locations)[0])")),
+];
+
+pub const PLATFORM: &[&str] = &["print(sysconfig.get_platform())"];
+
+// Sets `abi_tag` to the wheel ABI tag, like `cp311` or `pypy310_pp73`.
+pub const ABI_TAG: &[&str] = &[
+    "import sys",
+    "if sys.implementation.name == 'cpython':",
+    tab!("abi_tag = 'cp' + getvar('py_version_nodot') + getattr(sys, 'abiflags', '')"),
+    "else:",
+    tab!("abi_tag = '_'.join((getvar('SOABI') or 'none').split('-')[:2]).replace('.', '_')"),
+];
+
+// Run after `ABI_TAG`. The interpreter abbreviations are the ones used
+// by the `packaging` library.
+pub const WHEEL_TAG: &[&str] = &[
+    "name = sys.implementation.name",
+    "short = {'cpython': 'cp', 'pypy': 'pp', 'ironpython': 'ip', 'jython': 'jy'}.get(name, name)",
+    "nodot = getvar('py_version_nodot') or '%d%d' % sys.version_info[:2]",
+    "plat = sysconfig.get_platform().replace('-', '_').replace('.', '_')",
+    "print('-'.join((short + nodot, abi_tag, plat)))",
+];
+
+// Prints the C library of a Linux interpreter, like `glibc 2.35` or
+// `musl 1.2.4`, or nothing. musl doesn't report its version to the
+// interpreter, but its dynamic loader prints it when run without a program.
+pub const LIBC: &[&str] = &[
+    "libc = ''",
+    "if linux:",
+    tab!("try:"),
+    tab!(tab!("name, ver = os.confstr('CS_GNU_LIBC_VERSION').split()")),
+    tab!(tab!("libc = 'glibc ' + ver")),
+    tab!("except (AttributeError, OSError, ValueError):"),
+    tab!(tab!("import glob, subprocess")),
+    tab!(tab!("for loader in glob.glob('/lib/ld-musl-*.so.1'):")),
+    tab!(tab!(tab!("proc = subprocess.Popen([loader], stdout=subprocess.PIPE, stderr=subprocess.PIPE, universal_newlines=True)"))),
+    tab!(tab!(tab!("for line in proc.communicate()[1].splitlines():"))),
+    tab!(tab!(tab!(tab!("if line.startswith('Version '):")))),
+    tab!(tab!(tab!(tab!(tab!("libc = 'musl ' + line.split()[1]"))))),
+    "print(libc)",
+];
+
+/// Runs other query scripts, capturing what they print, and emits
+/// `__PCRS__ name=value` lines in the format understood by
+/// `PythonConfigData::parse`.
+///
+/// Use `field` to run a script, or call a function, and emit its value.
+/// If it raises, `field` emits `__PCRS__ !name=error` instead, and the
+/// other fields are unaffected.
+pub const SNAPSHOT_PRELUDE: &[&str] = &[
+    "import io, sys",
+    "def capture(code):",
+    tab!("out = io.StringIO()"),
+    tab!("stdout, sys.stdout = sys.stdout, out"),
+    tab!("try:"),
+    tab!(tab!("exec(code, dict(globals()))")),
+    tab!("finally:"),
+    tab!(tab!("sys.stdout = stdout")),
+    tab!("return out.getvalue().strip()"),
+    "def escape(value):",
+    tab!("return value.replace('\\\\', '\\\\\\\\').replace('\\n', '\\\\n')"),
+    "def emit(name, value):",
+    tab!("print('__PCRS__ ' + name + '=' + escape(value))"),
+    "def field(name, code):",
+    tab!("try:"),
+    tab!(tab!("value = code() if callable(code) else capture(code)")),
+    tab!("except Exception as err:"),
+    tab!(tab!(
+        "print('__PCRS__ !' + name + '=' + escape('%s: %s' % (type(err).__name__, err)))"
+    )),
+    tab!("else:"),
+    tab!(tab!("emit(name, value)")),
+    "def version():",
+    tab!("v = sys.version_info"),
+    tab!("pre = {'alpha': 'a', 'beta': 'b', 'candidate': 'rc'}.get(v[3])"),
+    tab!("return '%d.%d.%d' % v[:3] + (pre + str(v[4]) if pre else '')"),
+    "def emit_config_vars():",
+    tab!("for name, value in sorted(sysconfig.get_config_vars().items()):"),
+    tab!(tab!("if value is not None:")),
+    tab!(tab!(tab!("emit('config_var.' + name, str(value))"))),
+];
+
+/// The probe script, before we paste `HELPERS` over its marker line
+const PROBE: &str = include_str!("probe.py");
+
+/// The line of `probe.py` that we replace with `HELPERS`
+const PROBE_HELPERS: &str = "# __PCRS__ helpers\n";
+
+/// A complete script that prints a snapshot, without the prelude. See
+/// `PythonConfig::probe`.
+pub fn probe() -> &'static str {
+    static SCRIPT: OnceLock<String> = OnceLock::new();
+    SCRIPT.get_or_init(|| PROBE.replacen(PROBE_HELPERS, HELPERS, 1))
+}
+
+/// Renders `text` as a Python string literal
+pub fn literal(text: &str) -> String {
+    let mut lit = String::with_capacity(text.len() + 2);
+    lit.push('\'');
+    for c in text.chars() {
+        match c {
+            '\\' => lit.push_str("\\\\"),
+            '\'' => lit.push_str("\\'"),
+            '\n' => lit.push_str("\\n"),
+            '\t' => lit.push_str("\\t"),
+            c => lit.push(c),
+        }
+    }
+    lit.push('\'');
+    lit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::literal;
+
+    #[test]
+    fn literals() {
+        assert_eq!(literal("print('a\\\\b')\n\tx"), r"'print(\'a\\\\b\')\n\tx'");
+    }
+}
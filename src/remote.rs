@@ -0,0 +1,249 @@
+//! Running an interpreter in another environment, like over SSH or in a
+//! container
+
+use crate::{cmdr, Commander, Error, PyResult};
+
+use std::io::{self, Write};
+use std::process::{self, Stdio};
+use std::thread;
+
+/// A [`Commander`](trait.Commander.html) that runs an interpreter through
+/// a command prefix, like `ssh host`, `docker exec -i ctr`, or `wsl.exe`
+///
+/// Use it to query the Python inside a target container or VM while you
+/// build on the host. We send our scripts to the interpreter's standard
+/// input, as in `python3 - <args>`, so they survive any quoting that the
+/// prefix does. For prefixes that pass the command to a shell, like
+/// `ssh`, use [`through_shell`](#method.through_shell), which quotes the
+/// remaining arguments.
+///
+/// The interpreter must read its standard input through the prefix; for
+/// `docker exec`, that takes `-i`.
+///
+/// # Example
+///
+/// ```no_run
+/// use python_config::{PythonConfig, RemoteCommand, Version};
+///
+/// let remote = RemoteCommand::ssh("builder.example.com", "python3");
+/// let cfg = PythonConfig::with_commander(Version::Three, remote);
+/// println!("{}", cfg.includes().unwrap());
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RemoteCommand {
+    /// The program, and its arguments, that run the interpreter
+    prefix: Vec<String>,
+    /// The interpreter, as the prefix will find it
+    interpreter: String,
+    /// Pass the interpreter and its arguments as one quoted command, for
+    /// a shell to split
+    shell: bool,
+}
+
+/// Quotes `value` for a POSIX shell
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+impl RemoteCommand {
+    /// Runs `interpreter` by passing it, and its arguments, to the program
+    /// and arguments in `prefix`, like `["docker", "exec", "-i", "ctr"]`
+    ///
+    /// The arguments are passed as they are. `prefix` must not be empty.
+    pub fn new<I, S>(prefix: I, interpreter: &str) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        RemoteCommand {
+            prefix: prefix.into_iter().map(Into::into).collect(),
+            interpreter: interpreter.to_owned(),
+            shell: false,
+        }
+    }
+
+    /// Like [`new`](#method.new), but passes the interpreter and its
+    /// arguments as a single, quoted command, for a POSIX shell on the
+    /// other end to split, like `ssh` does
+    pub fn through_shell<I, S>(prefix: I, interpreter: &str) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        RemoteCommand {
+            shell: true,
+            ..RemoteCommand::new(prefix, interpreter)
+        }
+    }
+
+    /// Runs `interpreter` on `host` with `ssh`
+    ///
+    /// `host` may be anything that `ssh` accepts, like `user@host` or a
+    /// `Host` from your SSH config. Set up keys, or an agent, so that `ssh`
+    /// doesn't prompt for a password.
+    pub fn ssh(host: &str, interpreter: &str) -> Self {
+        RemoteCommand::through_shell(["ssh", host], interpreter)
+    }
+
+    /// Runs `interpreter` in the running Docker `container`, with
+    /// `docker exec -i`
+    pub fn docker_exec(container: &str, interpreter: &str) -> Self {
+        RemoteCommand::new(["docker", "exec", "-i", container], interpreter)
+    }
+
+    /// Runs `interpreter` in the default WSL distribution, with
+    /// `wsl.exe --exec`
+    pub fn wsl(interpreter: &str) -> Self {
+        RemoteCommand::new(["wsl.exe", "--exec"], interpreter)
+    }
+
+    /// Returns the program and arguments that run `cmd`, and what to send
+    /// to the interpreter's standard input
+    ///
+    /// A `-c <script>` command becomes `- <args>`, with the script on
+    /// standard input.
+    fn command_line<'a>(&self, cmd: &[&'a str]) -> (Vec<String>, Option<&'a str>) {
+        let (script, args) = match cmd {
+            ["-c", script, args @ ..] => (Some(*script), args),
+            args => (None, args),
+        };
+        let mut command = vec![self.interpreter.clone()];
+        if script.is_some() {
+            command.push("-".to_owned());
+        }
+        command.extend(args.iter().map(|arg| arg.to_string()));
+
+        let mut line = self.prefix.clone();
+        if self.shell {
+            let quoted: Vec<String> = command.iter().map(|arg| shell_quote(arg)).collect();
+            line.push(quoted.join(" "));
+        } else {
+            line.extend(command);
+        }
+        (line, script)
+    }
+}
+
+/// If the prefix program doesn't exist, the error is `InterpreterNotFound`.
+/// If the prefix, or the interpreter, exits with a non-zero status, the
+/// error holds the status and whatever was printed to stderr.
+impl Commander for RemoteCommand {
+    fn commands(&self, cmd: &[&str]) -> PyResult<String> {
+        let (line, script) = self.command_line(cmd);
+        let (program, args) = line
+            .split_first()
+            .ok_or_else(|| Error::Other("the remote command prefix is empty".into()))?;
+        let spawned = process::Command::new(program)
+            .args(args)
+            .stdin(if script.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match spawned {
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(Error::InterpreterNotFound {
+                    interpreter: program.clone(),
+                })
+            }
+            child => child?,
+        };
+        // Write on another thread, so that the interpreter can't block
+        // on a full stdout while we're still writing.
+        let writer = child.stdin.take().map(|mut stdin| {
+            let script = script.unwrap_or("").to_owned();
+            thread::spawn(move || stdin.write_all(script.as_bytes()))
+        });
+        let out = child.wait_with_output()?;
+        if let Some(writer) = writer {
+            // The interpreter may exit without reading everything, like
+            // when the prefix fails; the exit status says more than the
+            // broken pipe.
+            let _ = writer.join();
+        }
+        cmdr::response(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RemoteCommand;
+    use crate::{Commander, Error, PythonConfig, Version};
+
+    #[test]
+    fn command_lines() {
+        let docker = RemoteCommand::docker_exec("ctr", "python3");
+        assert_eq!(
+            docker.command_line(&["-c", "print(1)", "--embed"]),
+            (
+                vec![
+                    "docker".to_owned(),
+                    "exec".to_owned(),
+                    "-i".to_owned(),
+                    "ctr".to_owned(),
+                    "python3".to_owned(),
+                    "-".to_owned(),
+                    "--embed".to_owned()
+                ],
+                Some("print(1)")
+            )
+        );
+
+        let ssh = RemoteCommand::ssh("host", "/opt/my python/bin/python3");
+        assert_eq!(
+            ssh.command_line(&["--version"]),
+            (
+                vec![
+                    "ssh".to_owned(),
+                    "host".to_owned(),
+                    "'/opt/my python/bin/python3' '--version'".to_owned()
+                ],
+                None
+            )
+        );
+        assert_eq!(
+            ssh.command_line(&["-c", "print(1)", "it's"]).0[2],
+            r"'/opt/my python/bin/python3' '-' 'it'\''s'"
+        );
+    }
+
+    #[test]
+    fn local_stand_ins() {
+        // `env` passes its arguments along, like `docker exec`, and `sh -c`
+        // splits a command, like `ssh`.
+        let remotes = [
+            RemoteCommand::new(["env"], "python3"),
+            RemoteCommand::through_shell(["sh", "-c"], "python3"),
+        ];
+        for remote in remotes {
+            let cfg = PythonConfig::with_commander(Version::Three, remote);
+            let local = PythonConfig::new();
+            assert_eq!(cfg.prefix().unwrap(), local.prefix().unwrap());
+            assert_eq!(cfg.ldflags_split().unwrap(), local.ldflags_split().unwrap());
+            assert_eq!(
+                cfg.semantic_version().unwrap(),
+                local.semantic_version().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let missing = RemoteCommand::new(["this-program-does-not-exist-ssh"], "python3");
+        assert!(matches!(
+            missing.commands(&["--version"]),
+            Err(Error::InterpreterNotFound { .. })
+        ));
+        let failing = RemoteCommand::new(["env"], "python3");
+        assert!(matches!(
+            failing.commands(&["-c", "import sys\nsys.exit(3)"]),
+            Err(Error::Interpreter {
+                status: Some(3),
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,145 @@
+//! Side-by-side reports of several interpreters
+
+use crate::{dedup_paths, parse_err, pyversion, query, PyResult, PyVersion, PythonConfig};
+
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// What we learned about one of the interpreters passed to [`compare`](fn.compare.html)
+#[derive(Debug)]
+pub struct InterpreterReport {
+    /// The interpreter, as it was passed to `compare`
+    pub interpreter: PathBuf,
+    /// The interpreter's summary, or the reason that we couldn't query it
+    pub result: PyResult<InterpreterSummary>,
+}
+
+/// The values that tell interpreters apart
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterpreterSummary {
+    /// See [`PythonConfig::version_info`](struct.PythonConfig.html#method.version_info)
+    pub version: PyVersion,
+    /// See [`PythonConfig::executable`](struct.PythonConfig.html#method.executable)
+    pub executable: PathBuf,
+    /// See [`PythonConfig::prefix`](struct.PythonConfig.html#method.prefix)
+    pub prefix: PathBuf,
+    /// See [`PythonConfig::abi_flags`](struct.PythonConfig.html#method.abi_flags)
+    pub abi_flags: String,
+    /// See [`PythonConfig::include_paths`](struct.PythonConfig.html#method.include_paths).
+    /// A directory appears once, even if it's both the `include` and
+    /// `platinclude` path.
+    pub include_dirs: Vec<PathBuf>,
+}
+
+/// Queries each of the `interpreters` at the same time, returning a
+/// report for each, in the same order
+///
+/// Use this to answer "which Python is my build picking up?" An
+/// interpreter that fails to run has a report with the error, rather
+/// than failing the whole comparison.
+///
+/// # Example
+///
+/// ```no_run
+/// use python_config::discover;
+///
+/// let paths: Vec<_> = discover::all().into_iter().map(|found| found.path).collect();
+/// for report in python_config::compare(&paths) {
+///     match report.result {
+///         Ok(summary) => println!("{}: {}", report.interpreter.display(), summary.version),
+///         Err(err) => println!("{}: {}", report.interpreter.display(), err),
+///     }
+/// }
+/// ```
+pub fn compare(interpreters: &[PathBuf]) -> Vec<InterpreterReport> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = interpreters
+            .iter()
+            .map(|interpreter| scope.spawn(move || summarize(interpreter)))
+            .collect();
+        interpreters
+            .iter()
+            .zip(handles)
+            .map(|(interpreter, handle)| InterpreterReport {
+                interpreter: interpreter.clone(),
+                result: handle.join().expect("interpreter query panicked"),
+            })
+            .collect()
+    })
+}
+
+fn summarize(interpreter: &Path) -> PyResult<InterpreterSummary> {
+    let cfg = PythonConfig::interpreter(interpreter)?;
+    let resp = cfg.script(
+        &[
+            query::VERSION_INFO,
+            query::EXECUTABLE,
+            query::PREFIX,
+            query::ABI_FLAGS,
+            query::INCLUDE_PATHS,
+        ]
+        .concat(),
+    )?;
+    parse(&resp)
+}
+
+/// Parses the concatenated responses of the queries in `summarize`
+fn parse(resp: &str) -> PyResult<InterpreterSummary> {
+    let lines: Vec<&str> = resp.lines().collect();
+    match lines[..] {
+        [version, executable, prefix, abi_flags, ref includes @ ..] if !includes.is_empty() => {
+            let include_dirs = dedup_paths(includes.iter().map(PathBuf::from).collect());
+            Ok(InterpreterSummary {
+                version: pyversion::parse(version)
+                    .ok_or_else(|| parse_err("expected sys.version_info", version))?,
+                executable: PathBuf::from(executable),
+                prefix: PathBuf::from(prefix),
+                abi_flags: abi_flags.to_owned(),
+                include_dirs,
+            })
+        }
+        _ => Err(parse_err("expected an interpreter summary", resp)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, parse};
+    use crate::{Error, PythonConfig};
+    use std::path::PathBuf;
+
+    #[test]
+    fn parse_summary() {
+        let summary = parse(
+            "3 11 7 final 0\n/usr/bin/python3\n/usr\n\n/usr/include/python3.11\n/usr/include/python3.11",
+        )
+        .unwrap();
+        assert_eq!(summary.version.to_string(), "3.11.7");
+        assert_eq!(summary.abi_flags, "");
+        assert_eq!(
+            summary.include_dirs,
+            [PathBuf::from("/usr/include/python3.11")]
+        );
+        assert!(parse("3 11 7 final 0\n/usr/bin/python3").is_err());
+    }
+
+    #[test]
+    fn reports_in_order() {
+        let executable = PythonConfig::new().executable_path().unwrap();
+        let missing = PathBuf::from("this-program-does-not-exist-python");
+        let reports = compare(&[executable.clone(), missing.clone()]);
+        assert_eq!(reports.len(), 2);
+
+        assert_eq!(reports[0].interpreter, executable);
+        let summary = reports[0].result.as_ref().unwrap();
+        assert_eq!(summary.version, PythonConfig::new().version_info().unwrap());
+        assert!(!summary.include_dirs.is_empty());
+
+        assert_eq!(reports[1].interpreter, missing);
+        match reports[1].result {
+            Err(Error::InterpreterNotFound { .. }) => {}
+            ref other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Choosing an interpreter the way a version manager would
+
+use crate::{cache, discover, Error, PyResult, PythonConfig};
+
+use std::path::PathBuf;
+
+/// A way to choose the interpreter behind a `PythonConfig`
+///
+/// Version managers put shims, or their own `python3`, on the `PATH`,
+/// so the first `python3` on the `PATH` may not be the Python that you
+/// expect. Each strategy asks one source of interpreters which Python it
+/// selects.
+///
+/// See [`PythonConfig::resolve`](struct.PythonConfig.html#method.resolve).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResolutionStrategy {
+    /// The pyenv installation selected by `PYENV_VERSION`, the nearest
+    /// `.python-version` file, or pyenv's global version
+    Pyenv,
+    /// The newest Python in the Homebrew Cellar
+    Homebrew,
+    /// The asdf installation selected by `ASDF_PYTHON_VERSION`, or the
+    /// nearest `.tool-versions` file
+    Asdf,
+    /// The first `python3`, or `python`, on the `PATH`, whatever it is
+    PathOnly,
+}
+
+impl ResolutionStrategy {
+    /// Every strategy, in the order that
+    /// [`PythonConfig::resolve_default`](struct.PythonConfig.html#method.resolve_default)
+    /// tries them
+    pub const DEFAULT_ORDER: &'static [ResolutionStrategy] = &[
+        ResolutionStrategy::Pyenv,
+        ResolutionStrategy::Asdf,
+        ResolutionStrategy::Homebrew,
+        ResolutionStrategy::PathOnly,
+    ];
+
+    /// Returns the path to the interpreter that this strategy selects, or
+    /// `None` if it doesn't select one
+    ///
+    /// We don't run the interpreter.
+    pub fn find(self) -> Option<PathBuf> {
+        match self {
+            ResolutionStrategy::Pyenv => discover::pyenv_current().map(|i| i.path),
+            ResolutionStrategy::Homebrew => discover::homebrew().into_iter().next().map(|i| i.path),
+            ResolutionStrategy::Asdf => discover::asdf_current().map(|i| i.path),
+            ResolutionStrategy::PathOnly => ["python3", "python"]
+                .iter()
+                .find_map(|name| cache::find_program(name)),
+        }
+    }
+}
+
+impl PythonConfig {
+    /// Create a `PythonConfig` that uses the interpreter selected by the
+    /// first of `strategies` that selects one
+    ///
+    /// A strategy that selects an interpreter that we can't run is passed
+    /// over, and if no strategy succeeds, we return the first such error.
+    /// Use [`resolution`](#method.resolution) to learn which strategy won.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::{PythonConfig, ResolutionStrategy};
+    ///
+    /// // Prefer the Homebrew Python over a python.org framework on the PATH
+    /// let cfg = PythonConfig::resolve(&[
+    ///     ResolutionStrategy::Homebrew,
+    ///     ResolutionStrategy::PathOnly,
+    /// ])
+    /// .unwrap();
+    /// println!("Resolved by {:?}", cfg.resolution().unwrap());
+    /// ```
+    pub fn resolve(strategies: &[ResolutionStrategy]) -> PyResult<Self> {
+        let mut first_err = None;
+        for &strategy in strategies {
+            let path = match strategy.find() {
+                Some(path) => path,
+                None => continue,
+            };
+            match PythonConfig::interpreter(path) {
+                Ok(mut cfg) => {
+                    cfg.resolution = Some(strategy);
+                    return Ok(cfg);
+                }
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+        Err(first_err.unwrap_or_else(|| {
+            Error::Other(
+                format!(
+                    "none of the strategies {:?} found an interpreter",
+                    strategies
+                )
+                .into(),
+            )
+        }))
+    }
+
+    /// Create a `PythonConfig` that uses the interpreter selected by
+    /// pyenv, then asdf, then Homebrew, then the `PATH`
+    ///
+    /// See [`ResolutionStrategy::DEFAULT_ORDER`](enum.ResolutionStrategy.html#associatedconstant.DEFAULT_ORDER).
+    pub fn resolve_default() -> PyResult<Self> {
+        PythonConfig::resolve(ResolutionStrategy::DEFAULT_ORDER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResolutionStrategy;
+    use crate::PythonConfig;
+
+    #[test]
+    fn path_only() {
+        assert!(ResolutionStrategy::PathOnly.find().is_some());
+        let cfg = PythonConfig::resolve(&[ResolutionStrategy::PathOnly]).unwrap();
+        assert_eq!(cfg.resolution(), Some(ResolutionStrategy::PathOnly));
+        assert!(cfg.interpreter_info().is_some());
+        assert_eq!(PythonConfig::new().resolution(), None);
+    }
+
+    #[test]
+    fn nothing_resolved() {
+        assert!(PythonConfig::resolve(&[]).is_err());
+    }
+}
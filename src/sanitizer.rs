@@ -0,0 +1,68 @@
+//! Sanitizer instrumentation detection
+
+/// A sanitizer that instrumented the Python interpreter
+///
+/// Extensions loaded into a sanitized interpreter generally
+/// need to be built with the same instrumentation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sanitizer {
+    /// AddressSanitizer (`-fsanitize=address`)
+    Address,
+    /// UndefinedBehaviorSanitizer (`-fsanitize=undefined`)
+    Undefined,
+    /// MemorySanitizer (`-fsanitize=memory`)
+    Memory,
+    /// ThreadSanitizer (`-fsanitize=thread`)
+    Thread,
+    /// LeakSanitizer (`-fsanitize=leak`)
+    Leak,
+}
+
+impl Sanitizer {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "address" => Some(Sanitizer::Address),
+            "undefined" => Some(Sanitizer::Undefined),
+            "memory" => Some(Sanitizer::Memory),
+            "thread" => Some(Sanitizer::Thread),
+            "leak" => Some(Sanitizer::Leak),
+            _ => None,
+        }
+    }
+}
+
+/// Collects the sanitizers named by `-fsanitize=` flags in
+/// the whitespace-separated `flags`. Each sanitizer appears
+/// at most once, in the order it was first seen.
+pub(crate) fn parse(flags: &str) -> Vec<Sanitizer> {
+    let mut sanitizers = Vec::new();
+    let names = flags
+        .split_whitespace()
+        .filter_map(|flag| flag.strip_prefix("-fsanitize="))
+        .flat_map(|names| names.split(','));
+    for sanitizer in names.filter_map(Sanitizer::from_name) {
+        if !sanitizers.contains(&sanitizer) {
+            sanitizers.push(sanitizer);
+        }
+    }
+    sanitizers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Sanitizer};
+
+    #[test]
+    fn no_sanitizers() {
+        assert!(parse("-Wsign-compare -DNDEBUG -g -fwrapv -O3 -Wall").is_empty());
+    }
+
+    #[test]
+    fn combined_and_repeated() {
+        assert_eq!(
+            parse("-fsanitize=address,undefined -O1 -fsanitize=address -fsanitize-recover=all"),
+            vec![Sanitizer::Address, Sanitizer::Undefined]
+        );
+    }
+}
@@ -11,9 +11,9 @@ macro_rules! tab {
     };
 }
 
-macro_rules! target_line {
-    ($target:expr, $line:expr) => {
-        if cfg!(target_os = $target) {
+macro_rules! cfg_line {
+    ($cfg:meta, $line:expr) => {
+        if cfg!($cfg) {
             $line
         } else {
             ""
@@ -21,18 +21,15 @@ macro_rules! target_line {
     };
 }
 
-/// Sets an individual script line that only evaluates
-/// on Linux.
-macro_rules! linux_line {
-    ($line:expr) => {
-        target_line!("linux", $line)
-    };
-}
-
-/// Sets an individual script line that only evaluates
-/// on macOS
-macro_rules! macos_line {
+/// Sets an individual script line that only evaluates when
+/// we're compiled for the MSVC toolchain
+///
+/// Other platform differences depend on the interpreter, not on us.
+/// Scripts check the `linux`, `macos`, and `windows` variables that
+/// the prelude defines. But whether we emit library names for MSVC, or
+/// flags for MinGW, depends on the toolchain that will use them.
+macro_rules! msvc_line {
     ($line:expr) => {
-        target_line!("macos", $line)
+        cfg_line!(target_env = "msvc", $line)
     };
 }
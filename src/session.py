@@ -0,0 +1,77 @@
+"""Answers our queries, one after another, from a single interpreter
+
+This is the server behind `PersistentCommand`. Each request holds the
+arguments that we'd pass to the interpreter: a line with the number of
+arguments, then, for each argument, a line with its length in bytes,
+followed by its UTF-8 bytes. We answer `-c <script> <args>` by running the
+script, and `--version` like the interpreter does.
+
+Each response is the exit status on a line, then what the script printed
+to stdout, then what it printed to stderr, each as a line with the length
+in bytes, followed by the bytes.
+
+Before the first request, we print `__PCRS__ session`, so that the client
+can skip anything that the interpreter printed while it started.
+"""
+
+import io
+import platform
+import sys
+import traceback
+
+requests = sys.stdin.buffer
+responses = sys.stdout.buffer
+stdout, stderr = sys.stdout, sys.stderr
+
+
+def read_arg():
+    size = int(requests.readline())
+    return requests.read(size).decode('utf-8')
+
+
+def run(args):
+    """Returns the exit status, stdout, and stderr of running `args`"""
+    if args == ['--version']:
+        return 0, 'Python %s\n' % platform.python_version(), ''
+    if len(args) < 2 or args[0] != '-c':
+        return 2, '', 'a session only answers -c and --version\n'
+    out, err = io.StringIO(), io.StringIO()
+    argv = sys.argv
+    sys.argv = ['-c'] + args[2:]
+    sys.stdout, sys.stderr = out, err
+    status = 0
+    try:
+        exec(compile(args[1], '<string>', 'exec'), {'__name__': '__main__'})
+    except SystemExit as exit:
+        if exit.code is None:
+            status = 0
+        elif isinstance(exit.code, int):
+            status = exit.code
+        else:
+            print(exit.code, file=err)
+            status = 1
+    except BaseException:
+        traceback.print_exc(file=err)
+        status = 1
+    finally:
+        sys.stdout, sys.stderr = stdout, stderr
+        sys.argv = argv
+    return status, out.getvalue(), err.getvalue()
+
+
+def respond(status, out, err):
+    responses.write(b'%d\n' % status)
+    for text in (out, err):
+        data = text.encode('utf-8', 'replace')
+        responses.write(b'%d\n' % len(data))
+        responses.write(data)
+    responses.flush()
+
+
+stdout.write('__PCRS__ session\n')
+stdout.flush()
+while True:
+    line = requests.readline()
+    if not line:
+        break
+    respond(*run([read_arg() for _ in range(int(line))]))
@@ -0,0 +1,288 @@
+//! Answering many queries from one long-running interpreter
+
+use crate::{Commander, Error, PyResult, PythonConfig, SysCommand};
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{self, Child, ChildStdin, ChildStdout};
+use std::sync::{Mutex, PoisonError};
+
+/// The server that answers a session's queries
+const SERVER: &str = include_str!("session.py");
+
+/// What the server prints once it's ready for requests
+const READY: &str = "__PCRS__ session";
+
+/// A running server, and its pipes
+struct Session {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Session {
+    /// Sends `args` to the server, and returns its exit status, stdout,
+    /// and stderr
+    fn request(&mut self, args: &[&str]) -> io::Result<(i32, String, String)> {
+        let mut request = format!("{}\n", args.len()).into_bytes();
+        for arg in args {
+            request.extend_from_slice(format!("{}\n", arg.len()).as_bytes());
+            request.extend_from_slice(arg.as_bytes());
+        }
+        self.stdin.write_all(&request)?;
+        self.stdin.flush()?;
+
+        let status = self.read_line()?;
+        let status = status
+            .parse()
+            .map_err(|_| io::Error::other(format!("unexpected session status '{}'", status)))?;
+        let stdout = self.read_text()?;
+        let stderr = self.read_text()?;
+        Ok((status, stdout, stderr))
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the interpreter ended the session",
+            ));
+        }
+        Ok(line.trim_end().to_owned())
+    }
+
+    /// Reads a length line, then that many bytes
+    fn read_text(&mut self) -> io::Result<String> {
+        let len = self.read_line()?;
+        let len: usize = len
+            .parse()
+            .map_err(|_| io::Error::other(format!("unexpected session length '{}'", len)))?;
+        let mut buf = vec![0; len];
+        self.stdout.read_exact(&mut buf)?;
+        String::from_utf8(buf).map_err(io::Error::other)
+    }
+
+    /// Closes the server's stdin, which ends its loop, and waits for it
+    fn close(self) -> io::Result<()> {
+        let Session {
+            mut child, stdin, ..
+        } = self;
+        drop(stdin);
+        child.wait().map(drop)
+    }
+}
+
+/// A [`Commander`](trait.Commander.html) that keeps one interpreter
+/// running, and sends it each query, rather than starting an interpreter
+/// per query
+///
+/// Starting an interpreter takes tens of milliseconds. That adds up in
+/// tools that ask many questions over a long time, like editors and
+/// language servers. Create one with
+/// [`PythonConfig::persistent`](struct.PythonConfig.html#method.persistent).
+///
+/// We start the interpreter on the first query, and run each query's
+/// script in it, in a fresh namespace. Modules that a query imports stay
+/// imported, so a query that changes a module's state, like
+/// `sys.path`, affects later queries. The session doesn't honor the
+/// timeout, retry policy, or cache of the `SysCommand` that it's made
+/// from, but it does honor its environment and working directory.
+///
+/// The interpreter exits when you [`close`](#method.close) the session,
+/// or drop it. If the interpreter dies, the query fails, and the next
+/// query starts another. Sessions need Python 3.
+pub struct PersistentCommand {
+    python: SysCommand,
+    session: Mutex<Option<Session>>,
+}
+
+impl PersistentCommand {
+    /// Runs a session with the interpreter that `python` runs
+    pub fn new(python: SysCommand) -> Self {
+        PersistentCommand {
+            python,
+            session: Mutex::new(None),
+        }
+    }
+
+    /// Returns `true` if the interpreter is running
+    pub fn is_running(&self) -> bool {
+        self.lock().is_some()
+    }
+
+    /// Stops the interpreter, waiting for it to exit
+    ///
+    /// The next query starts another interpreter. Closing a session that
+    /// isn't running does nothing.
+    pub fn close(&self) -> PyResult<()> {
+        match self.lock().take() {
+            Some(session) => session.close().map_err(Error::from),
+            None => Ok(()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Session>> {
+        self.session.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Starts the server, and waits until it's ready
+    fn start(&self) -> PyResult<Session> {
+        let args = self.python.full_args(&["-u", "-c", SERVER]);
+        let mut child = self
+            .python
+            .command(&args)
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::null())
+            .spawn()
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => Error::InterpreterNotFound {
+                    interpreter: self.python.program().to_owned(),
+                },
+                _ => Error::IO(err),
+            })?;
+        let mut session = Session {
+            stdin: child.stdin.take().expect("piped stdin"),
+            stdout: BufReader::new(child.stdout.take().expect("piped stdout")),
+            child,
+        };
+        // Skip anything that the interpreter printed while it started
+        loop {
+            match session.read_line() {
+                Ok(ref line) if line == READY => return Ok(session),
+                Ok(_) => continue,
+                Err(_) => {
+                    let status = session.child.wait()?;
+                    return Err(Error::Interpreter {
+                        status: status.code(),
+                        stderr: "the interpreter exited before the session started".to_owned(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// A query that the interpreter answers with a non-zero exit status fails
+/// with [`Error::Interpreter`](enum.Error.html#variant.Interpreter), like
+/// a `SysCommand`.
+impl Commander for PersistentCommand {
+    fn commands(&self, args: &[&str]) -> PyResult<String> {
+        let mut session = self.lock();
+        if session.is_none() {
+            *session = Some(self.start()?);
+        }
+        let running = session.as_mut().expect("a running session");
+        match running.request(args) {
+            Ok((0, stdout, _)) => Ok(stdout.trim().to_owned()),
+            Ok((status, _, stderr)) => Err(Error::Interpreter {
+                status: Some(status),
+                stderr: stderr.trim().to_owned(),
+            }),
+            Err(err) => {
+                if let Some(mut dead) = session.take() {
+                    let _ = dead.child.kill();
+                    let _ = dead.child.wait();
+                }
+                Err(Error::IO(err))
+            }
+        }
+    }
+}
+
+impl Drop for PersistentCommand {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+impl PythonConfig {
+    /// Answer every query from one interpreter that keeps running, rather
+    /// than starting an interpreter per query
+    ///
+    /// Use it in long-running tools that ask many questions, like editors
+    /// and language servers. The interpreter starts on the first query,
+    /// and exits when you call [`close`](#method.close), or drop the
+    /// `PythonConfig`. See [`PersistentCommand`](struct.PersistentCommand.html).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfig;
+    ///
+    /// let cfg = PythonConfig::new().persistent();
+    /// let includes = cfg.includes().unwrap(); // Starts the interpreter
+    /// let ldflags = cfg.ldflags().unwrap(); // Reuses it
+    /// cfg.close().unwrap();
+    /// ```
+    pub fn persistent(self) -> PythonConfig<PersistentCommand> {
+        PythonConfig {
+            cmdr: PersistentCommand::new(self.cmdr),
+            ver: self.ver,
+            info: self.info,
+            resolution: self.resolution,
+            embed: self.embed,
+            caps: self.caps,
+        }
+    }
+}
+
+impl PythonConfig<PersistentCommand> {
+    /// Stops the interpreter that answers our queries, waiting for it to
+    /// exit
+    ///
+    /// The next query starts another interpreter. See
+    /// [`PersistentCommand::close`](struct.PersistentCommand.html#method.close).
+    pub fn close(&self) -> PyResult<()> {
+        self.cmdr.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Commander, Error, PythonConfig, SysCommand};
+
+    #[test]
+    fn session() {
+        let cfg = PythonConfig::new();
+        let persistent = PythonConfig::new().persistent();
+        assert!(!persistent.cmdr.is_running());
+        assert_eq!(persistent.includes().unwrap(), cfg.includes().unwrap());
+        assert!(persistent.cmdr.is_running());
+        assert_eq!(
+            persistent.version_raw().unwrap(),
+            cfg.version_raw().unwrap()
+        );
+        assert_eq!(persistent.snapshot().unwrap(), cfg.snapshot().unwrap());
+        assert_eq!(
+            persistent.config_var("LDVERSION").unwrap(),
+            cfg.config_var("LDVERSION").unwrap()
+        );
+
+        match persistent.run_python("import sys\nsys.exit(3)") {
+            Err(Error::Interpreter { status, .. }) => assert_eq!(status, Some(3)),
+            other => panic!("{:?}", other),
+        }
+        match persistent.run_python("raise KeyError('boom')") {
+            Err(Error::Interpreter { stderr, .. }) => {
+                assert!(stderr.contains("KeyError"), "{}", stderr)
+            }
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(persistent.run_python("print('ok')").unwrap(), "ok");
+
+        persistent.close().unwrap();
+        assert!(!persistent.cmdr.is_running());
+        assert_eq!(persistent.prefix().unwrap(), cfg.prefix().unwrap());
+    }
+
+    #[test]
+    fn missing_interpreter() {
+        let persistent =
+            super::PersistentCommand::new(SysCommand::new("/this/path/does/not/exist/python3"));
+        assert!(matches!(
+            persistent.commands(&["--version"]),
+            Err(Error::InterpreterNotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,492 @@
+//! Reads configuration from a `_sysconfigdata_*.py` file, without
+//! running the interpreter that it describes
+//!
+//! CPython's build writes its config vars to a module that assigns a
+//! dict literal to `build_time_vars`. The dict only holds strings and
+//! integers, so we parse the literal ourselves, then derive the same
+//! values that our query scripts compute from `sysconfig`.
+
+use crate::{Error, PyResult, PyVersion, PythonConfigData, ReleaseLevel};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Names the sysconfigdata module, like `_sysconfigdata__linux_x86_64-linux-gnu`
+const NAME_ENV_VAR: &str = "_PYTHON_SYSCONFIGDATA_NAME";
+
+fn malformed() -> Error {
+    Error::Other("unable to parse the sysconfigdata file".into())
+}
+
+/// A cursor over the text of a sysconfigdata module
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).copied()
+    }
+
+    /// Skips whitespace and comments
+    fn skip_space(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                b' ' | b'\t' | b'\r' | b'\n' => self.pos += 1,
+                b'#' => {
+                    while !matches!(self.peek(), None | Some(b'\n')) {
+                        self.pos += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> PyResult<()> {
+        self.skip_space();
+        if self.peek() != Some(c) {
+            return Err(malformed());
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Parses `count` hex digits as a character
+    fn hex_char(&mut self, count: usize) -> PyResult<char> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + count)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .ok_or_else(malformed)?;
+        self.pos += count;
+        u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(std::char::from_u32)
+            .ok_or_else(malformed)
+    }
+
+    /// Parses a single string literal
+    fn string(&mut self) -> PyResult<String> {
+        let quote = self.peek().ok_or_else(malformed)?;
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let c = self.peek().ok_or_else(malformed)?;
+            self.pos += 1;
+            if c == quote {
+                break;
+            } else if c != b'\\' {
+                bytes.push(c);
+                continue;
+            }
+            let escaped = self.peek().ok_or_else(malformed)?;
+            self.pos += 1;
+            let c = match escaped {
+                b'n' => '\n',
+                b't' => '\t',
+                b'r' => '\r',
+                b'0'..=b'7' => {
+                    let mut value = u32::from(escaped - b'0');
+                    for _ in 0..2 {
+                        match self.peek() {
+                            Some(d @ b'0'..=b'7') => {
+                                value = value * 8 + u32::from(d - b'0');
+                                self.pos += 1;
+                            }
+                            _ => break,
+                        }
+                    }
+                    std::char::from_u32(value).ok_or_else(malformed)?
+                }
+                b'x' => self.hex_char(2)?,
+                b'u' => self.hex_char(4)?,
+                b'U' => self.hex_char(8)?,
+                b'\n' => continue,
+                other => {
+                    // Includes `\\`, `\'`, and `\"`
+                    bytes.push(other);
+                    continue;
+                }
+            };
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+        }
+        String::from_utf8(bytes).map_err(|_| malformed())
+    }
+
+    /// Parses a value, returning `None` for `None`
+    ///
+    /// Adjacent string literals are concatenated. Other values, like
+    /// integers, are returned as written.
+    fn value(&mut self) -> PyResult<Option<String>> {
+        self.skip_space();
+        match self.peek() {
+            Some(b'\'') | Some(b'"') => {
+                let mut value = self.string()?;
+                loop {
+                    self.skip_space();
+                    match self.peek() {
+                        Some(b'\'') | Some(b'"') => value.push_str(&self.string()?),
+                        _ => return Ok(Some(value)),
+                    }
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while let Some(c) = self.peek() {
+                    if c.is_ascii_alphanumeric() || b"+-._".contains(&c) {
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+                match &self.text[start..self.pos] {
+                    b"" => Err(malformed()),
+                    b"None" => Ok(None),
+                    token => Ok(Some(String::from_utf8_lossy(token).into_owned())),
+                }
+            }
+        }
+    }
+}
+
+/// Parses the `build_time_vars` dict from the text of a sysconfigdata
+/// module
+///
+/// Integers are returned in their string form, and `None` values are
+/// skipped, like in a [`PythonConfigData`] snapshot.
+pub(crate) fn parse(text: &str) -> PyResult<BTreeMap<String, String>> {
+    let start = text.find("build_time_vars").ok_or_else(malformed)?;
+    let mut parser = Parser {
+        text: text.as_bytes(),
+        pos: start + "build_time_vars".len(),
+    };
+    parser.expect(b'=')?;
+    parser.expect(b'{')?;
+
+    let mut vars = BTreeMap::new();
+    loop {
+        parser.skip_space();
+        if parser.peek() == Some(b'}') {
+            return Ok(vars);
+        }
+        let name = parser.value()?.ok_or_else(malformed)?;
+        parser.expect(b':')?;
+        if let Some(value) = parser.value()? {
+            vars.insert(name, value);
+        }
+        parser.skip_space();
+        match parser.peek() {
+            Some(b',') => parser.pos += 1,
+            Some(b'}') => return Ok(vars),
+            _ => return Err(malformed()),
+        }
+    }
+}
+
+/// Returns `false` for the values that a missing, empty, or zero
+/// config var takes
+fn truthy(value: Option<&str>) -> bool {
+    !matches!(value, None | Some("") | Some("0"))
+}
+
+/// Reads the full version from `PY_VERSION` in `patchlevel.h`, looking
+/// next to the headers that the config vars describe
+fn header_version(vars: &BTreeMap<String, String>, path: &Path) -> Option<PyVersion> {
+    let get = |name: &str| vars.get(name).map(String::as_str).unwrap_or("");
+    // In a sysroot, the headers aren't where the config vars say. The
+    // module lives in `lib/pythonX.Y`, so look relative to it, too.
+    let relative = path
+        .parent()?
+        .parent()?
+        .parent()?
+        .join("include")
+        .join(format!("python{}{}", get("VERSION"), get("ABIFLAGS")));
+    for dir in &[PathBuf::from(get("INCLUDEPY")), relative] {
+        let header = match fs::read_to_string(dir.join("patchlevel.h")) {
+            Ok(header) => header,
+            Err(_) => continue,
+        };
+        for line in header.lines() {
+            let mut words = line.split_whitespace();
+            if let (Some("#define"), Some("PY_VERSION"), Some(version)) =
+                (words.next(), words.next(), words.next())
+            {
+                return version.trim_matches('"').parse().ok();
+            }
+        }
+    }
+    None
+}
+
+/// Computes a snapshot from the config vars in the sysconfigdata module
+/// at `path`
+fn snapshot(vars: BTreeMap<String, String>, path: &Path) -> PyResult<PythonConfigData> {
+    let get = |name: &str| vars.get(name).map(String::as_str).unwrap_or("");
+    let version = match header_version(&vars, path) {
+        Some(version) => version,
+        None => {
+            let mut parts = get("VERSION").splitn(2, '.').map(str::parse);
+            match (parts.next(), parts.next()) {
+                (Some(Ok(major)), Some(Ok(minor))) => PyVersion {
+                    major,
+                    minor,
+                    micro: 0,
+                    releaselevel: ReleaseLevel::Final,
+                    serial: 0,
+                },
+                _ => return Err(Error::Other("sysconfigdata is missing the VERSION".into())),
+            }
+        }
+    };
+    // The config vars describe the target, which may not be the
+    // platform that we're running on.
+    let linux = get("MACHDEP").starts_with("linux");
+    let macos = get("MACHDEP") == "darwin";
+
+    let include = get("INCLUDEPY");
+    let platinclude = match get("CONFINCLUDEPY") {
+        "" => include,
+        platinclude => platinclude,
+    };
+    let includes = format!("-I{} -I{}", include, platinclude);
+
+    let mut cflags: Vec<&str> = includes.split(' ').collect();
+    if linux {
+        cflags.extend(get("BASECFLAGS").split_whitespace());
+        cflags.extend(get("CONFIGURE_CFLAGS").split_whitespace());
+    } else if macos {
+        cflags.extend(get("CFLAGS").split_whitespace());
+    }
+
+    let mut libs: Vec<String> = if !version.at_least(3, 8) {
+        vec![format!("-lpython{}{}", get("VERSION"), get("ABIFLAGS"))]
+    } else if truthy(vars.get("LIBPYTHON").map(String::as_str)) {
+        vec![get("LIBPYTHON").to_owned()]
+    } else {
+        Vec::new()
+    };
+    libs.extend(get("LIBS").split_whitespace().map(str::to_owned));
+    libs.extend(get("SYSLIBS").split_whitespace().map(str::to_owned));
+
+    let mut ldflags = libs.clone();
+    if linux {
+        ldflags.insert(0, format!("-L{}/lib", get("exec_prefix")));
+    }
+    if !truthy(vars.get("Py_ENABLE_SHARED").map(String::as_str)) && !get("LIBPL").is_empty() {
+        ldflags.insert(0, format!("-L{}", get("LIBPL")));
+    }
+    if !truthy(vars.get("PYTHONFRAMEWORK").map(String::as_str)) {
+        ldflags.extend(get("LINKFORSHARED").split_whitespace().map(str::to_owned));
+    }
+
+    Ok(PythonConfigData {
+        executable: format!("{}/python{}", get("BINDIR"), get("VERSION")),
+        prefix: get("prefix").to_owned(),
+        exec_prefix: get("exec_prefix").to_owned(),
+        cflags: cflags.join(" "),
+        includes,
+        libs: libs.join(" "),
+        ldflags: ldflags.join(" "),
+        extension_suffix: get("EXT_SUFFIX").to_owned(),
+        abi_flags: get("ABIFLAGS").to_owned(),
+        config_dir: get("LIBPL").to_owned(),
+        // The config vars don't say which libc the target runs on
+        libc: None,
+        version,
+        config_vars: vars,
+    })
+}
+
+/// Finds the sysconfigdata module in the directories of `pythonpath`
+///
+/// If `name` is set, we look for that module. Otherwise, we look for
+/// any `_sysconfigdata_*.py` module, and it must be the only one.
+fn locate(name: Option<OsString>, pythonpath: Option<OsString>) -> PyResult<PathBuf> {
+    let pythonpath = pythonpath
+        .filter(|path| !path.is_empty())
+        .ok_or(Error::Other(
+            "PYTHONPATH must name the directory holding the sysconfigdata file".into(),
+        ))?;
+    let mut found = Vec::new();
+    for dir in env::split_paths(&pythonpath) {
+        match name {
+            Some(ref name) => {
+                let mut file = name.clone();
+                file.push(".py");
+                let path = dir.join(file);
+                if path.is_file() {
+                    return Ok(path);
+                }
+            }
+            None => {
+                let entries = match fs::read_dir(&dir) {
+                    Ok(entries) => entries,
+                    Err(_) => continue,
+                };
+                let mut paths: Vec<PathBuf> = entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        let file = path
+                            .file_name()
+                            .and_then(|file| file.to_str())
+                            .unwrap_or("");
+                        file.starts_with("_sysconfigdata_") && file.ends_with(".py")
+                    })
+                    .collect();
+                paths.sort();
+                found.extend(paths);
+            }
+        }
+    }
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        0 => Err(Error::Other(
+            "no sysconfigdata file found on the PYTHONPATH".into(),
+        )),
+        _ => Err(Error::Other(
+            "found more than one sysconfigdata file; set _PYTHON_SYSCONFIGDATA_NAME to pick one"
+                .into(),
+        )),
+    }
+}
+
+impl PythonConfigData {
+    /// Reads a snapshot from a `_sysconfigdata_*.py` file, without
+    /// running an interpreter
+    ///
+    /// Use this when cross compiling, where you can't run the target's
+    /// Python. The file lives next to the target's standard library,
+    /// like `lib/python3.11/_sysconfigdata__linux_x86_64-linux-gnu.py`.
+    /// The snapshot holds the values that the target interpreter would
+    /// report, and every config var in the file. Since it implements
+    /// [`PythonIntrospection`](trait.PythonIntrospection.html), code that
+    /// accepts a `&dyn PythonIntrospection` works with a live interpreter
+    /// or with a sysconfigdata file.
+    ///
+    /// Some values are approximate:
+    ///
+    /// - the file only records the `major.minor` version. We read the
+    ///   full version from the target's `patchlevel.h`, if we can find
+    ///   it, otherwise the micro version is `0`.
+    /// - the executable is the `pythonX.Y` in the target's `BINDIR`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use python_config::PythonConfigData;
+    ///
+    /// let target = PythonConfigData::from_sysconfigdata(
+    ///     "/sysroot/usr/lib/python3.11/_sysconfigdata__linux_aarch64-linux-gnu.py",
+    /// ).unwrap();
+    /// println!("{}", target.extension_suffix);
+    /// ```
+    pub fn from_sysconfigdata<P: AsRef<Path>>(path: P) -> PyResult<Self> {
+        let path = path.as_ref();
+        let vars = parse(&fs::read_to_string(path)?).map_err(|err| match err {
+            Error::Other(why) => Error::Other(format!("{}: {}", path.display(), why).into()),
+            err => err,
+        })?;
+        snapshot(vars, path)
+    }
+
+    /// Like [`from_sysconfigdata`](#method.from_sysconfigdata), but finds
+    /// the file using the same environment variables as CPython's own
+    /// cross builds
+    ///
+    /// We search the directories in `PYTHONPATH` for the module named by
+    /// `_PYTHON_SYSCONFIGDATA_NAME`. If that variable isn't set, the
+    /// `PYTHONPATH` directories must hold exactly one `_sysconfigdata_*.py`
+    /// file.
+    pub fn from_sysconfigdata_env() -> PyResult<Self> {
+        let name = env::var_os(NAME_ENV_VAR).filter(|name| !name.is_empty());
+        let path = locate(name, env::var_os("PYTHONPATH"))?;
+        Self::from_sysconfigdata(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{locate, parse};
+    use crate::{PythonConfig, PythonConfigData};
+
+    use std::fs;
+    use std::process::Command;
+
+    #[test]
+    fn literals() {
+        let text = "# system configuration generated and used by the sysconfig module\n\
+            build_time_vars = {'ABIFLAGS': '',\n \
+            'ALIGNOF_LONG': 8,\n \
+            'CONFIG_ARGS': \"'--prefix=/usr' \"\n                \"'--enable-shared'\",\n \
+            'ESCAPES': 'a\\\\b\\'c\\td\\x41\\u00e9',\n \
+            'NEG': -1,\n \
+            'NOTHING': None,\n \
+            'srcdir': '.'}\n";
+        let vars = parse(text).unwrap();
+        assert_eq!(vars["ABIFLAGS"], "");
+        assert_eq!(vars["ALIGNOF_LONG"], "8");
+        assert_eq!(vars["CONFIG_ARGS"], "'--prefix=/usr' '--enable-shared'");
+        assert_eq!(vars["ESCAPES"], "a\\b'c\tdAé");
+        assert_eq!(vars["NEG"], "-1");
+        assert!(!vars.contains_key("NOTHING"));
+        assert_eq!(vars["srcdir"], ".");
+
+        // Newer versions indent each entry, and end with a trailing comma
+        let vars = parse("build_time_vars = {\n    'A': 1,\n    'B': 'b',\n}\n").unwrap();
+        assert_eq!(vars.len(), 2);
+
+        assert!(parse("build_time_vars = {'A': 1").is_err());
+        assert!(parse("something_else = {}").is_err());
+    }
+
+    #[test]
+    fn locate_in_pythonpath() {
+        let dir = std::env::temp_dir().join(format!("sysconfigdata-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let pythonpath = std::env::join_paths(&[dir.join("missing"), dir.clone()]).unwrap();
+
+        assert!(locate(None, Some(pythonpath.clone())).is_err());
+        fs::write(dir.join("_sysconfigdata__linux_a.py"), "").unwrap();
+        assert_eq!(
+            locate(None, Some(pythonpath.clone())).unwrap(),
+            dir.join("_sysconfigdata__linux_a.py")
+        );
+        fs::write(dir.join("_sysconfigdata__linux_b.py"), "").unwrap();
+        assert!(locate(None, Some(pythonpath.clone())).is_err());
+        assert_eq!(
+            locate(Some("_sysconfigdata__linux_b".into()), Some(pythonpath)).unwrap(),
+            dir.join("_sysconfigdata__linux_b.py")
+        );
+        assert!(locate(None, None).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn same_as_interpreter() {
+        let out = Command::new("python3")
+            .args([
+                "-c",
+                "import sysconfig; print(__import__(sysconfig._get_sysconfigdata_name()).__file__)",
+            ])
+            .output()
+            .unwrap();
+        let path = String::from_utf8(out.stdout).unwrap();
+        let ours = PythonConfigData::from_sysconfigdata(path.trim()).unwrap();
+        let live = PythonConfig::new().snapshot().unwrap();
+        for ((name, ours), (_, live)) in ours.fields().into_iter().zip(live.fields()) {
+            if name != "executable" && name != "libc" {
+                assert_eq!(ours, live, "{}", name);
+            }
+        }
+        assert!(ours.config_var("SOABI").is_some());
+    }
+}
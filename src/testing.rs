@@ -0,0 +1,40 @@
+//! Helpers for the unit tests
+
+use crate::{Commander, PyResult, PythonConfig, SysCommand, Version};
+
+/// Runs `python3`, but passes each query script through `rewrite` first
+///
+/// Tests use it to pretend that the interpreter is another
+/// implementation, platform, or version, or that it misbehaves.
+pub struct Rewrite<F>(F);
+
+impl<F: Fn(&str) -> String> Commander for Rewrite<F> {
+    fn commands(&self, args: &[&str]) -> PyResult<String> {
+        let script;
+        let mut args = args.to_vec();
+        if args.len() > 1 && args[0] == "-c" {
+            script = (self.0)(args[1]);
+            args[1] = &script;
+        }
+        SysCommand::new("python3").commands(&args)
+    }
+}
+
+/// Returns a `PythonConfig` that runs `python3`, after `rewrite` changes
+/// each query script
+pub fn rewritten<F: Fn(&str) -> String>(rewrite: F) -> PythonConfig<Rewrite<F>> {
+    PythonConfig::with_commander(Version::Three, Rewrite(rewrite))
+}
+
+/// Makes `script` see `vars`, a Python `dict` literal, in place of the
+/// config vars that they name
+pub fn override_vars(script: &str, vars: &str) -> String {
+    script.replacen(
+        "getvar = sysconfig.get_config_var\n",
+        &format!(
+            "getvar = lambda name: {}.get(name, sysconfig.get_config_var(name))\n",
+            vars
+        ),
+        1,
+    )
+}
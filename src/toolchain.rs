@@ -0,0 +1,79 @@
+//! The compiler and linker used to build Python
+
+/// The compiler and linker commands, and their flags, that built this
+/// Python distribution, from the `sysconfig` config vars
+///
+/// Compile extension modules with a compatible compiler; on macOS and
+/// Windows in particular, that may not be the compiler on your `PATH`.
+/// Each field is `None` if the distribution doesn't define the config
+/// var. Windows distributions define almost none of them.
+///
+/// See [`PythonConfig::build_toolchain`](struct.PythonConfig.html#method.build_toolchain).
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BuildToolchain {
+    /// The C compiler, and its flags, from `CC`, like `gcc -pthread`
+    pub cc: Option<String>,
+    /// The C++ compiler, and its flags, from `CXX`, like `g++ -pthread`
+    pub cxx: Option<String>,
+    /// The command that links extension modules, from `LDSHARED`, like
+    /// `gcc -pthread -shared`
+    pub ldshared: Option<String>,
+    /// The command that links extension modules while building Python
+    /// itself, from `BLDSHARED`
+    pub bldshared: Option<String>,
+    /// The archiver, from `AR`, like `ar`
+    pub ar: Option<String>,
+    /// The optimization and debugging flags, from `OPT`, like
+    /// `-DNDEBUG -g -fwrapv -O3 -Wall`
+    pub opt: Option<String>,
+    /// The linker flags given to `configure`, from `CONFIGURE_LDFLAGS`
+    pub configure_ldflags: Option<String>,
+}
+
+/// The config vars that describe the toolchain, which we pass to the
+/// `BUILD_TOOLCHAIN` query
+pub(crate) const CONFIG_VARS: &[&str] = &[
+    "CC",
+    "CXX",
+    "LDSHARED",
+    "BLDSHARED",
+    "AR",
+    "OPT",
+    "CONFIGURE_LDFLAGS",
+];
+
+/// Parses the `NAME=value` lines printed by the `BUILD_TOOLCHAIN` query.
+/// Undefined config vars have no line.
+pub(crate) fn parse(resp: &str) -> BuildToolchain {
+    let mut toolchain = BuildToolchain::default();
+    for (name, value) in resp.lines().filter_map(|line| line.split_once('=')) {
+        let field = match name {
+            "CC" => &mut toolchain.cc,
+            "CXX" => &mut toolchain.cxx,
+            "LDSHARED" => &mut toolchain.ldshared,
+            "BLDSHARED" => &mut toolchain.bldshared,
+            "AR" => &mut toolchain.ar,
+            "OPT" => &mut toolchain.opt,
+            "CONFIGURE_LDFLAGS" => &mut toolchain.configure_ldflags,
+            _ => continue,
+        };
+        *field = Some(value.to_owned());
+    }
+    toolchain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn defined_vars() {
+        let toolchain = parse("CC=gcc -pthread\nAR=ar\nOPT=-DNDEBUG -g -O3\nCONFIGURE_LDFLAGS=\n");
+        assert_eq!(toolchain.cc.as_deref(), Some("gcc -pthread"));
+        assert_eq!(toolchain.cxx, None);
+        assert_eq!(toolchain.ar.as_deref(), Some("ar"));
+        assert_eq!(toolchain.opt.as_deref(), Some("-DNDEBUG -g -O3"));
+        assert_eq!(toolchain.configure_ldflags.as_deref(), Some(""));
+    }
+}
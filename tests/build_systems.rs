@@ -0,0 +1,30 @@
+//! The tests show that `python3-config --cmake` and `--meson` print the
+//! same text as the library.
+
+use assert_cmd::prelude::*;
+use python_config::PythonConfig;
+use std::process::Command;
+
+fn stdout(flag: &str) -> String {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .arg(flag)
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn cmake() {
+    let snippet = stdout("--cmake");
+    assert_eq!(snippet, PythonConfig::new().cmake_snippet().unwrap());
+    assert!(snippet.contains("set(Python3_INCLUDE_DIRS "));
+}
+
+#[test]
+fn meson() {
+    let file = stdout("--meson");
+    assert_eq!(file, PythonConfig::new().meson_machine_file().unwrap());
+    assert!(file.contains("[binaries]\npython = '"));
+}
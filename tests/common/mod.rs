@@ -0,0 +1,36 @@
+//! Helpers shared by the CLI tests
+
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+/// Writes an executable script that runs `python3`, but reports
+/// `version`, like `(3, 7, 18)`, as the interpreter's version. Remove the
+/// file when you're done with it.
+#[cfg(unix)]
+pub fn pretend_version(name: &str, (major, minor, micro): (u32, u32, u32)) -> PathBuf {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = format!(
+        r#"#!/usr/bin/env python3
+import sys
+
+class VersionInfo(tuple):
+    major, minor, micro, releaselevel, serial = {major}, {minor}, {micro}, 'final', 0
+
+sys.version_info = VersionInfo(({major}, {minor}, {micro}, 'final', 0))
+code = sys.argv[2]
+sys.argv = ['-c'] + sys.argv[3:]
+exec(compile(code, '<string>', 'exec'), {{'__name__': '__main__'}})
+"#,
+        major = major,
+        minor = minor,
+        micro = micro
+    );
+    let path =
+        std::env::temp_dir().join(format!("python-config-rs-{}-{}", name, std::process::id()));
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
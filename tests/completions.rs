@@ -0,0 +1,114 @@
+//! The tests show that `python3-config --interpreter` selects the
+//! interpreter, and that `--generate-completions` prints a script for
+//! each supported shell.
+
+use assert_cmd::prelude::*;
+use python_config::PythonConfig;
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .args(args)
+        .env_remove("PYTHON_CONFIG_INTERPRETER")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn interpreter_flag() {
+    let executable = PythonConfig::new().executable().unwrap();
+    let expected = format!("{}\n", PythonConfig::new().prefix().unwrap());
+
+    let out = run(&["--interpreter", &executable, "--prefix"]);
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), expected);
+
+    let out = run(&["--prefix", &format!("--interpreter={}", executable)]);
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), expected);
+
+    let out = run(&[
+        "--interpreter",
+        "/this/path/does/not/exist/python3",
+        "--prefix",
+    ]);
+    assert_eq!(out.status.code(), Some(2));
+    assert_eq!(run(&["--prefix", "--interpreter"]).status.code(), Some(1));
+}
+
+#[test]
+fn completions() {
+    for shell in &["bash", "zsh", "fish"] {
+        let out = run(&["--generate-completions", shell]);
+        assert!(out.status.success(), "{}", shell);
+        let script = String::from_utf8(out.stdout).unwrap();
+        assert!(script.contains("python3-config"), "{}", shell);
+        assert!(script.contains("extension-suffix"), "{}", shell);
+        assert!(script.contains("interpreter"), "{}", shell);
+    }
+    assert_eq!(
+        run(&["--generate-completions", "powershell"]).status.code(),
+        Some(1)
+    );
+}
+
+#[test]
+fn completions_cover_every_flag() {
+    let flags = [
+        "--prefix",
+        "--exec-prefix",
+        "--includes",
+        "--libs",
+        "--cflags",
+        "--ldflags",
+        "--extension-suffix",
+        "--help",
+        "--abiflags",
+        "--configdir",
+        "--embed",
+        "--rpath",
+        "--filter",
+        "--json",
+        "--all",
+        "--quiet",
+        "--version",
+    ];
+    for shell in &["bash", "zsh", "fish"] {
+        let out = run(&["--generate-completions", shell]);
+        let script = String::from_utf8(out.stdout).unwrap();
+        for flag in &flags {
+            // fish names the long flag without its dashes
+            let word = if *shell == "fish" {
+                format!("-l {} ", flag.trim_start_matches('-'))
+            } else {
+                flag.to_string()
+            };
+            assert!(script.contains(&word), "{} lacks {}", shell, flag);
+        }
+        assert!(
+            script.contains("includes no-optimization raw"),
+            "{} lacks the --filter policies",
+            shell
+        );
+    }
+}
+
+#[test]
+fn help_without_interpreter() {
+    let out = run(&[
+        "--interpreter",
+        "/this/path/does/not/exist/python3",
+        "--help",
+    ]);
+    assert_eq!(out.status.code(), Some(0));
+
+    // We don't suggest --interpreter to those who passed it
+    let out = run(&[
+        "--interpreter",
+        "/this/path/does/not/exist/python3",
+        "--prefix",
+    ]);
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("python3-config: error:"), "{}", stderr);
+    assert!(!stderr.contains("pass --interpreter"), "{}", stderr);
+}
@@ -0,0 +1,61 @@
+//! The tests show that `python3-config diff` lines up the values of two
+//! interpreters, field by field.
+
+mod common;
+
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+/// Runs `python3-config diff left right`, returning the fields whose
+/// values differ
+fn differences(left: &str, right: &str) -> Vec<String> {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .args(["diff", left, right])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert!(lines.next().unwrap().starts_with("field "));
+    // The rule under the header shows the width of each column
+    let widths: Vec<usize> = lines.next().unwrap().split("-+-").map(str::len).collect();
+    lines
+        .filter_map(|line| {
+            let line = format!("{:width$}", line, width = widths.iter().sum::<usize>() + 6);
+            let mut start = 0;
+            let cells: Vec<&str> = widths
+                .iter()
+                .map(|width| {
+                    let cell = line[start..start + width].trim();
+                    start += width + 3;
+                    cell
+                })
+                .collect();
+            Some(cells[0].to_owned()).filter(|_| cells[1] != cells[2])
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+#[test]
+fn diff() {
+    let newer = common::pretend_version("py399", (3, 99, 0));
+    let newer = newer.to_str().unwrap();
+    let same = differences("python3", "python3");
+    let changed = differences("python3", newer);
+    std::fs::remove_file(newer).unwrap();
+    assert!(same.is_empty(), "{:?}", same);
+    // sysconfig names the include directory after the version, too
+    assert_eq!(changed, ["version", "includes", "cflags"]);
+}
+
+#[test]
+fn needs_two_interpreters() {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .args(["diff", "python3"])
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(1));
+}
@@ -0,0 +1,35 @@
+//! The tests show that `python3-config --doctor` reports on each check,
+//! and that its exit code describes the first failed check.
+
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn healthy() {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .arg("--doctor")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(out.status.success(), "{}", stdout);
+    assert!(stdout.starts_with("ok      interpreter: "));
+    assert!(stdout.contains("ok      headers: Python.h found in "));
+}
+
+#[test]
+fn missing_interpreter() {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .arg("--doctor")
+        .env(
+            "PYTHON_CONFIG_INTERPRETER",
+            "this-program-does-not-exist-python",
+        )
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(2));
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.starts_with("FAILED  interpreter: "));
+    assert!(stdout.contains("hint: "));
+}
@@ -0,0 +1,38 @@
+//! The tests show that `python3-config --dump` prints the same values as
+//! the individual flags, as shell assignments.
+
+use assert_cmd::prelude::*;
+use python_config::PythonConfig;
+use std::collections::HashMap;
+use std::process::Command;
+
+#[test]
+fn dump() {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .arg("--dump")
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let values: HashMap<&str, &str> = stdout
+        .lines()
+        .map(|line| {
+            let (key, value) = line.split_once('=').unwrap();
+            assert!(value.starts_with('\'') && value.ends_with('\''));
+            (key, &value[1..value.len() - 1])
+        })
+        .collect();
+    assert_eq!(values.len(), 12);
+
+    let cfg = PythonConfig::new();
+    assert_eq!(values["prefix"], cfg.prefix().unwrap());
+    assert_eq!(values["includes"], cfg.includes().unwrap());
+    assert_eq!(values["ext_suffix"], cfg.extension_suffix().unwrap());
+    assert_eq!(values["configdir"], cfg.config_dir().unwrap());
+    assert_eq!(
+        values["libdir"],
+        cfg.config_var("LIBDIR").unwrap().unwrap_or_default()
+    );
+    assert_eq!(values["version"], cfg.version_info().unwrap().to_string());
+}
@@ -15,9 +15,7 @@ macro_rules! check_python3_config {
         match process::Command::new("python3-config").output() {
             Ok(_) => (),
             Err(err) => {
-                if let io::ErrorKind::NotFound = err.kind() {
-                    ()
-                } else {
+                if err.kind() != io::ErrorKind::NotFound {
                     panic!("python3-config not found on this system")
                 }
             }
@@ -35,8 +33,8 @@ fn assert_resp_eq(left: &[u8], right: &[u8]) {
     use std::cmp;
     const VIEW_WINDOW: usize = 80;
 
-    let left = str::from_utf8(&left).unwrap();
-    let right = str::from_utf8(&right).unwrap();
+    let left = str::from_utf8(left).unwrap();
+    let right = str::from_utf8(right).unwrap();
 
     for ((li, lc), (ri, rc)) in left
         .char_indices()
@@ -118,7 +116,7 @@ fn unknown_flag() {
     assert_eq!(usage_flags(&rust.stderr), usage_flags(&py.stderr));
 }
 
-static FLAGS: &[&'static str] = &[
+static FLAGS: &[&str] = &[
     "--prefix",
     "--exec-prefix",
     "--includes",
@@ -128,6 +126,7 @@ static FLAGS: &[&'static str] = &[
     "--extension-suffix",
     "--abiflags",
     "--configdir",
+    "--embed",
 ];
 
 fn test_outputs_given(flags: &[&str]) {
@@ -190,6 +189,12 @@ fn ldflags() {
     test_outputs_given(&["--ldflags"]);
 }
 
+#[test]
+fn embed() {
+    test_outputs_given(&["--libs", "--embed", "--ldflags"]);
+    test_outputs_given(&["--embed"]);
+}
+
 #[test]
 fn extension_suffix() {
     test_outputs_given(&["--extension-suffix"]);
@@ -0,0 +1,86 @@
+//! The tests show that the `python3-config` binary exits with
+//! the documented codes when something goes wrong.
+
+mod common;
+
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn interpreter_not_found() {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .arg("--prefix")
+        .env("PATH", "")
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(2));
+    assert!(out.stdout.is_empty());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.starts_with("python3-config: error: "));
+    assert!(stderr.contains("PYTHON_CONFIG_INTERPRETER"));
+}
+
+#[test]
+fn quiet() {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .args(["--prefix", "--quiet"])
+        .env("PATH", "")
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(2));
+    assert!(out.stderr.is_empty());
+
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .args(["--prefix", "--quiet"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(!out.stdout.is_empty());
+}
+
+#[test]
+fn unknown_flag_is_usage_error() {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .arg("--what")
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(1));
+
+    // No mode ignores an unknown flag
+    for args in [
+        &["--version", "--bogus"][..],
+        &["--prefix", "--bogus-flag", "--dump"],
+        &["--cmake", "--bogus"],
+        &["--doctor", "--bogus"],
+        &["--list-interpreters", "--bogus"],
+        &["--generate-completions", "bash", "--bogus"],
+    ] {
+        let out = Command::cargo_bin("python3-config")
+            .expect("cannot find our Rust binary")
+            .args(args)
+            .output()
+            .unwrap();
+        assert_eq!(out.status.code(), Some(1), "{:?}", args);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn embed_needs_python38() {
+    let path = common::pretend_version("py37", (3, 7, 18));
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .args(["--ldflags", "--embed", "--interpreter"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(out.status.code(), Some(4));
+    assert!(out.stdout.is_empty());
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.starts_with("python3-config: error: --embed: Python 3.7.18"));
+}
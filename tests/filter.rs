@@ -0,0 +1,53 @@
+//! The tests show that `python3-config --filter` filters `--cflags` and
+//! `--ldflags`, and leaves the other values alone.
+
+use assert_cmd::prelude::*;
+use python_config::{FilterPolicy, PythonConfig};
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(args: &[&str]) -> String {
+    let out = run(args);
+    assert!(out.status.success());
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn filter() {
+    let cfg = PythonConfig::new();
+    let includes = cfg.cflags_filtered(FilterPolicy::IncludeOnly).unwrap();
+    assert_eq!(
+        stdout(&["--cflags", "--filter=includes"]),
+        format!("{}\n", includes.join(" "))
+    );
+    assert_eq!(
+        stdout(&["--filter", "raw", "--cflags"]),
+        format!("{}\n", cfg.cflags().unwrap())
+    );
+    let ldflags = cfg.ldflags_filtered(FilterPolicy::NoOptimization).unwrap();
+    assert_eq!(
+        stdout(&["--ldflags", "--filter=no-optimization"]),
+        format!("{}\n", ldflags.join(" "))
+    );
+    assert!(!ldflags.iter().any(|flag| flag.starts_with("-O")));
+    assert_eq!(
+        stdout(&["--prefix", "--filter=includes"]),
+        format!("{}\n", cfg.prefix().unwrap())
+    );
+
+    assert_eq!(run(&["--cflags", "--filter=fast"]).status.code(), Some(1));
+    assert_eq!(run(&["--cflags", "--filter"]).status.code(), Some(1));
+    assert_eq!(
+        run(&["--ldflags", "--embed", "--filter=includes"])
+            .status
+            .code(),
+        Some(1)
+    );
+}
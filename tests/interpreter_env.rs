@@ -0,0 +1,43 @@
+//! The tests show that the binary selects its interpreter through
+//! the documented environment variables, in order.
+
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+fn version(vars: &[(&str, &str)]) -> Option<i32> {
+    let mut cmd = Command::cargo_bin("python3-config").expect("cannot find our Rust binary");
+    cmd.arg("--version");
+    for var in python_config::INTERPRETER_ENV_VARS {
+        cmd.env_remove(var);
+    }
+    cmd.envs(vars.iter().cloned());
+    cmd.output().unwrap().status.code()
+}
+
+#[test]
+fn precedence() {
+    const MISSING: &str = "/this/path/does/not/exist/python3";
+    assert_eq!(version(&[]), Some(0));
+    assert_eq!(version(&[("PYTHON_SYS_EXECUTABLE", MISSING)]), Some(2));
+    assert_eq!(
+        version(&[
+            ("PYO3_PYTHON", "python3"),
+            ("PYTHON_SYS_EXECUTABLE", MISSING)
+        ]),
+        Some(0)
+    );
+    assert_eq!(
+        version(&[
+            ("PYTHON_CONFIG_INTERPRETER", MISSING),
+            ("PYO3_PYTHON", "python3")
+        ]),
+        Some(2)
+    );
+    assert_eq!(
+        version(&[
+            ("PYTHON_CONFIG_INTERPRETER", ""),
+            ("PYO3_PYTHON", "python3")
+        ]),
+        Some(0)
+    );
+}
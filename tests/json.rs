@@ -0,0 +1,50 @@
+//! The tests show that `--json` prints the same values as the
+//! plain flags, wrapped in a JSON object.
+
+use assert_cmd::prelude::*;
+use std::process::Command;
+use std::str;
+
+fn run(args: &[&str]) -> String {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    str::from_utf8(&out.stdout).unwrap().trim_end().to_owned()
+}
+
+#[test]
+fn requested_values() {
+    let prefix = run(&["--prefix"]);
+    let abiflags = run(&["--abiflags"]);
+    assert_eq!(
+        run(&["--json", "--prefix", "--abiflags", "--prefix"]),
+        format!(r#"{{"prefix": "{}", "abiflags": "{}"}}"#, prefix, abiflags)
+    );
+}
+
+#[test]
+fn all_values() {
+    let json = run(&["--all", "--json"]);
+    assert!(json.starts_with(r#"{"prefix": "#));
+    for key in &["exec-prefix", "libs", "ldflags", "configdir"] {
+        assert!(json.contains(&format!(r#""{}": "#, key)));
+    }
+    assert!(!json.contains(r#""help": "#));
+    assert_eq!(json.lines().count(), 1);
+}
+
+#[test]
+fn needs_a_value() {
+    for args in [&["--json"][..], &["--json", "--embed"]] {
+        let out = Command::cargo_bin("python3-config")
+            .expect("cannot find our Rust binary")
+            .args(args)
+            .output()
+            .unwrap();
+        assert_eq!(out.status.code(), Some(1), "{:?}", args);
+        assert!(!out.stdout.starts_with(b"{"));
+    }
+}
@@ -0,0 +1,24 @@
+//! The tests show that `python3-config --list-interpreters` lists the
+//! interpreter that we'd describe by default.
+
+use assert_cmd::prelude::*;
+use python_config::PythonConfig;
+use std::process::Command;
+
+#[test]
+fn lists_default_interpreter() {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .arg("--list-interpreters")
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.starts_with("interpreter "));
+
+    let prefix = PythonConfig::new().prefix().unwrap();
+    assert!(stdout
+        .lines()
+        .skip(2)
+        .any(|line| line.split(" | ").nth(2).map(str::trim) == Some(prefix.as_str())));
+}
@@ -0,0 +1,18 @@
+//! The tests show that `python3-config --ldflags --rpath` prints the
+//! flags from `PythonConfig::ldflags_with_rpath`.
+
+use assert_cmd::prelude::*;
+use python_config::PythonConfig;
+use std::process::Command;
+
+#[test]
+fn ldflags_rpath() {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .args(["--ldflags", "--rpath"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let expected = PythonConfig::new().ldflags_with_rpath().unwrap();
+    assert_eq!(String::from_utf8(out.stdout).unwrap().trim_end(), expected);
+}
@@ -0,0 +1,26 @@
+//! The tests show that `python3-config --version` prints the version and
+//! path of the interpreter that we describe.
+
+use assert_cmd::prelude::*;
+use python_config::PythonConfig;
+use std::process::Command;
+
+#[test]
+fn version() {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .arg("--version")
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+
+    let cfg = PythonConfig::new();
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        format!(
+            "Python {} ({})\n",
+            cfg.version_info().unwrap(),
+            cfg.executable().unwrap()
+        )
+    );
+}
@@ -0,0 +1,41 @@
+//! The tests show that `python3-config --write-pc` writes the files that
+//! `PythonConfig::pkg_config` generates.
+
+use assert_cmd::prelude::*;
+use python_config::PythonConfig;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn write_pc() {
+    let dir = std::env::temp_dir().join(format!("python-config-rs-pc-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .arg("--write-pc")
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+
+    let files = PythonConfig::new().pkg_config().unwrap();
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), files.len());
+    for (file, path) in files.iter().zip(stdout.lines()) {
+        assert!(path.ends_with(&file.name));
+        assert_eq!(fs::read_to_string(path).unwrap(), file.contents);
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn write_pc_needs_a_directory() {
+    let out = Command::cargo_bin("python3-config")
+        .expect("cannot find our Rust binary")
+        .arg("--write-pc")
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(1));
+}